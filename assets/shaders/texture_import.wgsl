@@ -1,8 +1,5 @@
-import "texture_helper"
-// import shaders::texture_helper::RED;
-// import super::random::rand::BLUE;
-// import random::noise::perlin::value_noise;
-// import package::random::rand::BLUE;
+#import "texture_helper"
+#import shaders::random::rand
 
 struct VertexInput {
     @location(0) position: vec3<f32>,
@@ -31,8 +28,9 @@ struct VertexOutput {
 
 @fragment
 fn fs_main(in: VertexOutput) -> @location(0) vec4<f32> {
-    if true {
-        return RED;
-    }
-    return textureSample(tex, samp, in.uv);
+#ifdef TINT_BLUE
+    return mix(textureSample(tex, samp, in.uv), BLUE, 0.5);
+#else
+    return mix(textureSample(tex, samp, in.uv), RED, 0.5);
+#endif
 }
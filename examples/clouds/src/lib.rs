@@ -677,7 +677,7 @@ impl App {
         let ms = time::frame_time(ctx);
         let mut metadata_file = fs::File::create(format!(
             "{}/image_{}_{}.info",
-            filesystem::tmp_path(),
+            filesystem::temporary_path(ctx).display(),
             self.cloud_resolution.x,
             self.cloud_resolution.y
         ))
@@ -713,7 +713,7 @@ impl App {
         image_buffer
             .save(format!(
                 "{}/image_{}_{}.png",
-                filesystem::tmp_path(),
+                filesystem::temporary_path(ctx).display(),
                 self.cloud_resolution.x,
                 self.cloud_resolution.y
             ))
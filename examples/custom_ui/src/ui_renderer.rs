@@ -657,14 +657,24 @@ pub struct FontLoader {
     settings: fontdue::FontSettings,
 }
 
+impl std::hash::Hash for FontLoader {
+    fn hash<H: std::hash::Hasher>(&self, state: &mut H) {
+        self.settings.collection_index.hash(state);
+        self.settings.scale.to_bits().hash(state);
+        self.settings.load_substitutions.hash(state);
+    }
+}
+
 impl AssetLoader for FontLoader {
     type Asset = Font;
     type Error = filesystem::LoadFileError;
+    type Settings = ();
 
     async fn load(
         &self,
         load_ctx: gbase::asset::LoadContext,
         path: &std::path::Path,
+        _settings: &Self::Settings,
     ) -> Result<Self::Asset, Self::Error> {
         let bytes = load_ctx.load_bytes(path).await?;
         let font = fontdue::Font::from_bytes(bytes, self.settings)
@@ -697,7 +707,7 @@ impl<'a> AssetConverter for FontAtlasConverter<'a> {
     ) -> gbase::asset::ConvertAssetStatus<Self::TargetAsset> {
         let source = match source.get(cache) {
             GetAssetResult::Loading => return ConvertAssetStatus::SourceLoading,
-            GetAssetResult::Failed => return ConvertAssetStatus::Failed,
+            GetAssetResult::Failed(_) => return ConvertAssetStatus::Failed,
             GetAssetResult::Success(source) => source,
         };
         let (lookup, texture) = create_font_atlas(
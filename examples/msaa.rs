@@ -0,0 +1,99 @@
+use gbase::{
+    filesystem,
+    render::{self, ArcRenderPipeline},
+    wgpu, CallbackResult, Callbacks, Context,
+};
+
+fn main() {
+    gbase::run::<App>();
+}
+
+/// Left triangle is drawn with 4x MSAA, the right one without for comparison
+pub struct App {
+    msaa_mesh: render::GpuMesh,
+    msaa_pipeline: ArcRenderPipeline,
+    mesh: render::GpuMesh,
+    pipeline: ArcRenderPipeline,
+}
+
+fn triangle(offset: f32) -> render::Mesh {
+    let mut mesh = render::Mesh::new(wgpu::PrimitiveTopology::TriangleStrip);
+    mesh.set_attribute(
+        render::VertexAttributeId::Position,
+        render::VertexAttributeValues::Float32x3(vec![
+            [offset - 0.4, -0.6, 0.0],
+            [offset + 0.4, -0.5, 0.0],
+            [offset - 0.3, 0.6, 0.0],
+        ]),
+    );
+    mesh
+}
+
+impl Callbacks for App {
+    fn init_ctx() -> gbase::ContextBuilder {
+        gbase::ContextBuilder::new().msaa(4)
+    }
+
+    fn new(ctx: &mut Context, _cache: &mut gbase::asset::AssetCache) -> Self {
+        let msaa_mesh = triangle(-0.5);
+        let mesh = triangle(0.5);
+
+        let shader_str = filesystem::load_s!("shaders/triangle.wgsl").unwrap();
+        let shader = render::ShaderBuilder::new(shader_str).build(ctx);
+        let pipeline_layout = render::PipelineLayoutBuilder::new().build(ctx);
+        let msaa_pipeline =
+            render::RenderPipelineBuilder::new(shader.clone(), pipeline_layout.clone())
+                .buffers(msaa_mesh.buffer_layout())
+                .single_target(render::ColorTargetState::from_current_screen(ctx))
+                .sample_count(render::msaa_samples(ctx))
+                .build(ctx);
+        let pipeline = render::RenderPipelineBuilder::new(shader, pipeline_layout)
+            .buffers(mesh.buffer_layout())
+            .single_target(render::ColorTargetState::from_current_screen(ctx))
+            .build(ctx);
+
+        Self {
+            msaa_mesh: msaa_mesh.to_gpu_mesh(ctx),
+            msaa_pipeline,
+            mesh: mesh.to_gpu_mesh(ctx),
+            pipeline,
+        }
+    }
+
+    fn render(
+        &mut self,
+        ctx: &mut Context,
+        _cache: &mut gbase::asset::AssetCache,
+        screen_view: &wgpu::TextureView,
+    ) -> CallbackResult {
+        let mut encoder = render::EncoderBuilder::new().build_new(ctx);
+
+        // multisampled and resolved into the screen
+        render::RenderPassBuilder::new()
+            .label("msaa")
+            .color_attachments(&[Some(
+                render::RenderPassColorAttachment::new(screen_view)
+                    .clear(wgpu::Color::BLACK)
+                    .msaa_screen(),
+            )])
+            .build_run(ctx, &mut encoder, |_ctx, mut render_pass| {
+                render_pass.set_pipeline(&self.msaa_pipeline);
+                self.msaa_mesh.bind_to_render_pass(&mut render_pass);
+                render_pass.draw(0..self.msaa_mesh.vertex_count, 0..1);
+            });
+
+        // drawn directly to the screen after the resolve
+        render::RenderPassBuilder::new()
+            .label("no msaa")
+            .color_attachments(&[Some(render::RenderPassColorAttachment::new(screen_view))])
+            .build_run(ctx, &mut encoder, |_ctx, mut render_pass| {
+                render_pass.set_pipeline(&self.pipeline);
+                self.mesh.bind_to_render_pass(&mut render_pass);
+                render_pass.draw(0..self.mesh.vertex_count, 0..1);
+            });
+
+        encoder.submit(ctx);
+
+        CallbackResult::Continue
+    }
+}
@@ -1,82 +1,18 @@
 use gbase::{
     asset::{
         self, AssetHandle, ImageGpuConverter, ImageLoader, MeshGpuConverter, ShaderGpuConverter,
+        ShaderLoader,
     },
-    filesystem,
     render::{self, ArcPipelineLayout, Image},
     wgpu::{self},
     CallbackResult, Callbacks, Context,
 };
-use std::collections::{HashSet, VecDeque};
 
 #[cfg_attr(target_arch = "wasm32", wasm_bindgen::prelude::wasm_bindgen)]
 pub fn run() {
     gbase::run::<App>();
 }
 
-#[derive(Debug, Clone)]
-pub struct ShaderExtendedLoader {}
-
-impl asset::AssetLoader for ShaderExtendedLoader {
-    type Asset = render::ShaderBuilder;
-    type Error = filesystem::LoadFileError;
-
-    async fn load(
-        &self,
-        load_ctx: asset::LoadContext,
-        path: &std::path::Path,
-    ) -> Result<Self::Asset, Self::Error> {
-        // pseduo code
-        // load file content of path (for this asset)
-        // for each import
-        //  load new asset
-        //  add new asset to dependencies (load ctx?)
-        //
-        // wait for dependencies? async? (need wait for single asset load?)
-        // combine str
-
-        let mut imported_paths = HashSet::new();
-        let mut import_path_stack = VecDeque::new();
-
-        let normalized_path = filesystem::normalize_path(path);
-        import_path_stack.push_front(normalized_path);
-
-        let mut output = String::new();
-        while let Some(path) = import_path_stack.pop_front() {
-            // only include once
-            if imported_paths.contains(&path) {
-                continue;
-            } else {
-                imported_paths.insert(path.clone());
-            }
-
-            let source_code = load_ctx.load_string(&path).await?;
-
-            // resolve imports
-            for line in source_code.lines() {
-                if let Some(rest) = line.trim().strip_prefix("import \"") {
-                    if let Some(import_relative_path) = rest.strip_suffix('"') {
-                        let parent_folder = path.parent().expect("could not get parent");
-                        let full_path = parent_folder
-                            .join(import_relative_path)
-                            .with_extension("wgsl");
-                        let normalized_full_path = filesystem::normalize_path(full_path);
-                        import_path_stack.push_front(normalized_full_path);
-                        continue;
-                    }
-                }
-
-                output.push_str(line);
-                output.push('\n');
-            }
-        }
-
-        // TODO: add defines
-
-        Ok(render::ShaderBuilder::new(output))
-    }
-}
-
 struct App {
     pipeline_layout: ArcPipelineLayout,
     bindgroup_layout: render::ArcBindGroupLayout,
@@ -111,13 +47,10 @@ impl Callbacks for App {
         let pipeline_layout = render::PipelineLayoutBuilder::new()
             .bind_groups(vec![bindgroup_layout.clone()])
             .build_uncached(ctx);
-        let shader_handle = asset::AssetBuilder::load(
-            cache,
-            "shaders/texture_import.wgsl",
-            ShaderExtendedLoader {},
-        )
-        .watch(ctx, cache)
-        .build(cache);
+        let shader_handle =
+            asset::AssetBuilder::load(cache, "shaders/texture_import.wgsl", ShaderLoader {})
+                .watch(ctx, cache)
+                .build(cache);
         let texture_handle =
             asset::AssetBuilder::load(cache, "textures/texture.jpeg", ImageLoader {})
                 .watch(ctx, cache)
@@ -198,7 +198,7 @@ impl<C: Callbacks> winit::application::ApplicationHandler<Context> for App<C> {
             let time = time::TimeContext::default();
             let filesystem = filesystem::FileSystemContext::new(&builder);
             let audio = audio::AudioContext::new();
-            let render = render::RenderContext::new(&builder, window).await;
+            let render = render::RenderContext::new(&builder, &filesystem, window).await;
             let random = random::RandomContext::new();
             let profile = profile::ProfileContext::new(&builder, &render.device, &render.queue);
 
@@ -425,10 +425,15 @@ fn update_and_render(
             format: Some(render::surface_format(ctx)), // TODO: add option to avoid gamma correction
             ..Default::default()
         });
+    ctx.render.screen_view = Some(view.clone());
     match callbacks.render(ctx, cache, &view) {
-        CallbackResult::Exit => return CallbackResult::Exit,
+        CallbackResult::Exit => {
+            ctx.render.screen_view = None;
+            return CallbackResult::Exit;
+        }
         CallbackResult::Continue => {}
     }
+    ctx.render.screen_view = None;
 
     #[cfg(feature = "egui")]
     match ui.render(ctx, cache, &view, |ctx, cache, egui_ctx| {
@@ -469,14 +474,13 @@ fn update_and_render(
     //
 
     cache.poll();
-    // TODO: dont do this every frame?
-    cache.clear_cpu_handles();
-    cache.clear_derived_handles();
 
     CallbackResult::Continue
 }
 
-fn shutdown(_ctx: &mut Context, _cache: &mut AssetCache) {}
+fn shutdown(ctx: &mut Context, _cache: &mut AssetCache) {
+    render::save_pipeline_cache(ctx);
+}
 
 //
 // Context builder
@@ -492,6 +496,8 @@ pub struct ContextBuilder {
     pub(crate) device_features: wgpu::Features,
     pub(crate) log_level: tracing::Level,
     pub(crate) vsync_enabled: bool, // can be set later
+    pub(crate) pipeline_cache_enabled: bool,
+    pub(crate) msaa_samples: u32,
 
     // profiling
     pub(crate) gpu_profiler_enabled: bool, // can be set later
@@ -501,6 +507,7 @@ pub struct ContextBuilder {
     // filesystem
     pub(crate) assets_path: PathBuf,
     pub(crate) temporary_path: PathBuf,
+    pub(crate) save_path: Option<PathBuf>,
 }
 
 #[allow(clippy::new_without_default)]
@@ -509,6 +516,8 @@ impl ContextBuilder {
         Self {
             log_level: tracing::Level::INFO,
             vsync_enabled: true,
+            pipeline_cache_enabled: true,
+            msaa_samples: 1,
             device_features: wgpu::Features::default(),
             window_attributes: WindowAttributes::default(),
 
@@ -518,6 +527,7 @@ impl ContextBuilder {
 
             assets_path: PathBuf::from("assets"),
             temporary_path: PathBuf::from("tmp"),
+            save_path: None,
         }
     }
 
@@ -541,6 +551,20 @@ impl ContextBuilder {
         self
     }
 
+    /// Persist compiled pipelines in the temporary folder where the backend supports it
+    pub fn pipeline_cache(mut self, enabled: bool) -> Self {
+        self.pipeline_cache_enabled = enabled;
+        self
+    }
+
+    /// Multisample the screen with `samples` per pixel, see [`render::msaa_samples`]
+    ///
+    /// Lowered to the highest count the surface format supports
+    pub fn msaa(mut self, samples: u32) -> Self {
+        self.msaa_samples = samples;
+        self
+    }
+
     pub fn device_features(mut self, device_features: wgpu::Features) -> Self {
         self.device_features = device_features;
         self
@@ -560,6 +584,14 @@ impl ContextBuilder {
         self.temporary_path = path.into();
         self
     }
+
+    /// Folder for save slots, defaults to a folder in the user data directory
+    ///
+    /// On wasm this is the key prefix in local storage
+    pub fn save_path(mut self, path: impl Into<PathBuf>) -> Self {
+        self.save_path = Some(path.into());
+        self
+    }
 }
 
 impl ContextBuilder {
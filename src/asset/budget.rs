@@ -0,0 +1,107 @@
+use super::RenderAssetKey;
+use rustc_hash::FxHashMap;
+
+/// GPU memory used by derived assets of one type
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct GpuMemoryUsage {
+    pub count: usize,
+    pub bytes: u64,
+}
+
+/// GPU memory held by the derived asset cache, see [`AssetCache::gpu_memory_stats`](super::AssetCache::gpu_memory_stats)
+#[derive(Debug, Clone, Default)]
+pub struct GpuMemoryStats {
+    pub budget: Option<u64>,
+    pub total: GpuMemoryUsage,
+    /// Usage by derived asset type name
+    pub types: FxHashMap<&'static str, GpuMemoryUsage>,
+}
+
+#[derive(Debug, Clone, Copy)]
+pub(crate) struct RenderAssetUsage {
+    pub(crate) type_name: &'static str,
+    pub(crate) bytes: u64,
+    pub(crate) last_used: u64,
+}
+
+pub(crate) fn gpu_memory_stats(
+    usage: &FxHashMap<RenderAssetKey, RenderAssetUsage>,
+    budget: Option<u64>,
+) -> GpuMemoryStats {
+    let mut stats = GpuMemoryStats {
+        budget,
+        ..Default::default()
+    };
+    for usage in usage.values() {
+        for total in [
+            &mut stats.total,
+            stats.types.entry(usage.type_name).or_default(),
+        ] {
+            total.count += 1;
+            total.bytes += usage.bytes;
+        }
+    }
+    stats
+}
+
+/// Derived assets to evict to get within budget, least recently used first
+///
+/// Assets used within the last `eviction_frames` frames are never evicted,
+/// so the budget can be exceeded if a single frame needs more
+pub(crate) fn select_evictions(
+    usage: &FxHashMap<RenderAssetKey, RenderAssetUsage>,
+    frame: u64,
+    eviction_frames: u64,
+    budget: u64,
+) -> Vec<RenderAssetKey> {
+    let mut total = usage.values().map(|usage| usage.bytes).sum::<u64>();
+    if total <= budget {
+        return Vec::new();
+    }
+
+    let mut candidates = usage
+        .iter()
+        .filter(|(_, usage)| usage.last_used + eviction_frames < frame)
+        .collect::<Vec<_>>();
+    candidates.sort_by_key(|(key, usage)| (usage.last_used, key.0));
+
+    let mut evictions = Vec::new();
+    for (key, usage) in candidates {
+        if total <= budget {
+            break;
+        }
+        total -= usage.bytes;
+        evictions.push(*key);
+    }
+    evictions
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{gpu_memory_stats, select_evictions, RenderAssetUsage};
+    use rustc_hash::FxHashMap;
+    use std::any::TypeId;
+
+    #[test]
+    fn test_lru_eviction() {
+        let key = |id| (id, TypeId::of::<u32>());
+        let usage = |bytes, last_used| RenderAssetUsage {
+            type_name: "texture",
+            bytes,
+            last_used,
+        };
+        let usage = FxHashMap::from_iter([
+            (key(0), usage(100, 5)),
+            (key(1), usage(100, 2)),
+            (key(2), usage(100, 9)),
+            (key(3), usage(100, 10)),
+        ]);
+
+        assert_eq!(gpu_memory_stats(&usage, None).types["texture"].bytes, 400);
+        assert!(select_evictions(&usage, 10, 2, 400).is_empty());
+        // oldest first, only as many as needed
+        assert_eq!(select_evictions(&usage, 10, 2, 300), vec![key(1)]);
+        // recently used are kept even over budget
+        assert_eq!(select_evictions(&usage, 10, 2, 0), vec![key(1), key(0)]);
+    }
+}
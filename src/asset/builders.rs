@@ -15,10 +15,19 @@ impl AssetBuilder {
         path: impl Into<PathBuf>,
         loader: T,
     ) -> LoadAssetBuilder<T> {
+        let path = path.into();
+
+        // reuse handle if already loaded with the same loader
+        let (handle, existing) = match cache.lookup_load(&path, &loader) {
+            Some(handle) => (handle, true),
+            None => (AssetHandle::new(cache.asset_handle_ctx()), false),
+        };
+
         LoadAssetBuilder::<T> {
             loader,
-            handle: AssetHandle::new(cache.asset_handle_ctx()),
-            path: path.into(),
+            handle,
+            path,
+            existing,
         }
     }
 }
@@ -45,13 +54,16 @@ pub struct LoadAssetBuilder<T: AssetLoader> {
     loader: T,
     handle: AssetHandle<T::Asset>,
     path: PathBuf,
+    existing: bool,
 }
 
 // TODO: can these just store bool instead?
 impl<T: AssetWriter> LoadAssetBuilder<T> {
     #[cfg(not(target_arch = "wasm32"))]
-    pub fn write(self, cache: &mut AssetCache) -> Self {
-        cache.ext.write::<T>(self.handle.clone(), &self.path);
+    pub fn write(self, ctx: &Context, cache: &mut AssetCache) -> Self {
+        cache
+            .ext
+            .write::<T>(&ctx.filesystem, self.handle.clone(), &self.path);
         self
     }
 }
@@ -72,6 +84,9 @@ impl<T: AssetLoader + 'static> LoadAssetBuilder<T> {
 
 impl<T: AssetLoader + 'static> LoadAssetBuilder<T> {
     pub fn build(self, cache: &mut AssetCache) -> AssetHandle<T::Asset> {
+        if self.existing {
+            return self.handle;
+        }
         cache.load::<T>(self.handle, &self.path, self.loader)
     }
 }
@@ -86,8 +101,10 @@ pub struct LoadSyncAssetBuilder<T: AssetLoader> {
 }
 #[cfg(not(target_arch = "wasm32"))]
 impl<T: AssetWriter> LoadSyncAssetBuilder<T> {
-    pub fn write(self, cache: &mut AssetCache) -> Self {
-        cache.ext.write::<T>(self.handle.clone(), &self.path);
+    pub fn write(self, ctx: &Context, cache: &mut AssetCache) -> Self {
+        cache
+            .ext
+            .write::<T>(&ctx.filesystem, self.handle.clone(), &self.path);
         self
     }
 }
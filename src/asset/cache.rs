@@ -1,30 +1,39 @@
 use super::{
-    Asset, AssetHandle, AssetLoader, AssetWriter, DynAsset, DynAssetHandle, DynAssetLoadFn,
-    DynAssetWriteFn, DynRenderAsset,
+    labeled_path, meta, split_label, Asset, AssetError, AssetEventKind, AssetEventQueue,
+    AssetHandle, AssetHandleInner, AssetId, AssetLabels, AssetLoadError, AssetLoadState,
+    AssetLoader, AssetWait, AssetWaiters, AssetWriter, DynAssetHandle, DynAssetLoadFn,
+    DynAssetRetryFn, DynAssetWriteFn, DynRenderAsset, GpuMemoryStats, LoadRequest, LoaderRegistry,
+    RenderAssetUsage, WeakAssetHandle,
 };
 use crate::{
     asset::{
-        self, AssetConverter, ConvertAssetStatus, DerivedAsset, DynLoader, GetAssetResult,
-        GetAssetResultMut, InsertAssetBuilder, LoadAssetBuilder, RenderAssetKey,
+        self, gpu_memory_stats, select_evictions, AssetConverter, AssetPathKey, ConvertAssetStatus,
+        DerivedAsset, DynLoader, GetAssetResult, GetAssetResultMut, InsertAssetBuilder,
+        LoadAssetBuilder, RenderAssetKey,
     },
     filesystem::{self, FileSystemContext},
     render::ArcHandle,
+    time::Instant,
     Context,
 };
-use rustc_hash::{FxHashMap, FxHashSet};
+use rustc_hash::{FxHashMap, FxHashSet, FxHasher};
 use std::{
     any::{Any, TypeId},
+    hash::Hasher,
+    marker::PhantomData,
     path::{Path, PathBuf},
-    sync::{Arc, Mutex},
+    sync::{Arc, Mutex, Weak},
     time::Duration,
 };
 
 pub enum LoadAssetResult {
     Loading,
-    Success(DynAsset),
-    Error,
+    Success(super::DynAsset),
+    Error(AssetLoadError),
 }
 
+static ASSET_NOT_FOUND: AssetError = AssetError::NotFound;
+
 pub enum ConvertAssetResult<T: DerivedAsset> {
     Loading,
     Success(ArcHandle<T>),
@@ -48,29 +57,64 @@ impl<T: DerivedAsset> ConvertAssetResult<T> {
 
 pub struct AssetCache {
     // cache
-    cache: FxHashMap<DynAssetHandle, LoadAssetResult>,
+    cache: FxHashMap<AssetId, LoadAssetResult>,
 
     // derived cache
     render_cache: FxHashMap<RenderAssetKey, DynRenderAsset>,
     render_cache_last_valid: FxHashMap<RenderAssetKey, DynRenderAsset>,
-    render_cache_invalidate_lookup: FxHashMap<DynAssetHandle, FxHashSet<TypeId>>,
+    render_cache_invalidate_lookup: FxHashMap<AssetId, FxHashSet<TypeId>>,
+
+    // gpu memory budget
+    render_cache_usage: FxHashMap<RenderAssetKey, RenderAssetUsage>,
+    frame: u64,
+    gpu_budget: Option<u64>,
+    gpu_eviction_frames: u64,
 
     // async loading
-    currently_loading: FxHashSet<DynAssetHandle>,
-    just_loaded: FxHashSet<DynAssetHandle>,
+    currently_loading: FxHashSet<AssetId>,
+    // unloaded while loading, their results are dropped
+    cancelled_loads: FxHashSet<AssetId>,
+    just_loaded: FxHashSet<AssetId>,
     load_sender: async_channel::Sender<(DynAssetHandle, LoadAssetResult)>,
     load_receiver: async_channel::Receiver<(DynAssetHandle, LoadAssetResult)>,
+    request_receiver: async_channel::Receiver<LoadRequest>,
+    waiters: AssetWaiters,
+
+    // errors
+    errors: FxHashMap<AssetId, AssetLoadError>,
+    retry_functions: FxHashMap<AssetId, DynAssetRetryFn>,
 
     // lookups
-    paths: FxHashMap<DynAssetHandle, PathBuf>,
-    loaders: FxHashMap<DynAssetHandle, DynLoader>,
+    paths: FxHashMap<AssetId, PathBuf>,
+    loaders: FxHashMap<AssetId, DynLoader>,
+
+    // path deduplication
+    path_lookup: FxHashMap<AssetPathKey, AssetId>,
+    path_type_lookup: FxHashMap<(PathBuf, TypeId), AssetId>,
+    path_keys: FxHashMap<AssetId, AssetPathKey>,
+
+    // unloading
+    drop_receiver: async_channel::Receiver<AssetId>,
+    pending_unload: FxHashMap<AssetId, Instant>,
+    keep_alive: FxHashSet<AssetId>,
+    unload_grace_period: Duration,
 
     // thread copyable state
     load_ctx: LoadContext,
     asset_handle_ctx: AssetHandleContext,
 
     // dependency tracking
-    dependencies: FxHashMap<DynAssetHandle, Vec<DynAssetHandle>>,
+    dependencies: FxHashMap<AssetId, Vec<AssetId>>,
+
+    // events
+    events: AssetEventQueue,
+
+    // loaders by extension
+    registry: LoaderRegistry,
+
+    // sub assets
+    labels: AssetLabels,
+    pending_labels: FxHashMap<AssetId, (DynAssetHandle, Vec<AssetId>)>,
 
     // hot reload context
     #[cfg(not(target_arch = "wasm32"))]
@@ -79,6 +123,10 @@ pub struct AssetCache {
 
 impl AssetCache {
     pub fn new(ctx: &Context) -> Self {
+        Self::with_filesystem(ctx.filesystem.clone())
+    }
+
+    fn with_filesystem(filesystem_ctx: FileSystemContext) -> Self {
         let (load_sender, load_receiver) = async_channel::unbounded();
 
         #[cfg(not(target_arch = "wasm32"))]
@@ -100,11 +148,28 @@ impl AssetCache {
             (reload_watcher, reload_receiver)
         };
 
-        let asset_handle_ctx = AssetHandleContext::new();
+        let (request_sender, request_receiver) = async_channel::unbounded();
+        let (drop_sender, drop_receiver) = async_channel::unbounded();
+        let asset_handle_ctx = AssetHandleContext::new(drop_sender);
+        let waiters = AssetWaiters::default();
+
+        let mut registry = LoaderRegistry::default();
+        registry.register::<asset::ImageLoader>(&["png", "jpg", "jpeg", "bmp", "tga"]);
+        registry.register::<asset::Ktx2Loader>(&["ktx2"]);
+        registry.register::<asset::DdsLoader>(&["dds"]);
+        registry.register::<asset::HdrImageLoader>(&["hdr", "exr"]);
+        registry.register::<asset::ShaderLoader>(&["wgsl"]);
+        #[cfg(feature = "wesl")]
+        registry.register::<asset::WeslShaderLoader>(&["wesl"]);
+
+        let labels = AssetLabels::default();
         let load_ctx = LoadContext::new(
             load_sender.clone(),
+            request_sender,
             asset_handle_ctx.clone(),
-            ctx.filesystem.clone(),
+            waiters.clone(),
+            labels.clone(),
+            filesystem_ctx,
         );
 
         Self {
@@ -114,24 +179,53 @@ impl AssetCache {
             render_cache_last_valid: FxHashMap::default(),
             render_cache_invalidate_lookup: FxHashMap::default(),
 
+            render_cache_usage: FxHashMap::default(),
+            frame: 0,
+            gpu_budget: None,
+            gpu_eviction_frames: 60,
+
             currently_loading: FxHashSet::default(),
+            cancelled_loads: FxHashSet::default(),
             just_loaded: FxHashSet::default(),
             load_sender,
             load_receiver,
+            request_receiver,
+            waiters,
+
+            errors: FxHashMap::default(),
+            retry_functions: FxHashMap::default(),
 
             paths: FxHashMap::default(),
             loaders: FxHashMap::default(),
 
+            path_lookup: FxHashMap::default(),
+            path_type_lookup: FxHashMap::default(),
+            path_keys: FxHashMap::default(),
+
+            drop_receiver,
+            pending_unload: FxHashMap::default(),
+            keep_alive: FxHashSet::default(),
+            unload_grace_period: Duration::ZERO,
+
             load_ctx,
             asset_handle_ctx,
 
             dependencies: FxHashMap::default(),
 
+            events: AssetEventQueue::default(),
+
+            registry,
+
+            labels,
+            pending_labels: FxHashMap::default(),
+
             #[cfg(not(target_arch = "wasm32"))]
             ext: AssetCacheExt {
                 handle_to_type: FxHashMap::default(),
 
                 reload_handles: FxHashMap::default(),
+                reload_asset_paths: FxHashMap::default(),
+                file_dependencies: FxHashMap::default(),
                 reload_functions: FxHashMap::default(),
                 reload_watcher,
                 reload_receiver,
@@ -151,8 +245,15 @@ impl AssetCache {
         &self.asset_handle_ctx
     }
 
-    pub async fn wait_for_handle(&self) {
-        // what can i do here
+    pub(crate) fn event_queue(&self) -> &AssetEventQueue {
+        &self.events
+    }
+
+    /// Wait for an asset to finish loading
+    ///
+    /// The returned future does not borrow the cache and resolves once the cache is polled
+    pub fn wait<T: 'static>(&self, handle: AssetHandle<T>) -> AssetWait<T> {
+        AssetWait::new(handle, self.waiters.clone())
     }
 
     //
@@ -162,24 +263,40 @@ impl AssetCache {
     pub fn insert<T: Asset + 'static>(&mut self, data: T) -> AssetHandle<T> {
         let handle = AssetHandle::<T>::new(&self.asset_handle_ctx);
         self.cache
-            .insert(handle.as_any(), LoadAssetResult::Success(Box::new(data)));
+            .insert(handle.id(), LoadAssetResult::Success(Box::new(data)));
+        self.waiters.set(handle.id(), AssetLoadState::Loaded);
+
+        self.events.track(
+            handle.id(),
+            TypeId::of::<T>(),
+            &self.asset_handle_ctx,
+            &self.paths,
+        );
+        self.events.send(
+            AssetEventKind::Loaded,
+            handle.id(),
+            &self.asset_handle_ctx,
+            &self.paths,
+        );
+
         handle
     }
 
     pub fn get<'a, T: Asset + 'static>(&'a self, handle: AssetHandle<T>) -> GetAssetResult<'a, T> {
-        let Some(asset) = self.cache.get(&handle.as_any()) else {
-            if self.currently_loading.contains(&handle.as_any()) {
+        let Some(asset) = self.cache.get(&handle.id()) else {
+            if self.currently_loading.contains(&handle.id()) {
                 return GetAssetResult::Loading;
             } else {
-                return GetAssetResult::Failed;
+                return GetAssetResult::Failed(&ASSET_NOT_FOUND);
             }
         };
 
-        let LoadAssetResult::Success(asset) = asset else {
-            return GetAssetResult::Loading;
+        let asset = match asset {
+            LoadAssetResult::Success(asset) => asset,
+            LoadAssetResult::Loading => return GetAssetResult::Loading,
+            LoadAssetResult::Error(err) => return GetAssetResult::Failed(err),
         };
 
-        // TODO: retuen errors as well?
         let asset = (asset.as_ref() as &dyn Any)
             .downcast_ref::<T>()
             .expect("could not downcast");
@@ -191,29 +308,53 @@ impl AssetCache {
         &'a mut self,
         handle: AssetHandle<T>,
     ) -> GetAssetResultMut<'a, T> {
-        let Some(asset) = self.cache.get_mut(&handle.as_any()) else {
-            return GetAssetResultMut::Failed;
+        let Some(asset) = self.cache.get_mut(&handle.id()) else {
+            return GetAssetResultMut::Failed(&ASSET_NOT_FOUND);
         };
 
-        let LoadAssetResult::Success(asset) = asset else {
-            return GetAssetResultMut::Loading;
+        let asset = match asset {
+            LoadAssetResult::Success(asset) => asset,
+            LoadAssetResult::Loading => return GetAssetResultMut::Loading,
+            LoadAssetResult::Error(err) => return GetAssetResultMut::Failed(err),
         };
 
         let asset = (asset.as_mut() as &mut dyn Any)
             .downcast_mut::<T>()
             .expect("could not downcast");
 
+        // copy on write, the asset no longer matches its source file
+        // so later loads of the same path should not share it
+        #[cfg(not(target_arch = "wasm32"))]
+        let written_to_disk = self.ext.write_handles.contains_key(&handle.id());
+        #[cfg(target_arch = "wasm32")]
+        let written_to_disk = false;
+        if !written_to_disk {
+            detach_path(
+                &mut self.path_lookup,
+                &mut self.path_type_lookup,
+                &mut self.path_keys,
+                handle.id(),
+            );
+        }
+
         // invalidate gpu cache
         invalidate_render_cache(
             &mut self.render_cache,
             &self.render_cache_invalidate_lookup,
-            handle.as_any(),
+            handle.id(),
+        );
+
+        self.events.send(
+            AssetEventKind::Modified,
+            handle.id(),
+            &self.asset_handle_ctx,
+            &self.paths,
         );
 
         // set dirty
         // TODO: move inside
         #[cfg(not(target_arch = "wasm32"))]
-        self.ext.write_dirty.insert(handle.clone().as_any());
+        self.ext.write_dirty.insert(handle.id());
 
         GetAssetResultMut::Loaded(asset)
     }
@@ -234,6 +375,177 @@ impl AssetCache {
         asset::AssetBuilder::load(self, path, loader)
     }
 
+    //
+    // Loader registry
+    //
+
+    /// Process assets before loading them and keep the results in `dir`
+    ///
+    /// Processed assets are reused until their source, settings, dependencies or processor change
+    #[cfg(not(target_arch = "wasm32"))]
+    pub fn process_assets(&mut self, dir: impl Into<PathBuf>, processors: asset::AssetProcessors) {
+        self.load_ctx.processed = Some(asset::ProcessedAssetCache::new(dir, processors));
+    }
+
+    /// Register a loader for files with any of the extensions
+    ///
+    /// Loaders producing the same asset type for an extension are replaced
+    pub fn register_loader<L: AssetLoader + Default + 'static>(&mut self, extensions: &[&str]) {
+        self.registry.register::<L>(extensions);
+    }
+
+    /// Load an asset of type `T` using a loader registered for the extension of the path
+    ///
+    /// Sub assets can be loaded with a label, e.g. `models/ship.glb#Mesh/Hull`
+    pub fn load_path<T: Asset>(
+        &mut self,
+        path: impl Into<PathBuf>,
+    ) -> Result<AssetHandle<T>, AssetError> {
+        let path = path.into();
+        if let (parent, Some(label)) = split_label(&path) {
+            return self.load_labeled(&parent, &label);
+        }
+
+        let loader = self.registry.find(&path, Some(TypeId::of::<T>()))?;
+        Ok((loader.load)(self, path).cast())
+    }
+
+    /// Load an asset using the most recently registered loader for the extension of the path
+    ///
+    /// The returned handle can be downcast once the asset type is known
+    pub fn load_untyped(&mut self, path: impl Into<PathBuf>) -> Result<DynAssetHandle, AssetError> {
+        let path = path.into();
+        if let (_, Some(_)) = split_label(&path) {
+            return Err(AssetError::UntypedLabel(path));
+        }
+
+        let loader = self.registry.find(&path, None)?;
+        Ok((loader.load)(self, path))
+    }
+
+    // load the parent asset and resolve the label once it finished loading
+    fn load_labeled<T: Asset>(
+        &mut self,
+        path: &Path,
+        label: &str,
+    ) -> Result<AssetHandle<T>, AssetError> {
+        let labeled = labeled_path(path, label);
+        let asset_handle_ctx = &self.asset_handle_ctx;
+        let id = self
+            .labels
+            .get_or_insert(labeled.clone(), TypeId::of::<T>(), || {
+                asset_handle_ctx.next_id()
+            });
+        let handle = self.asset_handle_ctx.upgrade::<T>(id);
+
+        // already inserted by the parent or waiting for it
+        if self.cache.contains_key(&id) || self.currently_loading.contains(&id) {
+            return Ok(handle);
+        }
+
+        let parent = match self.load_untyped(path) {
+            Ok(parent) => parent,
+            Err(err) => {
+                self.labels.remove(id);
+                return Err(err);
+            }
+        };
+
+        self.paths.insert(id, labeled);
+        self.events
+            .track(id, TypeId::of::<T>(), &self.asset_handle_ctx, &self.paths);
+        self.currently_loading.insert(id);
+        self.waiters.set(id, AssetLoadState::Loading);
+        self.pending_labels
+            .entry(parent.id())
+            .or_insert_with(|| (parent.clone(), Vec::new()))
+            .1
+            .push(id);
+
+        // parent might already be loaded
+        if self.load_state(parent.clone()) != AssetLoadState::Loading {
+            self.resolve_labels(parent.id());
+        }
+
+        Ok(handle)
+    }
+
+    // fail labels the parent did not insert
+    fn resolve_labels(&mut self, parent: AssetId) {
+        let Some((_, pending)) = self.pending_labels.remove(&parent) else {
+            return;
+        };
+
+        // sub assets are sent before their parent finishes, but might still be queued
+        self.poll_loaded();
+
+        for id in pending {
+            if let Some(LoadAssetResult::Success(_)) = self.cache.get(&id) {
+                continue;
+            }
+            let path = self.paths.get(&id).cloned().unwrap_or_default();
+            let err = AssetLoadError::new(Some(&path), AssetError::LabelNotFound(path.clone()));
+            self.finish_load(id, LoadAssetResult::Error(err));
+        }
+    }
+
+    /// Get the type of an asset tracked by the cache
+    pub fn asset_type_id(&self, id: AssetId) -> Option<TypeId> {
+        self.events.asset_type(id)
+    }
+
+    //
+    // Path lookups
+    //
+
+    /// Get the handle of an asset already loaded from the same path with an identical loader
+    pub fn lookup_load<T: AssetLoader + 'static>(
+        &self,
+        path: &Path,
+        loader: &T,
+    ) -> Option<AssetHandle<T::Asset>> {
+        self.path_lookup
+            .get(&asset_path_key(path, loader))
+            .map(|id| self.asset_handle_ctx.upgrade(*id))
+    }
+
+    /// Get the handle of an asset of type `T` loaded from a path
+    ///
+    /// If the path was loaded with multiple loaders, the most recent load is returned
+    pub fn handle_for_path<T: Asset>(&self, path: impl AsRef<Path>) -> Option<AssetHandle<T>> {
+        let path = filesystem::normalize_path(path);
+        self.path_type_lookup
+            .get(&(path, TypeId::of::<T>()))
+            .map(|id| self.asset_handle_ctx.upgrade(*id))
+    }
+
+    /// Get the path an asset was loaded from
+    pub fn path_of<T: Asset>(&self, handle: AssetHandle<T>) -> Option<&Path> {
+        self.paths.get(&handle.id()).map(|path| path.as_path())
+    }
+
+    fn register_path<T: AssetLoader + 'static>(
+        &mut self,
+        handle: &AssetHandle<T::Asset>,
+        path: &Path,
+        loader: &T,
+    ) {
+        let key = asset_path_key(path, loader);
+
+        // handle might have been loaded from another path before
+        detach_path(
+            &mut self.path_lookup,
+            &mut self.path_type_lookup,
+            &mut self.path_keys,
+            handle.id(),
+        );
+
+        self.path_lookup.insert(key.clone(), handle.id());
+        self.path_type_lookup
+            .insert((key.0.clone(), TypeId::of::<T::Asset>()), handle.id());
+        self.path_keys.insert(handle.id(), key);
+    }
+
     //
     // Reloading
     //
@@ -246,11 +558,19 @@ impl AssetCache {
     ) -> AssetHandle<T::Asset> {
         let path = path.to_path_buf();
 
-        self.paths.insert(handle.as_any(), path.clone());
-        self.loaders
-            .insert(handle.as_any(), Box::new(loader.clone()));
+        self.register_path(&handle, &path, &loader);
+        self.paths.insert(handle.id(), path.clone());
+        self.loaders.insert(handle.id(), Box::new(loader.clone()));
+        self.events.track(
+            handle.id(),
+            TypeId::of::<T::Asset>(),
+            &self.asset_handle_ctx,
+            &self.paths,
+        );
 
-        self.currently_loading.insert(handle.as_any());
+        self.currently_loading.insert(handle.id());
+        self.waiters.set(handle.id(), AssetLoadState::Loading);
+        self.insert_retry_function::<T>(handle.id());
 
         let path_clone = path.clone();
         let handle_clone = handle.clone();
@@ -263,46 +583,20 @@ impl AssetCache {
         #[cfg(not(target_arch = "wasm32"))]
         std::thread::spawn(move || {
             pollster::block_on(async {
-                let data = loader.load(load_context, &path_clone).await;
-
-                match data {
-                    Ok(asset) => loaded_sender_clone
-                        .try_send((
-                            handle_clone.as_any(),
-                            LoadAssetResult::Success(Box::new(asset)),
-                        ))
-                        .expect("could not send"),
-                    Err(err) => {
-                        // TODO: doesnt include asset base
-                        tracing::error!("error loading asset {:?}: {}", path, err);
-                        loaded_sender_clone
-                            .try_send((handle_clone.as_any(), LoadAssetResult::Error))
-                            .expect("could not send");
-                    }
-                }
+                let result = meta::load_asset(&loader, load_context, &path_clone).await;
+                loaded_sender_clone
+                    .try_send((handle_clone.as_any(), result))
+                    .expect("could not send");
             })
         });
 
         #[cfg(target_arch = "wasm32")]
         wasm_bindgen_futures::spawn_local(async move {
-            let data = loader.load(load_context, &path_clone).await;
-
-            match data {
-                Ok(asset) => loaded_sender_clone
-                    .send((
-                        handle_clone.as_any(),
-                        LoadAssetResult::Success(Box::new(asset)),
-                    ))
-                    .await
-                    .expect("could not send"),
-                Err(err) => {
-                    tracing::error!("error loading asset {:?}: {}", path, err);
-                    loaded_sender_clone
-                        .send((handle_clone.as_any(), LoadAssetResult::Error))
-                        .await
-                        .expect("could not send")
-                }
-            }
+            let result = meta::load_asset(&loader, load_context, &path_clone).await;
+            loaded_sender_clone
+                .send((handle_clone.as_any(), result))
+                .await
+                .expect("could not send");
         });
 
         handle
@@ -317,25 +611,25 @@ impl AssetCache {
     ) -> AssetHandle<T::Asset> {
         let path = path.to_path_buf();
 
-        self.paths.insert(handle.as_any(), path.clone());
-        self.loaders
-            .insert(handle.as_any(), Box::new(loader.clone()));
-
-        // load sync
-        let data = pollster::block_on(loader.load(self.load_ctx.clone(), &path));
+        self.register_path(&handle, &path, &loader);
+        self.paths.insert(handle.id(), path.clone());
+        self.loaders.insert(handle.id(), Box::new(loader.clone()));
+        self.events.track(
+            handle.id(),
+            TypeId::of::<T::Asset>(),
+            &self.asset_handle_ctx,
+            &self.paths,
+        );
 
-        match data {
-            Ok(asset) => {
-                self.cache
-                    .insert(handle.as_any(), LoadAssetResult::Success(Box::new(asset)));
-            }
-            Err(err) => {
-                tracing::error!("error loading asset {:?}: {}", path, err);
-                self.cache.insert(handle.as_any(), LoadAssetResult::Error);
-            }
-        }
+        self.insert_retry_function::<T>(handle.id());
 
-        self.just_loaded.insert(handle.as_any());
+        // load sync
+        let result = pollster::block_on(meta::load_asset(
+            &loader,
+            self.load_ctx.synchronous(),
+            &path,
+        ));
+        self.finish_load(handle.id(), result);
 
         handle
     }
@@ -359,6 +653,51 @@ impl AssetCache {
         }
     }
 
+    //
+    // Errors
+    //
+
+    /// Get the error of the last failed load of an asset
+    ///
+    /// Cleared once the asset loads successfully
+    pub fn error<T: 'static>(&self, handle: AssetHandle<T>) -> Option<&AssetLoadError> {
+        self.errors.get(&handle.id())
+    }
+
+    /// All assets whose last load failed
+    pub fn errors(&self) -> impl Iterator<Item = (AssetId, &AssetLoadError)> {
+        self.errors.iter().map(|(id, err)| (*id, err))
+    }
+
+    /// Load an asset again using the last path and loader
+    pub fn retry<T: 'static>(&mut self, handle: AssetHandle<T>) {
+        let Some(retry_fn) = self.retry_functions.remove(&handle.id()) else {
+            tracing::warn!("trying to retry asset which was never loaded from a path");
+            return;
+        };
+        retry_fn(self);
+    }
+
+    /// Load all assets whose last load failed again
+    pub fn retry_failed(&mut self) {
+        let failed = self.errors.keys().copied().collect::<Vec<_>>();
+        for id in failed {
+            if let Some(retry_fn) = self.retry_functions.remove(&id) {
+                retry_fn(self);
+            }
+        }
+    }
+
+    fn insert_retry_function<T: AssetLoader + 'static>(&mut self, id: AssetId) {
+        self.retry_functions.insert(
+            id,
+            Box::new(move |cache: &mut AssetCache| {
+                let handle = cache.asset_handle_ctx.upgrade::<T::Asset>(id);
+                cache.reload::<T>(handle);
+            }),
+        );
+    }
+
     // TODO: this probably should not use a generic, it should store the type some other way if
     // possible, maybe the handle can store the loader type
     fn get_handle_path_and_loader<T: AssetLoader + 'static>(
@@ -366,14 +705,14 @@ impl AssetCache {
         handle: AssetHandle<T::Asset>,
     ) -> Option<(PathBuf, T)> {
         // load prev path
-        let Some(path) = self.paths.get(&handle.as_any()) else {
+        let Some(path) = self.paths.get(&handle.id()) else {
             tracing::warn!("trying to reload asset without previous path");
             return None;
         };
         let path = path.clone();
 
         // load prev loader
-        let Some(loader) = self.loaders.get(&handle.as_any()) else {
+        let Some(loader) = self.loaders.get(&handle.id()) else {
             tracing::warn!("trying to reload asset without previous path");
             return None;
         };
@@ -397,13 +736,44 @@ impl AssetCache {
     // Render assets
     //
 
+    /// Limit GPU memory used by derived assets, `None` for no limit
+    ///
+    /// Derived assets not converted within the eviction period are dropped, least recently used first,
+    /// and converted again on their next access
+    pub fn set_gpu_budget(&mut self, bytes: Option<u64>) {
+        self.gpu_budget = bytes;
+    }
+
+    /// Frames a derived asset is kept after its last conversion before it can be evicted
+    pub fn set_gpu_eviction_frames(&mut self, frames: u64) {
+        self.gpu_eviction_frames = frames;
+    }
+
+    /// GPU memory used by derived assets per type
+    pub fn gpu_memory_stats(&self) -> GpuMemoryStats {
+        gpu_memory_stats(&self.render_cache_usage, self.gpu_budget)
+    }
+
+    fn evict_render_asset(&mut self, key: RenderAssetKey) {
+        let (id, render_type) = key;
+        self.render_cache.remove(&key);
+        self.render_cache_last_valid.remove(&key);
+        self.render_cache_usage.remove(&key);
+        if let Some(render_types) = self.render_cache_invalidate_lookup.get_mut(&id) {
+            render_types.remove(&render_type);
+            if render_types.is_empty() {
+                self.render_cache_invalidate_lookup.remove(&id);
+            }
+        }
+    }
+
     pub fn convert<G: AssetConverter>(
         &mut self,
         ctx: &mut Context,
         handle: AssetHandle<G::SourceAsset>,
         converter: G,
     ) -> ConvertAssetResult<G::TargetAsset> {
-        let key = (handle.clone().as_any(), TypeId::of::<G::TargetAsset>());
+        let key = (handle.id(), TypeId::of::<G::TargetAsset>());
 
         let render_asset_handle = match self.render_cache.get(&key) {
             Some(render_asset_handle) => render_asset_handle.clone(),
@@ -417,7 +787,7 @@ impl AssetCache {
                             tracing::warn!(
                                 "assert conversion failed, using last valid version instead"
                             );
-                            self.render_cache.insert(key.clone(), asset_handle.clone());
+                            self.render_cache.insert(key, asset_handle.clone());
                             asset_handle.clone()
                         }
                         None => {
@@ -429,17 +799,25 @@ impl AssetCache {
                     },
 
                     ConvertAssetStatus::Success(render_asset_handle) => {
+                        self.render_cache_usage.insert(
+                            key,
+                            RenderAssetUsage {
+                                type_name: std::any::type_name::<G::TargetAsset>(),
+                                bytes: render_asset_handle.gpu_memory_size(),
+                                last_used: self.frame,
+                            },
+                        );
                         let render_asset_any_handle =
                             ArcHandle::new(ctx, render_asset_handle).upcast();
                         // actual cache
                         self.render_cache
-                            .insert(key.clone(), render_asset_any_handle.clone());
+                            .insert(key, render_asset_any_handle.clone());
                         // last valid cache
                         self.render_cache_last_valid
-                            .insert(key.clone(), render_asset_any_handle.clone());
+                            .insert(key, render_asset_any_handle.clone());
                         // invalidate lookup
                         self.render_cache_invalidate_lookup
-                            .entry(handle.as_any())
+                            .entry(handle.id())
                             .or_default()
                             .insert(TypeId::of::<G::TargetAsset>());
 
@@ -449,6 +827,10 @@ impl AssetCache {
             }
         };
 
+        if let Some(usage) = self.render_cache_usage.get_mut(&key) {
+            usage.last_used = self.frame;
+        }
+
         let typed_handle = render_asset_handle
             .downcast::<G::TargetAsset>()
             .expect("could not downcast render any handle");
@@ -462,54 +844,222 @@ impl AssetCache {
 
     pub fn poll(&mut self) {
         self.just_loaded.clear();
+        self.events.update();
 
         #[cfg(not(target_arch = "wasm32"))]
         {
-            self.ext.poll_reload(
-                &mut self.cache,
-                &mut self.render_cache,
-                &self.render_cache_invalidate_lookup,
-                &mut self.just_loaded,
-                self.load_ctx.clone(),
-            );
+            let reloaded = self.ext.poll_reload(self.load_ctx.synchronous());
+            for (id, result) in reloaded {
+                self.finish_load(id, result);
+            }
             self.ext.poll_write(&mut self.cache);
         }
 
+        self.poll_requests();
         self.poll_loaded();
+        self.poll_unload();
+        self.poll_gpu_budget();
+
+        self.frame += 1;
+    }
+
+    // start loads requested from inside loaders
+    pub fn poll_requests(&mut self) {
+        while let Ok(request) = self.request_receiver.try_recv() {
+            request(self);
+        }
     }
 
     // check if any files completed loading and update cache and invalidate render cache
     pub fn poll_loaded(&mut self) {
         while let Ok((handle, asset)) = self.load_receiver.try_recv() {
-            if let LoadAssetResult::Success(_) = &asset {
-                self.currently_loading.remove(&handle.as_any());
-                self.just_loaded.insert(handle.clone());
+            self.finish_load(handle.id(), asset);
+        }
+    }
+
+    // insert a finished load in the cache and invalidate render cache
+    fn finish_load(&mut self, id: AssetId, result: LoadAssetResult) {
+        // the load task keeps running when its asset is unloaded
+        if self.cancelled_loads.remove(&id) {
+            return;
+        }
+        self.currently_loading.remove(&id);
+
+        match result {
+            LoadAssetResult::Success(asset) => {
+                if let Some(path) = self.labels.path(id) {
+                    self.paths.entry(id).or_insert(path);
+                }
+
+                // assets inserted from loaders are not tracked yet
+                let ty = (asset.as_ref() as &dyn Any).type_id();
+                self.events
+                    .track(id, ty, &self.asset_handle_ctx, &self.paths);
+
+                let kind = match self.cache.get(&id) {
+                    Some(LoadAssetResult::Success(_)) => AssetEventKind::Modified,
+                    _ => AssetEventKind::Loaded,
+                };
+                self.events
+                    .send(kind, id, &self.asset_handle_ctx, &self.paths);
+
+                self.errors.remove(&id);
+                self.cache.insert(id, LoadAssetResult::Success(asset));
+                self.just_loaded.insert(id);
+                self.waiters.set(id, AssetLoadState::Loaded);
+            }
+            LoadAssetResult::Error(err) => {
+                tracing::error!("{}", err);
+                self.errors.insert(id, err.clone());
+                self.events.send(
+                    AssetEventKind::Failed,
+                    id,
+                    &self.asset_handle_ctx,
+                    &self.paths,
+                );
+
+                // keep last good version, e.g. when a hot reload fails
+                if let Some(LoadAssetResult::Success(_)) = self.cache.get(&id) {
+                    tracing::warn!("keeping last loaded version of asset");
+                    self.waiters.set(id, AssetLoadState::Loaded);
+                    return;
+                }
+                self.waiters.set(id, AssetLoadState::Failed);
+                self.cache.insert(id, LoadAssetResult::Error(err));
+            }
+            LoadAssetResult::Loading => {
+                self.cache.insert(id, LoadAssetResult::Loading);
             }
+        }
+
+        // TODO: can i just place this success and remove caching kinda?
+
+        // invalidate render cache
+        invalidate_render_cache(
+            &mut self.render_cache,
+            &self.render_cache_invalidate_lookup,
+            id,
+        );
 
-            // insert in cache
-            self.cache.insert(handle.clone(), asset);
+        self.resolve_labels(id);
+    }
 
-            // TODO: can i just place this success and remove caching kinda?
+    // evict least recently used derived assets while over the gpu budget
+    pub fn poll_gpu_budget(&mut self) {
+        let Some(budget) = self.gpu_budget else {
+            return;
+        };
 
-            // invalidate render cache
-            invalidate_render_cache(
-                &mut self.render_cache,
-                &self.render_cache_invalidate_lookup,
-                handle.clone(),
-            );
+        let evictions = select_evictions(
+            &self.render_cache_usage,
+            self.frame,
+            self.gpu_eviction_frames,
+            budget,
+        );
+        for key in evictions {
+            self.evict_render_asset(key);
         }
     }
 
-    pub fn clear_cpu_handles(&mut self) {
-        // TODO: clear all other stuff related to this handle
-        self.cache
-            .retain(|handle, _| Arc::strong_count(&handle.id) > 1);
+    // unload assets whose last strong handle was dropped
+    pub fn poll_unload(&mut self) {
+        let now = Instant::now();
+
+        while let Ok(id) = self.drop_receiver.try_recv() {
+            // handle might have been revived through a path lookup
+            if self.asset_handle_ctx.is_alive(id) || self.keep_alive.contains(&id) {
+                continue;
+            }
+            self.pending_unload
+                .insert(id, now + self.unload_grace_period);
+        }
+
+        let expired = self
+            .pending_unload
+            .iter()
+            .filter(|(_, deadline)| **deadline <= now)
+            .map(|(id, _)| *id)
+            .collect::<Vec<_>>();
+        for id in expired {
+            self.pending_unload.remove(&id);
+            if self.asset_handle_ctx.is_alive(id) || self.keep_alive.contains(&id) {
+                continue;
+            }
+            self.unload_id(id);
+        }
+    }
+
+    //
+    // Unloading
+    //
+
+    /// Unload an asset and all its derived assets
+    ///
+    /// Remaining handles to the asset will return `Failed` on access
+    pub fn unload<T: Asset>(&mut self, handle: AssetHandle<T>) {
+        self.unload_id(handle.id());
+    }
+
+    /// Keep an asset loaded even when all strong handles are dropped
+    pub fn keep_alive<T: Asset>(&mut self, handle: AssetHandle<T>, keep_alive: bool) {
+        if keep_alive {
+            self.keep_alive.insert(handle.id());
+        } else {
+            self.keep_alive.remove(&handle.id());
+
+            // might not have any strong handles left
+            if !self.asset_handle_ctx.is_alive(handle.id()) {
+                self.pending_unload
+                    .insert(handle.id(), Instant::now() + self.unload_grace_period);
+            }
+        }
+    }
+
+    /// Time to keep an asset loaded after its last strong handle was dropped
+    ///
+    /// Loading the same path again within this period reuses the loaded asset
+    pub fn set_unload_grace_period(&mut self, grace_period: Duration) {
+        self.unload_grace_period = grace_period;
     }
 
-    pub fn clear_derived_handles(&mut self) {
-        // TODO: clear all other stuff related to this handle
-        self.render_cache
-            .retain(|(handle, _), _| Arc::strong_count(&handle.id) > 1);
+    fn unload_id(&mut self, id: AssetId) {
+        self.resolve_labels(id);
+        self.labels.remove(id);
+        self.events.untrack(id, &self.asset_handle_ctx, &self.paths);
+        self.cache.remove(&id);
+
+        // derived assets
+        if let Some(render_types) = self.render_cache_invalidate_lookup.remove(&id) {
+            for render_type in render_types {
+                self.render_cache.remove(&(id, render_type));
+                self.render_cache_last_valid.remove(&(id, render_type));
+                self.render_cache_usage.remove(&(id, render_type));
+            }
+        }
+
+        if self.currently_loading.remove(&id) {
+            self.cancelled_loads.insert(id);
+        }
+        self.just_loaded.remove(&id);
+        self.paths.remove(&id);
+        self.loaders.remove(&id);
+        detach_path(
+            &mut self.path_lookup,
+            &mut self.path_type_lookup,
+            &mut self.path_keys,
+            id,
+        );
+        self.dependencies.remove(&id);
+        self.keep_alive.remove(&id);
+        self.pending_unload.remove(&id);
+
+        #[cfg(not(target_arch = "wasm32"))]
+        self.ext.remove(id);
+
+        self.errors.remove(&id);
+        self.retry_functions.remove(&id);
+        self.waiters.remove(id);
+        self.asset_handle_ctx.remove(id);
     }
 
     pub fn all_loaded(&self) -> bool {
@@ -517,15 +1067,29 @@ impl AssetCache {
     }
 
     pub fn handle_just_loaded<T: Asset>(&self, handle: AssetHandle<T>) -> bool {
-        self.just_loaded.contains(&handle.as_any())
+        self.just_loaded.contains(&handle.id())
     }
     pub fn handle_loaded<T: Asset>(&self, handle: AssetHandle<T>) -> bool {
-        !self.currently_loading.contains(&handle.as_any())
+        !self.currently_loading.contains(&handle.id())
+    }
+
+    /// Get the load state of an asset
+    ///
+    /// Assets which are not tracked by the cache, e.g. unloaded ones, count as failed
+    pub fn load_state<T: 'static>(&self, handle: AssetHandle<T>) -> AssetLoadState {
+        if self.currently_loading.contains(&handle.id()) {
+            return AssetLoadState::Loading;
+        }
+        match self.cache.get(&handle.id()) {
+            Some(LoadAssetResult::Success(_)) => AssetLoadState::Loaded,
+            Some(LoadAssetResult::Loading) => AssetLoadState::Loading,
+            Some(LoadAssetResult::Error(_)) | None => AssetLoadState::Failed,
+        }
     }
 
     pub fn handles_loaded(&self, handles: impl IntoIterator<Item = DynAssetHandle>) -> bool {
         for handle in handles {
-            if !self.currently_loading.contains(&handle) {
+            if self.currently_loading.contains(&handle.id()) {
                 return false;
             }
         }
@@ -540,37 +1104,126 @@ impl AssetCache {
 #[derive(Debug, Clone)]
 pub struct LoadContext {
     sender: async_channel::Sender<(DynAssetHandle, LoadAssetResult)>,
+    request_sender: async_channel::Sender<LoadRequest>,
     asset_handle_ctx: AssetHandleContext,
+    waiters: AssetWaiters,
+    labels: AssetLabels,
     filesystem_ctx: filesystem::FileSystemContext,
+    synchronous: bool,
+    path: Option<PathBuf>,
+    #[cfg(not(target_arch = "wasm32"))]
+    processed: Option<asset::ProcessedAssetCache>,
 }
 
 impl LoadContext {
-    pub fn new(
+    pub(crate) fn new(
         sender: async_channel::Sender<(DynAssetHandle, LoadAssetResult)>,
+        request_sender: async_channel::Sender<LoadRequest>,
         asset_handle_ctx: AssetHandleContext,
+        waiters: AssetWaiters,
+        labels: AssetLabels,
         filesystem_ctx: filesystem::FileSystemContext,
     ) -> Self {
         Self {
             sender,
+            request_sender,
             asset_handle_ctx,
+            waiters,
+            labels,
             filesystem_ctx,
+            synchronous: false,
+            path: None,
+            #[cfg(not(target_arch = "wasm32"))]
+            processed: None,
+        }
+    }
+
+    /// Context for loads which block the thread owning the cache
+    pub(crate) fn synchronous(&self) -> Self {
+        Self {
+            synchronous: true,
+            ..self.clone()
         }
     }
 
+    /// Context for loading the asset at a path
+    pub(crate) fn with_path(&self, path: &Path) -> Self {
+        Self {
+            path: Some(path.to_path_buf()),
+            ..self.clone()
+        }
+    }
+
+    /// Path of the asset being loaded
+    pub fn path(&self) -> Option<&Path> {
+        self.path.as_deref()
+    }
+
     pub fn insert<T: Asset>(&self, value: T) -> AssetHandle<T> {
         let handle = AssetHandle::<T>::new(&self.asset_handle_ctx);
+        self.insert_handle(handle, value)
+    }
+
+    /// Insert a sub asset which can be loaded with `<path>#<label>`
+    ///
+    /// Labels keep their handle when the asset is reloaded
+    pub fn insert_labeled<T: Asset>(&self, label: &str, value: T) -> AssetHandle<T> {
+        let Some(path) = &self.path else {
+            return self.insert(value);
+        };
+
+        let id = self
+            .labels
+            .get_or_insert(labeled_path(path, label), TypeId::of::<T>(), || {
+                self.asset_handle_ctx.next_id()
+            });
+        self.insert_handle(self.asset_handle_ctx.upgrade(id), value)
+    }
+
+    fn insert_handle<T: Asset>(&self, handle: AssetHandle<T>, value: T) -> AssetHandle<T> {
         self.sender
             .try_send((handle.as_any(), LoadAssetResult::Success(Box::new(value))))
             .expect("could not send asset handle");
         handle
     }
 
-    // TODO: ref vs owned self?
-    // pub async fn load<T: LoadableAsset>(&self, path: impl Into<PathBuf>) -> AssetHandle<T> {
-    //     let path = path.into();
-    //     let value = T::load(self.clone(), &path).await;
-    //     self.insert(value)
-    // }
+    /// Load another asset and wait for it to finish loading
+    ///
+    /// Goes through the cache, so loads of the same path are shared
+    pub async fn load<L: AssetLoader + 'static>(
+        &self,
+        path: impl Into<PathBuf>,
+        loader: L,
+    ) -> Result<AssetHandle<L::Asset>, AssetError> {
+        let path = path.into();
+
+        // the cache can not be polled while blocked on this load, so load inline instead
+        if self.synchronous {
+            // keep the typed error so callers can downcast it
+            let settings = meta::load_settings(self, &path, &loader)
+                .await
+                .map_err(|err| AssetLoadError::new(Some(&path), err))?;
+            let asset = loader
+                .load(self.with_path(&path), &path, &settings)
+                .await
+                .map_err(|err| AssetLoadError::new(Some(&path), err))?;
+            return Ok(self.insert(asset));
+        }
+
+        let (handle_sender, handle_receiver) = async_channel::bounded(1);
+        self.request_sender
+            .try_send(Box::new(move |cache: &mut AssetCache| {
+                let handle = asset::AssetBuilder::load(cache, path, loader).build(cache);
+                let _ = handle_sender.try_send(handle);
+            }))
+            .expect("could not send load request");
+
+        let handle = handle_receiver
+            .recv()
+            .await
+            .map_err(|_| AssetError::LoadFailed)?;
+        AssetWait::new(handle, self.waiters.clone()).await
+    }
 
     pub async fn load_bytes(
         &self,
@@ -584,6 +1237,63 @@ impl LoadContext {
     ) -> Result<String, filesystem::LoadFileError> {
         self.filesystem_ctx.load_asset_string(path).await
     }
+
+    /// Reload the asset being loaded when another file changes, e.g. a shader import
+    ///
+    /// Only has an effect for watched assets
+    pub fn add_file_dependency(&self, dependency: impl AsRef<Path>) {
+        #[cfg(not(target_arch = "wasm32"))]
+        if let Some(path) = self.path.clone() {
+            let dependency = dependency.as_ref().to_path_buf();
+            self.request_sender
+                .try_send(Box::new(move |cache: &mut AssetCache| {
+                    let filesystem_ctx = cache.load_ctx.filesystem_ctx.clone();
+                    cache
+                        .ext
+                        .add_file_dependency(&filesystem_ctx, &path, &dependency);
+                }))
+                .expect("could not send load request");
+        }
+        #[cfg(target_arch = "wasm32")]
+        let _ = dependency;
+    }
+
+    /// Load the processed data of an asset
+    ///
+    /// Processes the source if processing is enabled and a processor is registered for it.
+    /// Otherwise loads `<path>.processed` written by `asset_cooker`, or the source if there is none
+    pub async fn load_processed(
+        &self,
+        path: impl AsRef<Path>,
+    ) -> Result<Vec<u8>, filesystem::LoadFileError> {
+        let path = path.as_ref();
+
+        #[cfg(not(target_arch = "wasm32"))]
+        if let Some(processed) = &self.processed {
+            let bytes = self.load_bytes(path).await?;
+            let meta = match self.load_string(meta::meta_path(path)).await {
+                Ok(meta) => Some(meta),
+                Err(filesystem::LoadFileError::FileNotFound) => None,
+                Err(err) => return Err(err),
+            };
+            let read = |dependency: &Path| pollster::block_on(self.load_bytes(dependency));
+            let Some(result) = processed.process(path, &bytes, meta.as_deref(), &read) else {
+                return Ok(bytes);
+            };
+
+            let processed =
+                result.map_err(|err| filesystem::LoadFileError::Other(Box::new(err)))?;
+            for dependency in &processed.dependencies {
+                self.add_file_dependency(dependency);
+            }
+            return Ok(processed.data);
+        }
+
+        match self.load_bytes(asset::processed_path(path)).await {
+            Err(filesystem::LoadFileError::FileNotFound) => self.load_bytes(path).await,
+            result => result,
+        }
+    }
 }
 
 //
@@ -592,10 +1302,12 @@ impl LoadContext {
 
 #[cfg(not(target_arch = "wasm32"))]
 pub struct AssetCacheExt {
-    handle_to_type: FxHashMap<DynAssetHandle, TypeId>,
+    handle_to_type: FxHashMap<AssetId, TypeId>,
 
     // reloading
-    reload_handles: FxHashMap<PathBuf, Vec<DynAssetHandle>>,
+    reload_handles: FxHashMap<PathBuf, Vec<AssetId>>,
+    reload_asset_paths: FxHashMap<AssetId, PathBuf>,
+    file_dependencies: FxHashMap<PathBuf, FxHashSet<PathBuf>>,
     // TODO: still needed?
     reload_functions: FxHashMap<TypeId, DynAssetLoadFn>,
     reload_watcher:
@@ -603,20 +1315,24 @@ pub struct AssetCacheExt {
     reload_receiver: async_channel::Receiver<PathBuf>,
 
     // writing
-    write_handles: FxHashMap<DynAssetHandle, PathBuf>,
+    write_handles: FxHashMap<AssetId, PathBuf>,
     write_functions: FxHashMap<TypeId, DynAssetWriteFn>,
-    write_dirty: FxHashSet<DynAssetHandle>,
+    write_dirty: FxHashSet<AssetId>,
 }
 
 #[derive(Debug, Clone)]
 pub struct AssetHandleContext {
     id: Arc<Mutex<u64>>,
+    drop_sender: async_channel::Sender<AssetId>,
+    live: Arc<Mutex<FxHashMap<AssetId, Weak<AssetHandleInner>>>>,
 }
 
 impl AssetHandleContext {
-    fn new() -> Self {
+    pub(crate) fn new(drop_sender: async_channel::Sender<AssetId>) -> Self {
         Self {
             id: Arc::new(Mutex::new(0)),
+            drop_sender,
+            live: Arc::new(Mutex::new(FxHashMap::default())),
         }
     }
     pub fn next_id(&self) -> u64 {
@@ -625,6 +1341,56 @@ impl AssetHandleContext {
         *id_guard += 1;
         id
     }
+
+    pub(crate) fn next_handle<T: 'static>(&self) -> AssetHandle<T> {
+        self.upgrade(self.next_id())
+    }
+
+    /// Get a strong handle for an id, reviving it if all strong handles were dropped
+    pub(crate) fn upgrade<T: 'static>(&self, id: AssetId) -> AssetHandle<T> {
+        let mut live = self
+            .live
+            .lock()
+            .expect("could not unlock asset handle lock");
+        let inner = match live.get(&id).and_then(|weak| weak.upgrade()) {
+            Some(inner) => inner,
+            None => {
+                let inner = Arc::new(AssetHandleInner::new(id, self.drop_sender.clone()));
+                live.insert(id, Arc::downgrade(&inner));
+                inner
+            }
+        };
+        AssetHandle {
+            inner,
+            ty: PhantomData,
+        }
+    }
+
+    pub(crate) fn weak<T: 'static>(&self, id: AssetId) -> WeakAssetHandle<T> {
+        let live = self
+            .live
+            .lock()
+            .expect("could not unlock asset handle lock");
+        WeakAssetHandle::new(id, live.get(&id).cloned().unwrap_or_default())
+    }
+
+    pub(crate) fn is_alive(&self, id: AssetId) -> bool {
+        let live = self
+            .live
+            .lock()
+            .expect("could not unlock asset handle lock");
+        live.get(&id).is_some_and(|weak| weak.strong_count() > 0)
+    }
+
+    fn remove(&self, id: AssetId) {
+        let mut live = self
+            .live
+            .lock()
+            .expect("could not unlock asset handle lock");
+        if live.get(&id).is_some_and(|weak| weak.strong_count() == 0) {
+            live.remove(&id);
+        }
+    }
 }
 
 // TODO: check if canoicalize is necessary
@@ -641,53 +1407,125 @@ impl AssetCacheExt {
         loader: T,
     ) {
         // need absolute path since notify uses them
-        let asset_path = filesystem_ctx.format_asset_path(path);
+        let Some(asset_path) = filesystem_ctx.vfs().disk_path(path) else {
+            tracing::warn!("could not watch {}: not mounted from disk", path.display());
+            return;
+        };
 
         // start watching path
-        self.reload_watcher
-            .watcher()
-            .watch(
-                &asset_path,
-                notify_debouncer_mini::notify::RecursiveMode::Recursive, // TODO: non recursive?
-            )
-            .unwrap_or_else(|err| panic!("could not watch {}: {:?}", asset_path.display(), err));
+        self.watch_disk_path(&asset_path, handle.id());
+
+        // watch the folder since the .meta file might not exist yet
+        if let Some(parent) = asset_path.parent() {
+            self.reload_watcher
+                .watcher()
+                .watch(
+                    parent,
+                    notify_debouncer_mini::notify::RecursiveMode::NonRecursive,
+                )
+                .unwrap_or_else(|err| panic!("could not watch {}: {:?}", parent.display(), err));
+        }
+        let handles = self
+            .reload_handles
+            .entry(meta::meta_path(&asset_path))
+            .or_default();
+        if !handles.contains(&handle.id()) {
+            handles.push(handle.id());
+        }
 
-        // map path to handle
-        let handles = self.reload_handles.entry(asset_path).or_default();
-        handles.push(handle.as_any());
+        // changes to the asset, its .meta file or dependencies reload the asset path
+        let path = filesystem::normalize_path(path);
+        self.reload_asset_paths.insert(handle.id(), path.clone());
+        let dependencies = self
+            .file_dependencies
+            .get(&path)
+            .cloned()
+            .unwrap_or_default();
+        for dependency in dependencies {
+            self.watch_dependency(filesystem_ctx, handle.id(), &dependency);
+        }
 
         // map handle to type
         self.handle_to_type
-            .insert(handle.as_any(), TypeId::of::<T::Asset>());
+            .insert(handle.id(), TypeId::of::<T::Asset>());
 
         // store reload function
         self.reload_functions
             .entry(TypeId::of::<T::Asset>())
             .or_insert_with(|| {
                 Box::new(move |load_ctx, path| {
-                    let result = pollster::block_on(loader.clone().load(load_ctx, path));
-                    match result {
-                        Ok(asset) => LoadAssetResult::Success(Box::new(asset)),
-                        Err(err) => {
-                            tracing::error!("could not reload asset {:?}: {}", path, err);
-                            LoadAssetResult::Error
-                        }
-                    }
+                    pollster::block_on(meta::load_asset(&loader, load_ctx, path))
                 })
             });
     }
 
+    fn watch_disk_path(&mut self, disk_path: &Path, id: AssetId) {
+        self.reload_watcher
+            .watcher()
+            .watch(
+                disk_path,
+                notify_debouncer_mini::notify::RecursiveMode::Recursive, // TODO: non recursive?
+            )
+            .unwrap_or_else(|err| panic!("could not watch {}: {:?}", disk_path.display(), err));
+
+        let handles = self
+            .reload_handles
+            .entry(disk_path.to_path_buf())
+            .or_default();
+        if !handles.contains(&id) {
+            handles.push(id);
+        }
+    }
+
+    fn watch_dependency(&mut self, filesystem_ctx: &FileSystemContext, id: AssetId, path: &Path) {
+        // e.g. embedded files, which can not change
+        if let Some(disk_path) = filesystem_ctx.vfs().disk_path(path) {
+            self.watch_disk_path(&disk_path, id);
+        }
+    }
+
+    /// Reload assets at `path` when the file at `dependency` changes
+    pub(crate) fn add_file_dependency(
+        &mut self,
+        filesystem_ctx: &FileSystemContext,
+        path: &Path,
+        dependency: &Path,
+    ) {
+        let path = filesystem::normalize_path(path);
+        let dependency = filesystem::normalize_path(dependency);
+        let dependencies = self.file_dependencies.entry(path.clone()).or_default();
+        if !dependencies.insert(dependency.clone()) {
+            return;
+        }
+
+        let watched = self
+            .reload_asset_paths
+            .iter()
+            .filter(|(_, asset_path)| **asset_path == path)
+            .map(|(id, _)| *id)
+            .collect::<Vec<_>>();
+        for id in watched {
+            self.watch_dependency(filesystem_ctx, id, &dependency);
+        }
+    }
+
     /// Register asset for being written to disk when updated
     #[cfg(not(target_arch = "wasm32"))]
-    pub fn write<T: AssetWriter>(&mut self, handle: AssetHandle<T::Asset>, path: &Path) {
-        let path = path.to_path_buf();
+    pub fn write<T: AssetWriter>(
+        &mut self,
+        filesystem_ctx: &FileSystemContext,
+        handle: AssetHandle<T::Asset>,
+        path: &Path,
+    ) {
+        // writers get the path on disk
+        let path = filesystem_ctx.asset_disk_path(path);
 
         // map handle to path
-        self.write_handles.insert(handle.as_any(), path.clone());
+        self.write_handles.insert(handle.id(), path.clone());
 
         // map handle to type
         self.handle_to_type
-            .insert(handle.as_any(), TypeId::of::<T::Asset>());
+            .insert(handle.id(), TypeId::of::<T::Asset>());
 
         // TODO:
         // store reload function
@@ -703,8 +1541,20 @@ impl AssetCacheExt {
             });
     }
 
+    // stop tracking an unloaded asset
+    fn remove(&mut self, id: AssetId) {
+        self.handle_to_type.remove(&id);
+        self.reload_handles.retain(|_, handles| {
+            handles.retain(|handle| *handle != id);
+            !handles.is_empty()
+        });
+        self.reload_asset_paths.remove(&id);
+        self.write_handles.remove(&id);
+        self.write_dirty.remove(&id);
+    }
+
     // check if any files are scheduled for writing to disk
-    pub fn poll_write(&mut self, cache: &mut FxHashMap<DynAssetHandle, LoadAssetResult>) {
+    pub fn poll_write(&mut self, cache: &mut FxHashMap<AssetId, LoadAssetResult>) {
         for handle in self.write_dirty.drain() {
             if let Some(path) = self.write_handles.get(&handle) {
                 let asset = cache.get_mut(&handle);
@@ -731,24 +1581,21 @@ impl AssetCacheExt {
         }
     }
 
-    // checks if any files changed and spawns a thread which reloads the data
-    pub fn poll_reload(
-        &mut self,
-        cache: &mut FxHashMap<DynAssetHandle, LoadAssetResult>,
-        render_cache: &mut FxHashMap<RenderAssetKey, DynRenderAsset>,
-        render_cache_invalidate_lookup: &FxHashMap<DynAssetHandle, FxHashSet<TypeId>>,
-        just_loaded: &mut FxHashSet<DynAssetHandle>,
-        load_ctx: LoadContext,
-    ) {
+    // checks if any files changed and reloads the data, results are inserted by the cache
+    pub(crate) fn poll_reload(&mut self, load_ctx: LoadContext) -> Vec<(AssetId, LoadAssetResult)> {
+        let mut reloaded = Vec::new();
         while let Ok(path) = self.reload_receiver.try_recv() {
             if let Some(handles) = self.reload_handles.get_mut(&path) {
-                for handle in handles {
-                    // println!("reload {:?}", path);
-                    just_loaded.insert(handle.clone());
+                for handle in handles.iter().copied() {
+                    // reload through the vfs, .meta and dependency changes reload the asset itself
+                    let Some(path) = self.reload_asset_paths.get(&handle) else {
+                        continue;
+                    };
 
+                    // println!("reload {:?}", path);
                     let ty_id = self
                         .handle_to_type
-                        .get(handle)
+                        .get(&handle)
                         .expect("could not get type id from asset handle");
 
                     // load new fn
@@ -756,31 +1603,48 @@ impl AssetCacheExt {
                         .reload_functions
                         .get(ty_id)
                         .expect("could not get loader fn");
-                    let asset = loader_fn(load_ctx.clone(), &path);
-
-                    // insert into cache
-                    cache.insert(handle.clone(), asset);
-
-                    // invalidate render cache
-                    invalidate_render_cache(
-                        render_cache,
-                        render_cache_invalidate_lookup,
-                        handle.as_any(),
-                    );
+                    let asset = loader_fn(load_ctx.clone(), path);
+                    reloaded.push((handle, asset));
                 }
             }
         }
+        reloaded
     }
 }
 
+fn asset_path_key<T: AssetLoader + 'static>(path: &Path, loader: &T) -> AssetPathKey {
+    let mut hasher = FxHasher::default();
+    loader.hash(&mut hasher);
+    (
+        filesystem::normalize_path(path),
+        TypeId::of::<T>(),
+        hasher.finish(),
+    )
+}
+
+fn detach_path(
+    path_lookup: &mut FxHashMap<AssetPathKey, AssetId>,
+    path_type_lookup: &mut FxHashMap<(PathBuf, TypeId), AssetId>,
+    path_keys: &mut FxHashMap<AssetId, AssetPathKey>,
+    id: AssetId,
+) {
+    let Some(key) = path_keys.remove(&id) else {
+        return;
+    };
+    if path_lookup.get(&key) == Some(&id) {
+        path_lookup.remove(&key);
+    }
+    path_type_lookup.retain(|_, value| *value != id);
+}
+
 pub fn invalidate_render_cache(
     render_cache: &mut FxHashMap<RenderAssetKey, DynRenderAsset>,
-    render_cache_invalidate_lookup: &FxHashMap<DynAssetHandle, FxHashSet<TypeId>>,
-    handle: DynAssetHandle,
+    render_cache_invalidate_lookup: &FxHashMap<AssetId, FxHashSet<TypeId>>,
+    id: AssetId,
 ) {
-    if let Some(render_types) = render_cache_invalidate_lookup.get(&handle) {
+    if let Some(render_types) = render_cache_invalidate_lookup.get(&id) {
         for render_type in render_types {
-            render_cache.remove(&(handle.clone(), *render_type));
+            render_cache.remove(&(id, *render_type));
         }
     }
 }
@@ -807,3 +1671,107 @@ impl<T: Asset + 'static> AssetHandle<T> {
         cache.convert(ctx, self.clone(), converter)
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::{AssetCache, AssetHandleContext, GetAssetResult, LoadAssetResult};
+    use crate::{
+        asset::{Asset, AssetError, AssetHandle, AssetLoadState, AssetWait, AssetWaiters},
+        filesystem::FileSystemContext,
+        ContextBuilder,
+    };
+
+    #[test]
+    fn test_last_strong_handle_drop_notifies() {
+        let (sender, receiver) = async_channel::unbounded();
+        let ctx = AssetHandleContext::new(sender);
+
+        let handle = AssetHandle::<u32>::new(&ctx);
+        let weak = handle.downgrade();
+        let clone = handle.clone();
+
+        drop(handle);
+        assert!(receiver.try_recv().is_err());
+        assert!(ctx.is_alive(clone.id()));
+
+        drop(clone);
+        assert_eq!(receiver.try_recv().ok(), Some(weak.id()));
+        assert!(!ctx.is_alive(weak.id()));
+        assert!(weak.upgrade().is_none());
+    }
+
+    #[test]
+    fn test_revive_dropped_handle() {
+        let (sender, receiver) = async_channel::unbounded();
+        let ctx = AssetHandleContext::new(sender);
+
+        let handle = AssetHandle::<u32>::new(&ctx);
+        let id = handle.id();
+        drop(handle);
+        assert_eq!(receiver.try_recv().ok(), Some(id));
+
+        let revived = ctx.upgrade::<u32>(id);
+        assert_eq!(revived.id(), id);
+        assert!(ctx.is_alive(id));
+    }
+
+    #[test]
+    fn test_wait_resolves_on_load() {
+        let (sender, _receiver) = async_channel::unbounded();
+        let ctx = AssetHandleContext::new(sender);
+        let waiters = AssetWaiters::default();
+
+        let handle = AssetHandle::<u32>::new(&ctx);
+        waiters.set(handle.id(), AssetLoadState::Loading);
+
+        let wait = AssetWait::new(handle.clone(), waiters.clone());
+        let id = handle.id();
+        let thread_waiters = waiters.clone();
+        std::thread::spawn(move || thread_waiters.set(id, AssetLoadState::Loaded));
+
+        assert_eq!(pollster::block_on(wait).ok(), Some(handle));
+    }
+
+    #[test]
+    fn test_wait_reports_failure() {
+        let (sender, _receiver) = async_channel::unbounded();
+        let ctx = AssetHandleContext::new(sender);
+        let waiters = AssetWaiters::default();
+
+        let handle = AssetHandle::<u32>::new(&ctx);
+        waiters.set(handle.id(), AssetLoadState::Failed);
+
+        let result = pollster::block_on(AssetWait::new(handle, waiters));
+        assert!(matches!(result, Err(AssetError::LoadFailed)));
+    }
+
+    #[test]
+    fn test_unload_during_pending_load() {
+        struct Counter;
+        impl Asset for Counter {}
+
+        let dir = std::env::temp_dir().join(format!("gbase_unload_{}", std::process::id()));
+        let builder = ContextBuilder::new()
+            .assets_path(dir.join("assets"))
+            .temporary_path(dir.join("tmp"))
+            .save_path(dir.join("save"));
+        let mut cache = AssetCache::with_filesystem(FileSystemContext::new(&builder));
+
+        let handle = cache.asset_handle_ctx.next_handle::<Counter>();
+        cache.currently_loading.insert(handle.id());
+        cache.waiters.set(handle.id(), AssetLoadState::Loading);
+        cache.unload(handle.clone());
+
+        // the load task finishes after the unload
+        let result = LoadAssetResult::Success(Box::new(Counter));
+        cache
+            .load_sender
+            .try_send((cache.asset_handle_ctx.upgrade(handle.id()), result))
+            .unwrap();
+        cache.poll_loaded();
+        assert_eq!(cache.load_state(handle.clone()), AssetLoadState::Failed);
+        assert!(matches!(cache.get(handle), GetAssetResult::Failed(_)));
+
+        std::fs::remove_dir_all(dir).unwrap();
+    }
+}
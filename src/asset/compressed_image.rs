@@ -0,0 +1,493 @@
+use super::{AssetLoader, LoadContext, SamplerSettings};
+use crate::{filesystem, render};
+use serde::{Deserialize, Serialize};
+use std::{io::Read, path::Path};
+
+#[derive(thiserror::Error, Debug)]
+pub enum CompressedImageError {
+    #[error(transparent)]
+    Load(#[from] filesystem::LoadFileError),
+    #[error("invalid KTX2 file: {0}")]
+    Ktx2(#[from] ktx2::ParseError),
+    #[error("invalid DDS file: {0}")]
+    Dds(#[from] ddsfile::Error),
+    #[error("unsupported texture format {0}")]
+    UnsupportedFormat(String),
+    #[error("unsupported texture: {0}")]
+    Unsupported(String),
+    #[error("could not decompress mip level {level}: {err}")]
+    Supercompression { level: usize, err: std::io::Error },
+    #[error("mip level {level} is {actual} bytes, expected {expected}")]
+    LevelSize {
+        level: usize,
+        expected: usize,
+        actual: usize,
+    },
+}
+
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+#[serde(default)]
+pub struct CompressedImageSettings {
+    /// Override the color space of the file, e.g. for DDS files without one
+    pub srgb: Option<bool>,
+    pub sampler: SamplerSettings,
+}
+
+impl CompressedImageSettings {
+    fn format(&self, format: wgpu::TextureFormat) -> wgpu::TextureFormat {
+        match self.srgb {
+            Some(true) => format.add_srgb_suffix(),
+            Some(false) => format.remove_srgb_suffix(),
+            None => format,
+        }
+    }
+}
+
+/// Block compressed textures whose format is not supported by the device are
+/// decompressed on the CPU when building them, see [`render::decompressed_format`].
+/// BC6H, signed and ASTC textures can't be decompressed and need device support
+fn image(
+    path: &Path,
+    format: wgpu::TextureFormat,
+    (width, height, layers): (u32, u32, u32),
+    levels: Vec<Vec<u8>>,
+    settings: &CompressedImageSettings,
+) -> Result<render::Image, CompressedImageError> {
+    let size = wgpu::Extent3d {
+        width,
+        height,
+        depth_or_array_layers: layers,
+    };
+    for (level, bytes) in levels.iter().enumerate() {
+        let level_size = size.mip_level_size(level as u32, wgpu::TextureDimension::D2);
+        let expected = render::level_data_size(format, level_size);
+        if bytes.len() != expected {
+            return Err(CompressedImageError::LevelSize {
+                level,
+                expected,
+                actual: bytes.len(),
+            });
+        }
+    }
+
+    let mip_level_count = levels.len() as u32;
+    let texture = render::TextureBuilder::new(render::TextureSource::Levels(width, height, levels))
+        .label(path.to_string_lossy())
+        .with_format(format)
+        .depth_or_array_layers(layers)
+        .mip_level_count(mip_level_count);
+    Ok(render::Image {
+        texture,
+        sampler: settings.sampler.builder(),
+    })
+}
+
+//
+// KTX2
+//
+
+/// Loads KTX2 textures, optionally Zstandard or zlib supercompressed
+///
+/// Basis Universal textures are not supported, and ASTC, BC6H or signed formats
+/// fail to build on devices without support for them
+#[derive(Clone, Hash, Default)]
+pub struct Ktx2Loader {}
+
+impl AssetLoader for Ktx2Loader {
+    type Asset = render::Image;
+    type Error = CompressedImageError;
+    type Settings = CompressedImageSettings;
+
+    async fn load(
+        &self,
+        load_ctx: LoadContext,
+        path: &Path,
+        settings: &Self::Settings,
+    ) -> Result<Self::Asset, Self::Error> {
+        let bytes = load_ctx.load_bytes(path).await?;
+        let reader = ktx2::Reader::new(bytes.as_slice())?;
+        let header = reader.header();
+
+        let format = header.format.ok_or_else(|| {
+            CompressedImageError::UnsupportedFormat(String::from("Basis Universal"))
+        })?;
+        let format = ktx2_format(format)
+            .ok_or_else(|| CompressedImageError::UnsupportedFormat(format!("{format:?}")))?;
+        if header.pixel_depth > 1 {
+            return Err(CompressedImageError::Unsupported(String::from(
+                "3D textures",
+            )));
+        }
+
+        // a level count of 0 still stores the base level
+        check_mip_level_count(
+            header.pixel_width,
+            header.pixel_height.max(1),
+            header.level_count.max(1),
+        )?;
+        let layers = header.layer_count.max(1) * header.face_count;
+        let size = wgpu::Extent3d {
+            width: header.pixel_width,
+            height: header.pixel_height.max(1),
+            depth_or_array_layers: layers,
+        };
+        let mut levels = Vec::with_capacity(header.level_count as usize);
+        for (level, data) in reader.levels().enumerate() {
+            // bound the decompressed size by the level size instead of trusting the file
+            let level_size = size.mip_level_size(level as u32, wgpu::TextureDimension::D2);
+            let expected = render::level_data_size(format, level_size);
+            if header.supercompression_scheme.is_some()
+                && data.uncompressed_byte_length != expected as u64
+            {
+                return Err(CompressedImageError::LevelSize {
+                    level,
+                    expected,
+                    actual: data.uncompressed_byte_length as usize,
+                });
+            }
+            let decompressed = match header.supercompression_scheme {
+                None => Ok(data.data.to_vec()),
+                Some(ktx2::SupercompressionScheme::Zstandard) => {
+                    zstd::bulk::decompress(data.data, expected)
+                }
+                Some(ktx2::SupercompressionScheme::ZLIB) => {
+                    let mut decompressed = Vec::with_capacity(expected);
+                    flate2::read::ZlibDecoder::new(data.data)
+                        .take(expected as u64 + 1)
+                        .read_to_end(&mut decompressed)
+                        .map(|_| decompressed)
+                }
+                Some(scheme) => {
+                    return Err(CompressedImageError::Unsupported(format!(
+                        "{scheme:?} supercompression"
+                    )))
+                }
+            };
+            levels.push(
+                decompressed
+                    .map_err(|err| CompressedImageError::Supercompression { level, err })?,
+            );
+        }
+
+        let mut image = image(
+            path,
+            settings.format(format),
+            (size.width, size.height, layers),
+            levels,
+            settings,
+        )?;
+        // a level count of 0 asks for mipmaps to be generated
+        if header.level_count == 0 {
+            image.texture = image.texture.generate_mipmaps(true);
+        }
+        Ok(image)
+    }
+}
+
+/// Vulkan format of a KTX2 file
+fn ktx2_format(format: ktx2::Format) -> Option<wgpu::TextureFormat> {
+    use ktx2::Format as F;
+    use wgpu::{AstcBlock, AstcChannel, TextureFormat as T};
+
+    let astc = |block, channel| T::Astc { block, channel };
+    Some(match format {
+        F::R8_UNORM => T::R8Unorm,
+        F::R8_SNORM => T::R8Snorm,
+        F::R8_UINT => T::R8Uint,
+        F::R8_SINT => T::R8Sint,
+        F::R8G8_UNORM => T::Rg8Unorm,
+        F::R8G8_SNORM => T::Rg8Snorm,
+        F::R8G8_UINT => T::Rg8Uint,
+        F::R8G8_SINT => T::Rg8Sint,
+        F::R8G8B8A8_UNORM => T::Rgba8Unorm,
+        F::R8G8B8A8_SRGB => T::Rgba8UnormSrgb,
+        F::R8G8B8A8_SNORM => T::Rgba8Snorm,
+        F::R8G8B8A8_UINT => T::Rgba8Uint,
+        F::R8G8B8A8_SINT => T::Rgba8Sint,
+        F::B8G8R8A8_UNORM => T::Bgra8Unorm,
+        F::B8G8R8A8_SRGB => T::Bgra8UnormSrgb,
+        F::A2B10G10R10_UNORM_PACK32 => T::Rgb10a2Unorm,
+        F::R16_UNORM => T::R16Unorm,
+        F::R16_SFLOAT => T::R16Float,
+        F::R16G16_UNORM => T::Rg16Unorm,
+        F::R16G16_SFLOAT => T::Rg16Float,
+        F::R16G16B16A16_UNORM => T::Rgba16Unorm,
+        F::R16G16B16A16_SFLOAT => T::Rgba16Float,
+        F::R32_SFLOAT => T::R32Float,
+        F::R32G32_SFLOAT => T::Rg32Float,
+        F::R32G32B32A32_SFLOAT => T::Rgba32Float,
+        F::B10G11R11_UFLOAT_PACK32 => T::Rg11b10Ufloat,
+        F::E5B9G9R9_UFLOAT_PACK32 => T::Rgb9e5Ufloat,
+        F::BC1_RGBA_UNORM_BLOCK => T::Bc1RgbaUnorm,
+        F::BC1_RGBA_SRGB_BLOCK => T::Bc1RgbaUnormSrgb,
+        F::BC2_UNORM_BLOCK => T::Bc2RgbaUnorm,
+        F::BC2_SRGB_BLOCK => T::Bc2RgbaUnormSrgb,
+        F::BC3_UNORM_BLOCK => T::Bc3RgbaUnorm,
+        F::BC3_SRGB_BLOCK => T::Bc3RgbaUnormSrgb,
+        F::BC4_UNORM_BLOCK => T::Bc4RUnorm,
+        F::BC4_SNORM_BLOCK => T::Bc4RSnorm,
+        F::BC5_UNORM_BLOCK => T::Bc5RgUnorm,
+        F::BC5_SNORM_BLOCK => T::Bc5RgSnorm,
+        F::BC6H_UFLOAT_BLOCK => T::Bc6hRgbUfloat,
+        F::BC6H_SFLOAT_BLOCK => T::Bc6hRgbFloat,
+        F::BC7_UNORM_BLOCK => T::Bc7RgbaUnorm,
+        F::BC7_SRGB_BLOCK => T::Bc7RgbaUnormSrgb,
+        F::ETC2_R8G8B8_UNORM_BLOCK => T::Etc2Rgb8Unorm,
+        F::ETC2_R8G8B8_SRGB_BLOCK => T::Etc2Rgb8UnormSrgb,
+        F::ETC2_R8G8B8A1_UNORM_BLOCK => T::Etc2Rgb8A1Unorm,
+        F::ETC2_R8G8B8A1_SRGB_BLOCK => T::Etc2Rgb8A1UnormSrgb,
+        F::ETC2_R8G8B8A8_UNORM_BLOCK => T::Etc2Rgba8Unorm,
+        F::ETC2_R8G8B8A8_SRGB_BLOCK => T::Etc2Rgba8UnormSrgb,
+        F::EAC_R11_UNORM_BLOCK => T::EacR11Unorm,
+        F::EAC_R11_SNORM_BLOCK => T::EacR11Snorm,
+        F::EAC_R11G11_UNORM_BLOCK => T::EacRg11Unorm,
+        F::EAC_R11G11_SNORM_BLOCK => T::EacRg11Snorm,
+        F::ASTC_4x4_UNORM_BLOCK => astc(AstcBlock::B4x4, AstcChannel::Unorm),
+        F::ASTC_4x4_SRGB_BLOCK => astc(AstcBlock::B4x4, AstcChannel::UnormSrgb),
+        F::ASTC_5x4_UNORM_BLOCK => astc(AstcBlock::B5x4, AstcChannel::Unorm),
+        F::ASTC_5x4_SRGB_BLOCK => astc(AstcBlock::B5x4, AstcChannel::UnormSrgb),
+        F::ASTC_5x5_UNORM_BLOCK => astc(AstcBlock::B5x5, AstcChannel::Unorm),
+        F::ASTC_5x5_SRGB_BLOCK => astc(AstcBlock::B5x5, AstcChannel::UnormSrgb),
+        F::ASTC_6x5_UNORM_BLOCK => astc(AstcBlock::B6x5, AstcChannel::Unorm),
+        F::ASTC_6x5_SRGB_BLOCK => astc(AstcBlock::B6x5, AstcChannel::UnormSrgb),
+        F::ASTC_6x6_UNORM_BLOCK => astc(AstcBlock::B6x6, AstcChannel::Unorm),
+        F::ASTC_6x6_SRGB_BLOCK => astc(AstcBlock::B6x6, AstcChannel::UnormSrgb),
+        F::ASTC_8x5_UNORM_BLOCK => astc(AstcBlock::B8x5, AstcChannel::Unorm),
+        F::ASTC_8x5_SRGB_BLOCK => astc(AstcBlock::B8x5, AstcChannel::UnormSrgb),
+        F::ASTC_8x6_UNORM_BLOCK => astc(AstcBlock::B8x6, AstcChannel::Unorm),
+        F::ASTC_8x6_SRGB_BLOCK => astc(AstcBlock::B8x6, AstcChannel::UnormSrgb),
+        F::ASTC_8x8_UNORM_BLOCK => astc(AstcBlock::B8x8, AstcChannel::Unorm),
+        F::ASTC_8x8_SRGB_BLOCK => astc(AstcBlock::B8x8, AstcChannel::UnormSrgb),
+        F::ASTC_10x5_UNORM_BLOCK => astc(AstcBlock::B10x5, AstcChannel::Unorm),
+        F::ASTC_10x5_SRGB_BLOCK => astc(AstcBlock::B10x5, AstcChannel::UnormSrgb),
+        F::ASTC_10x6_UNORM_BLOCK => astc(AstcBlock::B10x6, AstcChannel::Unorm),
+        F::ASTC_10x6_SRGB_BLOCK => astc(AstcBlock::B10x6, AstcChannel::UnormSrgb),
+        F::ASTC_10x8_UNORM_BLOCK => astc(AstcBlock::B10x8, AstcChannel::Unorm),
+        F::ASTC_10x8_SRGB_BLOCK => astc(AstcBlock::B10x8, AstcChannel::UnormSrgb),
+        F::ASTC_10x10_UNORM_BLOCK => astc(AstcBlock::B10x10, AstcChannel::Unorm),
+        F::ASTC_10x10_SRGB_BLOCK => astc(AstcBlock::B10x10, AstcChannel::UnormSrgb),
+        F::ASTC_12x10_UNORM_BLOCK => astc(AstcBlock::B12x10, AstcChannel::Unorm),
+        F::ASTC_12x10_SRGB_BLOCK => astc(AstcBlock::B12x10, AstcChannel::UnormSrgb),
+        F::ASTC_12x12_UNORM_BLOCK => astc(AstcBlock::B12x12, AstcChannel::Unorm),
+        F::ASTC_12x12_SRGB_BLOCK => astc(AstcBlock::B12x12, AstcChannel::UnormSrgb),
+        F::ASTC_4x4_SFLOAT_BLOCK => astc(AstcBlock::B4x4, AstcChannel::Hdr),
+        _ => return None,
+    })
+}
+
+//
+// DDS
+//
+
+/// Loads DDS textures, both DX10 and legacy headers
+///
+/// BC6H or signed formats fail to build on devices without support for them
+#[derive(Clone, Hash, Default)]
+pub struct DdsLoader {}
+
+impl AssetLoader for DdsLoader {
+    type Asset = render::Image;
+    type Error = CompressedImageError;
+    type Settings = CompressedImageSettings;
+
+    async fn load(
+        &self,
+        load_ctx: LoadContext,
+        path: &Path,
+        settings: &Self::Settings,
+    ) -> Result<Self::Asset, Self::Error> {
+        let bytes = load_ctx.load_bytes(path).await?;
+        let dds = ddsfile::Dds::read(bytes.as_slice())?;
+
+        let format = match (dds.get_dxgi_format(), dds.get_d3d_format()) {
+            (Some(format), _) => dxgi_format(format)
+                .ok_or_else(|| CompressedImageError::UnsupportedFormat(format!("{format:?}")))?,
+            (None, Some(format)) => d3d_format(format)
+                .ok_or_else(|| CompressedImageError::UnsupportedFormat(format!("{format:?}")))?,
+            (None, None) => {
+                return Err(CompressedImageError::UnsupportedFormat(String::from(
+                    "unknown",
+                )))
+            }
+        };
+        if dds.get_depth() > 1 {
+            return Err(CompressedImageError::Unsupported(String::from(
+                "3D textures",
+            )));
+        }
+
+        // DX10 cubemap arrays count cubes, legacy cubemaps are always 6 layers
+        let cube = dds
+            .header10
+            .as_ref()
+            .is_some_and(|header| header.misc_flag.contains(ddsfile::MiscFlag::TEXTURECUBE));
+        let layers = match cube {
+            true => dds.get_num_array_layers() * 6,
+            false => dds.get_num_array_layers(),
+        };
+        let (width, height) = (dds.get_width(), dds.get_height());
+        let levels = dds_levels(
+            format,
+            (width, height, layers),
+            dds.get_num_mipmap_levels(),
+            &dds.data,
+        )?;
+
+        image(
+            path,
+            settings.format(format),
+            (width, height, layers),
+            levels,
+            settings,
+        )
+    }
+}
+
+/// DDS stores all mip levels of a layer together, wgpu wants all layers of a level
+fn dds_levels(
+    format: wgpu::TextureFormat,
+    (width, height, layers): (u32, u32, u32),
+    mip_level_count: u32,
+    data: &[u8],
+) -> Result<Vec<Vec<u8>>, CompressedImageError> {
+    check_mip_level_count(width, height, mip_level_count)?;
+    let layer_size = wgpu::Extent3d {
+        width,
+        height,
+        depth_or_array_layers: 1,
+    };
+    let level_lens = (0..mip_level_count)
+        .map(|level| {
+            render::level_data_size(
+                format,
+                layer_size.mip_level_size(level, wgpu::TextureDimension::D2),
+            )
+        })
+        .collect::<Vec<_>>();
+    let layer_len = level_lens.iter().sum::<usize>();
+    if data.len() < layer_len * layers as usize {
+        return Err(CompressedImageError::LevelSize {
+            level: 0,
+            expected: layer_len * layers as usize,
+            actual: data.len(),
+        });
+    }
+
+    let mut levels = vec![Vec::new(); mip_level_count as usize];
+    for layer in data.chunks_exact(layer_len).take(layers as usize) {
+        let mut offset = 0;
+        for (level, len) in levels.iter_mut().zip(&level_lens) {
+            level.extend_from_slice(&layer[offset..offset + len]);
+            offset += len;
+        }
+    }
+    Ok(levels)
+}
+
+/// Corrupt headers can ask for no or more mip levels than the size allows
+fn check_mip_level_count(
+    width: u32,
+    height: u32,
+    mip_level_count: u32,
+) -> Result<(), CompressedImageError> {
+    let max = 32 - width.max(height).leading_zeros();
+    if mip_level_count == 0 || mip_level_count > max {
+        return Err(CompressedImageError::Unsupported(format!(
+            "{mip_level_count} mip levels for a {width}x{height} texture"
+        )));
+    }
+    Ok(())
+}
+
+fn dxgi_format(format: ddsfile::DxgiFormat) -> Option<wgpu::TextureFormat> {
+    use ddsfile::DxgiFormat as F;
+    use wgpu::TextureFormat as T;
+
+    Some(match format {
+        F::R8_UNorm => T::R8Unorm,
+        F::R8_SNorm => T::R8Snorm,
+        F::R8G8_UNorm => T::Rg8Unorm,
+        F::R8G8_SNorm => T::Rg8Snorm,
+        F::R8G8B8A8_UNorm => T::Rgba8Unorm,
+        F::R8G8B8A8_UNorm_sRGB => T::Rgba8UnormSrgb,
+        F::R8G8B8A8_SNorm => T::Rgba8Snorm,
+        F::B8G8R8A8_UNorm => T::Bgra8Unorm,
+        F::B8G8R8A8_UNorm_sRGB => T::Bgra8UnormSrgb,
+        F::R10G10B10A2_UNorm => T::Rgb10a2Unorm,
+        F::R11G11B10_Float => T::Rg11b10Ufloat,
+        F::R9G9B9E5_SharedExp => T::Rgb9e5Ufloat,
+        F::R16_UNorm => T::R16Unorm,
+        F::R16_Float => T::R16Float,
+        F::R16G16_UNorm => T::Rg16Unorm,
+        F::R16G16_Float => T::Rg16Float,
+        F::R16G16B16A16_UNorm => T::Rgba16Unorm,
+        F::R16G16B16A16_Float => T::Rgba16Float,
+        F::R32_Float => T::R32Float,
+        F::R32G32_Float => T::Rg32Float,
+        F::R32G32B32A32_Float => T::Rgba32Float,
+        F::BC1_UNorm => T::Bc1RgbaUnorm,
+        F::BC1_UNorm_sRGB => T::Bc1RgbaUnormSrgb,
+        F::BC2_UNorm => T::Bc2RgbaUnorm,
+        F::BC2_UNorm_sRGB => T::Bc2RgbaUnormSrgb,
+        F::BC3_UNorm => T::Bc3RgbaUnorm,
+        F::BC3_UNorm_sRGB => T::Bc3RgbaUnormSrgb,
+        F::BC4_UNorm => T::Bc4RUnorm,
+        F::BC4_SNorm => T::Bc4RSnorm,
+        F::BC5_UNorm => T::Bc5RgUnorm,
+        F::BC5_SNorm => T::Bc5RgSnorm,
+        F::BC6H_UF16 => T::Bc6hRgbUfloat,
+        F::BC6H_SF16 => T::Bc6hRgbFloat,
+        F::BC7_UNorm => T::Bc7RgbaUnorm,
+        F::BC7_UNorm_sRGB => T::Bc7RgbaUnormSrgb,
+        _ => return None,
+    })
+}
+
+fn d3d_format(format: ddsfile::D3DFormat) -> Option<wgpu::TextureFormat> {
+    use ddsfile::D3DFormat as F;
+    use wgpu::TextureFormat as T;
+
+    // D3D names list channels from the most significant bit, e.g. A8R8G8B8 is BGRA in memory
+    Some(match format {
+        F::A8B8G8R8 => T::Rgba8Unorm,
+        F::A8R8G8B8 => T::Bgra8Unorm,
+        F::L8 => T::R8Unorm,
+        F::A8L8 => T::Rg8Unorm,
+        F::G16R16 => T::Rg16Unorm,
+        F::A16B16G16R16 => T::Rgba16Unorm,
+        F::R16F => T::R16Float,
+        F::G16R16F => T::Rg16Float,
+        F::A16B16G16R16F => T::Rgba16Float,
+        F::R32F => T::R32Float,
+        F::G32R32F => T::Rg32Float,
+        F::A32B32G32R32F => T::Rgba32Float,
+        F::DXT1 => T::Bc1RgbaUnorm,
+        F::DXT2 | F::DXT3 => T::Bc2RgbaUnorm,
+        F::DXT4 | F::DXT5 => T::Bc3RgbaUnorm,
+        _ => return None,
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::dds_levels;
+
+    #[test]
+    fn test_dds_levels() {
+        // 2 layers of BC1 8x8 with 4 mip levels, the last two are a single padded block
+        let format = wgpu::TextureFormat::Bc1RgbaUnorm;
+        let layer = [vec![0; 4 * 8], vec![1; 8], vec![2; 8], vec![3; 8]].concat();
+        let data = [layer.clone(), layer.iter().map(|v| v + 10).collect()].concat();
+
+        let levels = dds_levels(format, (8, 8, 2), 4, &data).unwrap();
+        assert_eq!(levels.len(), 4);
+        assert_eq!(levels[0].len(), 2 * 4 * 8);
+        assert_eq!(levels[3], [vec![3; 8], vec![13; 8]].concat());
+
+        assert!(dds_levels(format, (8, 8, 3), 4, &data).is_err());
+        // corrupt mip level counts
+        assert!(dds_levels(format, (8, 8, 2), 0, &data).is_err());
+        assert!(dds_levels(format, (8, 8, 2), 5, &data).is_err());
+        assert!(dds_levels(format, (8, 8, 2), u32::MAX, &data).is_err());
+    }
+}
@@ -0,0 +1,193 @@
+use super::{line_column, Asset, AssetLoader, AssetWriter, LoadContext};
+use crate::filesystem;
+use serde::{de::DeserializeOwned, Serialize};
+use std::{
+    marker::PhantomData,
+    path::{Path, PathBuf},
+};
+
+/// Text formats of data assets, chosen by file extension
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum DataFormat {
+    Ron,
+    Json,
+    Toml,
+}
+
+impl DataFormat {
+    pub fn from_path(path: &Path) -> Option<Self> {
+        let extension = path.extension()?.to_str()?.to_lowercase();
+        match extension.as_str() {
+            "ron" => Some(Self::Ron),
+            "json" => Some(Self::Json),
+            "toml" => Some(Self::Toml),
+            _ => None,
+        }
+    }
+
+    pub fn parse<T: DeserializeOwned>(self, source: &str) -> Result<T, DataAssetError> {
+        match self {
+            DataFormat::Ron => ron::from_str(source).map_err(|err| DataAssetError::Parse {
+                line: err.position.line,
+                column: err.position.col,
+                message: err.code.to_string(),
+            }),
+            DataFormat::Json => serde_json::from_str(source).map_err(|err| DataAssetError::Parse {
+                line: err.line(),
+                column: err.column(),
+                // display of serde_json errors already ends with the position
+                message: err
+                    .to_string()
+                    .split(" at line ")
+                    .next()
+                    .unwrap_or_default()
+                    .to_string(),
+            }),
+            DataFormat::Toml => toml::from_str(source).map_err(|err| {
+                let offset = err.span().map_or(0, |span| span.start);
+                let (line, column) = line_column(source, offset);
+                DataAssetError::Parse {
+                    line,
+                    column,
+                    message: err.message().to_string(),
+                }
+            }),
+        }
+    }
+
+    pub fn serialize<T: Serialize>(self, value: &T) -> Result<String, DataAssetError> {
+        let result = match self {
+            DataFormat::Ron => ron::ser::to_string_pretty(value, ron::ser::PrettyConfig::default())
+                .map_err(|err| err.to_string()),
+            DataFormat::Json => serde_json::to_string_pretty(value).map_err(|err| err.to_string()),
+            DataFormat::Toml => toml::to_string_pretty(value).map_err(|err| err.to_string()),
+        };
+        result.map_err(DataAssetError::Serialize)
+    }
+}
+
+#[derive(thiserror::Error, Debug)]
+pub enum DataAssetError {
+    #[error(transparent)]
+    Load(#[from] filesystem::LoadFileError),
+    #[error("unsupported data format {0}, expected ron, json or toml")]
+    UnknownFormat(PathBuf),
+    #[error("{line}:{column}: {message}")]
+    Parse {
+        line: usize,
+        column: usize,
+        message: String,
+    },
+    #[error("could not serialize: {0}")]
+    Serialize(String),
+}
+
+//
+// Loader
+//
+
+/// Loads serde data, e.g. tuning parameters, from RON, JSON or TOML files
+///
+/// Use with `watch` for live tweaking and `write` to save edits back to disk
+pub struct DataAssetLoader<T> {
+    ty: PhantomData<fn() -> T>,
+}
+
+impl<T> Default for DataAssetLoader<T> {
+    fn default() -> Self {
+        Self { ty: PhantomData }
+    }
+}
+
+impl<T> Clone for DataAssetLoader<T> {
+    fn clone(&self) -> Self {
+        Self::default()
+    }
+}
+
+impl<T> std::hash::Hash for DataAssetLoader<T> {
+    fn hash<H: std::hash::Hasher>(&self, _state: &mut H) {}
+}
+
+impl<T: Asset + DeserializeOwned> AssetLoader for DataAssetLoader<T> {
+    type Asset = T;
+    type Error = DataAssetError;
+    type Settings = ();
+
+    async fn load(
+        &self,
+        load_ctx: LoadContext,
+        path: &Path,
+        _settings: &Self::Settings,
+    ) -> Result<Self::Asset, Self::Error> {
+        let format = DataFormat::from_path(path)
+            .ok_or_else(|| DataAssetError::UnknownFormat(path.to_path_buf()))?;
+        let source = load_ctx.load_string(path).await?;
+        format.parse(&source)
+    }
+}
+
+impl<T: Asset + Serialize + DeserializeOwned> AssetWriter for DataAssetLoader<T> {
+    fn write(asset: &Self::Asset, path: &Path) {
+        let Some(format) = DataFormat::from_path(path) else {
+            tracing::error!("{}", DataAssetError::UnknownFormat(path.to_path_buf()));
+            return;
+        };
+
+        let source = match format.serialize(asset) {
+            Ok(source) => source,
+            Err(err) => {
+                tracing::error!("could not write {}: {}", path.display(), err);
+                return;
+            }
+        };
+        if let Err(err) = std::fs::write(path, source) {
+            tracing::error!("could not write {}: {}", path.display(), err);
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{DataAssetError, DataFormat};
+    use serde::{Deserialize, Serialize};
+    use std::path::Path;
+
+    #[derive(Debug, PartialEq, Serialize, Deserialize)]
+    struct Params {
+        density: f32,
+        steps: u32,
+    }
+
+    #[test]
+    fn test_data_formats_roundtrip() {
+        let params = Params {
+            density: 0.5,
+            steps: 64,
+        };
+        for path in ["params.ron", "params.json", "params.TOML"] {
+            let format = DataFormat::from_path(Path::new(path)).unwrap();
+            let source = format.serialize(&params).unwrap();
+            assert_eq!(format.parse::<Params>(&source).unwrap(), params);
+        }
+        assert_eq!(DataFormat::from_path(Path::new("params.txt")), None);
+    }
+
+    #[test]
+    fn test_data_parse_error_position() {
+        let source = "density = 0.5\nsteps = \"many\"\n";
+        let Err(DataAssetError::Parse { line, column, .. }) =
+            DataFormat::Toml.parse::<Params>(source)
+        else {
+            panic!("expected parse error");
+        };
+        assert_eq!((line, column), (2, 9));
+
+        let source = "{\n  \"density\": 0.5,\n  \"steps\": -1\n}";
+        let Err(DataAssetError::Parse { line, .. }) = DataFormat::Json.parse::<Params>(source)
+        else {
+            panic!("expected parse error");
+        };
+        assert_eq!(line, 3);
+    }
+}
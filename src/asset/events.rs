@@ -0,0 +1,284 @@
+use super::{AssetCache, AssetHandleContext, AssetId, WeakAssetHandle};
+use rustc_hash::FxHashMap;
+use std::{
+    any::TypeId,
+    marker::PhantomData,
+    path::{Path, PathBuf},
+};
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum AssetEventKind {
+    /// Asset started being tracked by the cache
+    Added,
+    /// Asset data became available for the first time
+    Loaded,
+    /// Asset data was replaced, e.g. by a hot reload or `get_mut`
+    Modified,
+    /// Asset failed to load
+    Failed,
+    /// Asset was unloaded
+    Removed,
+}
+
+#[derive(Debug, Clone)]
+pub enum AssetEvent<T: 'static> {
+    Added {
+        handle: WeakAssetHandle<T>,
+        path: Option<PathBuf>,
+    },
+    Loaded {
+        handle: WeakAssetHandle<T>,
+        path: Option<PathBuf>,
+    },
+    Modified {
+        handle: WeakAssetHandle<T>,
+        path: Option<PathBuf>,
+    },
+    Failed {
+        handle: WeakAssetHandle<T>,
+        path: Option<PathBuf>,
+    },
+    Removed {
+        handle: WeakAssetHandle<T>,
+        path: Option<PathBuf>,
+    },
+}
+
+impl<T: 'static> AssetEvent<T> {
+    fn new(kind: AssetEventKind, handle: WeakAssetHandle<T>, path: Option<PathBuf>) -> Self {
+        match kind {
+            AssetEventKind::Added => Self::Added { handle, path },
+            AssetEventKind::Loaded => Self::Loaded { handle, path },
+            AssetEventKind::Modified => Self::Modified { handle, path },
+            AssetEventKind::Failed => Self::Failed { handle, path },
+            AssetEventKind::Removed => Self::Removed { handle, path },
+        }
+    }
+
+    pub fn kind(&self) -> AssetEventKind {
+        match self {
+            Self::Added { .. } => AssetEventKind::Added,
+            Self::Loaded { .. } => AssetEventKind::Loaded,
+            Self::Modified { .. } => AssetEventKind::Modified,
+            Self::Failed { .. } => AssetEventKind::Failed,
+            Self::Removed { .. } => AssetEventKind::Removed,
+        }
+    }
+
+    pub fn handle(&self) -> &WeakAssetHandle<T> {
+        match self {
+            Self::Added { handle, .. }
+            | Self::Loaded { handle, .. }
+            | Self::Modified { handle, .. }
+            | Self::Failed { handle, .. }
+            | Self::Removed { handle, .. } => handle,
+        }
+    }
+
+    pub fn path(&self) -> Option<&Path> {
+        match self {
+            Self::Added { path, .. }
+            | Self::Loaded { path, .. }
+            | Self::Modified { path, .. }
+            | Self::Failed { path, .. }
+            | Self::Removed { path, .. } => path.as_deref(),
+        }
+    }
+
+    /// Check if the asset data changed, i.e. derived state should be rebuilt
+    pub fn is_changed(&self) -> bool {
+        matches!(self, Self::Loaded { .. } | Self::Modified { .. })
+    }
+}
+
+//
+// Queue
+//
+
+#[derive(Debug, Clone)]
+pub(crate) struct RawAssetEvent {
+    index: u64,
+    kind: AssetEventKind,
+    ty: TypeId,
+    handle: WeakAssetHandle<()>,
+    path: Option<PathBuf>,
+}
+
+/// Double buffered events, events are kept alive for two cache polls
+#[derive(Debug, Default)]
+pub(crate) struct AssetEventQueue {
+    previous: Vec<RawAssetEvent>,
+    current: Vec<RawAssetEvent>,
+    next_index: u64,
+
+    // asset type of every tracked asset
+    types: FxHashMap<AssetId, TypeId>,
+}
+
+impl AssetEventQueue {
+    /// Start tracking an asset, sends `Added` the first time an asset is seen
+    pub(crate) fn track(
+        &mut self,
+        id: AssetId,
+        ty: TypeId,
+        handle_ctx: &AssetHandleContext,
+        paths: &FxHashMap<AssetId, PathBuf>,
+    ) {
+        if self.types.insert(id, ty).is_none() {
+            self.send(AssetEventKind::Added, id, handle_ctx, paths);
+        }
+    }
+
+    pub(crate) fn asset_type(&self, id: AssetId) -> Option<TypeId> {
+        self.types.get(&id).copied()
+    }
+
+    /// Stop tracking an asset, sends `Removed`
+    pub(crate) fn untrack(
+        &mut self,
+        id: AssetId,
+        handle_ctx: &AssetHandleContext,
+        paths: &FxHashMap<AssetId, PathBuf>,
+    ) {
+        self.send(AssetEventKind::Removed, id, handle_ctx, paths);
+        self.types.remove(&id);
+    }
+
+    pub(crate) fn send(
+        &mut self,
+        kind: AssetEventKind,
+        id: AssetId,
+        handle_ctx: &AssetHandleContext,
+        paths: &FxHashMap<AssetId, PathBuf>,
+    ) {
+        let Some(ty) = self.types.get(&id) else {
+            return;
+        };
+        self.current.push(RawAssetEvent {
+            index: self.next_index,
+            kind,
+            ty: *ty,
+            handle: handle_ctx.weak(id),
+            path: paths.get(&id).cloned(),
+        });
+        self.next_index += 1;
+    }
+
+    /// Drop events older than one poll
+    pub(crate) fn update(&mut self) {
+        self.previous = std::mem::take(&mut self.current);
+    }
+}
+
+//
+// Reader
+//
+
+/// Reads asset events of a single asset type
+///
+/// Each reader sees every event once, as long as it reads at least once every frame
+#[derive(Debug)]
+pub struct AssetEventReader<T: 'static> {
+    next_index: u64,
+    ty: PhantomData<T>,
+}
+
+impl<T: 'static> Default for AssetEventReader<T> {
+    fn default() -> Self {
+        Self {
+            next_index: 0,
+            ty: PhantomData,
+        }
+    }
+}
+
+impl<T: 'static> AssetEventReader<T> {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Read all events since the last read
+    pub fn read<'a>(&mut self, cache: &'a AssetCache) -> impl Iterator<Item = AssetEvent<T>> + 'a {
+        self.read_queue(cache.event_queue())
+    }
+
+    fn read_queue<'a>(
+        &mut self,
+        queue: &'a AssetEventQueue,
+    ) -> impl Iterator<Item = AssetEvent<T>> + 'a {
+        let start = self.next_index;
+        self.next_index = queue.next_index;
+
+        queue
+            .previous
+            .iter()
+            .chain(queue.current.iter())
+            .filter(move |event| event.index >= start && event.ty == TypeId::of::<T>())
+            .map(|event| AssetEvent::new(event.kind, event.handle.cast(), event.path.clone()))
+    }
+
+    /// Check if any asset of this type changed since the last read
+    pub fn changed(&mut self, cache: &AssetCache) -> bool {
+        self.read(cache).any(|event| event.is_changed())
+    }
+
+    /// Check if a specific asset changed since the last read
+    ///
+    /// Consumes all events, use one reader per tracked asset
+    pub fn handle_changed(&mut self, cache: &AssetCache, id: AssetId) -> bool {
+        self.read(cache)
+            .filter(|event| event.handle().id() == id)
+            .any(|event| event.is_changed())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{AssetEventKind, AssetEventQueue, AssetEventReader};
+    use crate::asset::{AssetHandle, AssetHandleContext};
+    use rustc_hash::FxHashMap;
+    use std::{any::TypeId, path::PathBuf};
+
+    #[test]
+    fn test_events_read_once_per_reader() {
+        let (sender, _receiver) = async_channel::unbounded();
+        let ctx = AssetHandleContext::new(sender);
+        let handle = AssetHandle::<u32>::new(&ctx);
+        let other = AssetHandle::<f32>::new(&ctx);
+
+        let mut paths = FxHashMap::default();
+        paths.insert(handle.id(), PathBuf::from("number.txt"));
+
+        let mut queue = AssetEventQueue::default();
+        let mut reader = AssetEventReader::<u32>::new();
+        queue.track(handle.id(), TypeId::of::<u32>(), &ctx, &paths);
+        queue.track(other.id(), TypeId::of::<f32>(), &ctx, &paths);
+        queue.send(AssetEventKind::Loaded, handle.id(), &ctx, &paths);
+
+        let events = reader.read_queue(&queue).collect::<Vec<_>>();
+        assert_eq!(
+            events.iter().map(|e| e.kind()).collect::<Vec<_>>(),
+            vec![AssetEventKind::Added, AssetEventKind::Loaded]
+        );
+        assert_eq!(events[0].handle().upgrade(), Some(handle.clone()));
+        assert_eq!(
+            events[0].path(),
+            Some(PathBuf::from("number.txt").as_path())
+        );
+        assert_eq!(reader.read_queue(&queue).count(), 0);
+
+        // events survive one poll and are dropped after the second
+        let mut late_reader = AssetEventReader::<u32>::new();
+        queue.send(AssetEventKind::Modified, handle.id(), &ctx, &paths);
+        queue.update();
+        assert_eq!(reader.read_queue(&queue).count(), 1);
+        queue.update();
+        assert_eq!(late_reader.read_queue(&queue).count(), 0);
+
+        queue.untrack(handle.id(), &ctx, &paths);
+        queue.send(AssetEventKind::Modified, handle.id(), &ctx, &paths);
+        let events = reader.read_queue(&queue).collect::<Vec<_>>();
+        assert_eq!(events.len(), 1);
+        assert_eq!(events[0].kind(), AssetEventKind::Removed);
+    }
+}
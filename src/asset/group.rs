@@ -0,0 +1,88 @@
+use super::{Asset, AssetCache, AssetHandle, AssetLoadState, DynAssetHandle};
+
+/// Collection of assets which are tracked together
+///
+/// Useful for loading screens which need to know when a set of assets is ready
+#[derive(Debug, Clone, Default)]
+pub struct AssetGroup {
+    handles: Vec<DynAssetHandle>,
+    finished_event: bool,
+}
+
+impl AssetGroup {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn with<T: Asset>(mut self, handle: AssetHandle<T>) -> Self {
+        self.add(handle);
+        self
+    }
+
+    pub fn add<T: Asset>(&mut self, handle: AssetHandle<T>) {
+        self.handles.push(handle.as_any());
+        self.finished_event = false;
+    }
+
+    pub fn handles(&self) -> &[DynAssetHandle] {
+        &self.handles
+    }
+
+    pub fn len(&self) -> usize {
+        self.handles.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.handles.is_empty()
+    }
+
+    /// Fraction of assets which are done loading, either successfully or failed
+    ///
+    /// An empty group counts as fully loaded
+    pub fn progress(&self, cache: &AssetCache) -> f32 {
+        if self.handles.is_empty() {
+            return 1.0;
+        }
+
+        let done = self
+            .handles
+            .iter()
+            .filter(|handle| cache.load_state((*handle).clone()) != AssetLoadState::Loading)
+            .count();
+        done as f32 / self.handles.len() as f32
+    }
+
+    /// Check if all assets are done loading, either successfully or failed
+    pub fn finished(&self, cache: &AssetCache) -> bool {
+        self.handles
+            .iter()
+            .all(|handle| cache.load_state(handle.clone()) != AssetLoadState::Loading)
+    }
+
+    /// Check if all assets loaded successfully
+    pub fn loaded(&self, cache: &AssetCache) -> bool {
+        self.handles
+            .iter()
+            .all(|handle| cache.load_state(handle.clone()) == AssetLoadState::Loaded)
+    }
+
+    /// Handles of assets which failed to load
+    pub fn failed(&self, cache: &AssetCache) -> Vec<DynAssetHandle> {
+        self.handles
+            .iter()
+            .filter(|handle| cache.load_state((*handle).clone()) == AssetLoadState::Failed)
+            .cloned()
+            .collect()
+    }
+
+    /// Returns true the first time this is called after all assets finished loading
+    ///
+    /// Adding new handles resets the event
+    pub fn just_finished(&mut self, cache: &AssetCache) -> bool {
+        if self.finished_event || !self.finished(cache) {
+            return false;
+        }
+        self.finished_event = true;
+        true
+    }
+}
@@ -1,36 +1,92 @@
-use super::DynAsset;
+use super::{Asset, AssetCache, DynAsset};
 use crate::asset;
-use std::{marker::PhantomData, sync::Arc};
+use std::{
+    any::TypeId,
+    marker::PhantomData,
+    sync::{Arc, Weak},
+};
 
+pub type AssetId = u64;
+
+/// Shared state of all strong handles to an asset
+///
+/// Notifies the cache when the last strong handle is dropped
 #[derive(Debug)]
+pub(crate) struct AssetHandleInner {
+    pub(crate) id: AssetId,
+    drop_sender: async_channel::Sender<AssetId>,
+}
+
+impl AssetHandleInner {
+    pub(crate) fn new(id: AssetId, drop_sender: async_channel::Sender<AssetId>) -> Self {
+        Self { id, drop_sender }
+    }
+}
+
+impl Drop for AssetHandleInner {
+    fn drop(&mut self) {
+        // cache might already be dropped
+        let _ = self.drop_sender.try_send(self.id);
+    }
+}
+
+//
+// Strong
+//
+
+/// Strong handle to an asset
+///
+/// The asset is kept loaded as long as at least one strong handle exists
 pub struct AssetHandle<T: 'static> {
-    pub(crate) id: Arc<u64>, // TODO: use strong and weak outside/inside cache
+    pub(crate) inner: Arc<AssetHandleInner>,
     pub(crate) ty: PhantomData<T>,
 }
 
 impl<T: 'static> AssetHandle<T> {
     #![allow(clippy::new_without_default)]
     pub fn new(asset_handle_ctx: &asset::AssetHandleContext) -> Self {
-        let id = asset_handle_ctx.next_id();
-        Self {
-            id: Arc::new(id),
-            ty: PhantomData,
-        }
+        asset_handle_ctx.next_handle()
     }
 
     #[inline]
-    pub fn id(&self) -> u64 {
-        *self.id
+    pub fn id(&self) -> AssetId {
+        self.inner.id
+    }
+
+    /// Create a weak handle which does not keep the asset loaded
+    pub fn downgrade(&self) -> WeakAssetHandle<T> {
+        WeakAssetHandle {
+            id: self.inner.id,
+            inner: Arc::downgrade(&self.inner),
+            ty: PhantomData,
+        }
     }
 
     pub(crate) fn as_any(&self) -> AssetHandle<DynAsset> {
-        AssetHandle::<DynAsset> {
-            id: self.id.clone(),
+        self.cast()
+    }
+
+    pub(crate) fn cast<U: 'static>(&self) -> AssetHandle<U> {
+        AssetHandle {
+            inner: self.inner.clone(),
             ty: PhantomData,
         }
     }
 }
 
+impl AssetHandle<DynAsset> {
+    /// Get a typed handle if the asset is of type `T`
+    pub fn downcast<T: Asset>(&self, cache: &AssetCache) -> Option<AssetHandle<T>> {
+        (cache.asset_type_id(self.id()) == Some(TypeId::of::<T>())).then(|| self.cast())
+    }
+}
+
+impl<T: 'static> std::fmt::Debug for AssetHandle<T> {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_tuple("AssetHandle").field(&self.id()).finish()
+    }
+}
+
 impl<T: 'static> PartialOrd for AssetHandle<T> {
     fn partial_cmp(&self, other: &Self) -> Option<std::cmp::Ordering> {
         Some(self.cmp(other))
@@ -39,13 +95,13 @@ impl<T: 'static> PartialOrd for AssetHandle<T> {
 
 impl<T: 'static> Ord for AssetHandle<T> {
     fn cmp(&self, other: &Self) -> std::cmp::Ordering {
-        self.id.cmp(&other.id)
+        self.id().cmp(&other.id())
     }
 }
 
 impl<T: 'static> PartialEq for AssetHandle<T> {
     fn eq(&self, other: &Self) -> bool {
-        self.id == other.id
+        self.id() == other.id()
     }
 }
 
@@ -53,14 +109,84 @@ impl<T: 'static> Eq for AssetHandle<T> {}
 
 impl<T: 'static> std::hash::Hash for AssetHandle<T> {
     fn hash<H: std::hash::Hasher>(&self, state: &mut H) {
-        self.id.hash(state);
+        self.id().hash(state);
     }
 }
 
 impl<T: 'static> Clone for AssetHandle<T> {
     fn clone(&self) -> Self {
         Self {
-            id: self.id.clone(),
+            inner: self.inner.clone(),
+            ty: PhantomData,
+        }
+    }
+}
+
+//
+// Weak
+//
+
+/// Weak handle to an asset
+///
+/// Does not keep the asset loaded, upgrade to access it
+pub struct WeakAssetHandle<T: 'static> {
+    id: AssetId,
+    inner: Weak<AssetHandleInner>,
+    ty: PhantomData<T>,
+}
+
+impl<T: 'static> WeakAssetHandle<T> {
+    pub(crate) fn new(id: AssetId, inner: Weak<AssetHandleInner>) -> Self {
+        Self {
+            id,
+            inner,
+            ty: PhantomData,
+        }
+    }
+
+    #[inline]
+    pub fn id(&self) -> AssetId {
+        self.id
+    }
+
+    pub(crate) fn cast<U: 'static>(&self) -> WeakAssetHandle<U> {
+        WeakAssetHandle::new(self.id, self.inner.clone())
+    }
+
+    /// Get a strong handle if any other strong handle is still alive
+    pub fn upgrade(&self) -> Option<AssetHandle<T>> {
+        self.inner.upgrade().map(|inner| AssetHandle {
+            inner,
+            ty: PhantomData,
+        })
+    }
+}
+
+impl<T: 'static> std::fmt::Debug for WeakAssetHandle<T> {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_tuple("WeakAssetHandle").field(&self.id).finish()
+    }
+}
+
+impl<T: 'static> PartialEq for WeakAssetHandle<T> {
+    fn eq(&self, other: &Self) -> bool {
+        self.id == other.id
+    }
+}
+
+impl<T: 'static> Eq for WeakAssetHandle<T> {}
+
+impl<T: 'static> std::hash::Hash for WeakAssetHandle<T> {
+    fn hash<H: std::hash::Hasher>(&self, state: &mut H) {
+        self.id.hash(state);
+    }
+}
+
+impl<T: 'static> Clone for WeakAssetHandle<T> {
+    fn clone(&self) -> Self {
+        Self {
+            id: self.id,
+            inner: self.inner.clone(),
             ty: PhantomData,
         }
     }
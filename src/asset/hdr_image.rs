@@ -0,0 +1,99 @@
+use super::{AssetLoader, LoadContext, SamplerSettings};
+use crate::{filesystem, render};
+use serde::{Deserialize, Serialize};
+use std::path::Path;
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(default)]
+pub struct HdrImageSettings {
+    /// Store as `Rgba16Float` instead of `Rgba32Float`,
+    /// which is half the size and filterable on all devices
+    pub half: bool,
+    /// Convert the image from an equirectangular panorama to a cubemap with faces of this size
+    pub cubemap: Option<u32>,
+    /// Generate mip levels on the GPU
+    pub mipmaps: bool,
+    pub sampler: SamplerSettings,
+}
+
+impl Default for HdrImageSettings {
+    fn default() -> Self {
+        Self {
+            half: true,
+            cubemap: None,
+            mipmaps: true,
+            sampler: SamplerSettings::default(),
+        }
+    }
+}
+
+/// Loads Radiance HDR and OpenEXR images into float textures, in linear color
+#[derive(Clone, Hash, Default)]
+pub struct HdrImageLoader {}
+
+impl AssetLoader for HdrImageLoader {
+    type Asset = render::Image;
+    type Error = filesystem::LoadFileError;
+    type Settings = HdrImageSettings;
+
+    async fn load(
+        &self,
+        load_ctx: LoadContext,
+        path: &Path,
+        settings: &Self::Settings,
+    ) -> Result<Self::Asset, Self::Error> {
+        let bytes = load_ctx.load_bytes(path).await?;
+        let img = image::load_from_memory(&bytes)
+            .map_err(|err| filesystem::LoadFileError::Other(Box::new(err)))?
+            .to_rgba32f();
+        let (width, height) = img.dimensions();
+
+        let (format, data) = match settings.half {
+            true => (wgpu::TextureFormat::Rgba16Float, to_f16_bytes(img.as_raw())),
+            false => (
+                wgpu::TextureFormat::Rgba32Float,
+                img.as_raw().iter().flat_map(|v| v.to_le_bytes()).collect(),
+            ),
+        };
+        let source = match settings.cubemap {
+            Some(face_size) => render::TextureSource::Equirectangular {
+                width,
+                height,
+                data,
+                face_size,
+            },
+            None => render::TextureSource::Data(width, height, data),
+        };
+        let texture = render::TextureBuilder::new(source)
+            .label(path.to_string_lossy())
+            .with_format(format)
+            .generate_mipmaps(settings.mipmaps);
+        Ok(render::Image {
+            texture,
+            sampler: settings.sampler.builder(),
+        })
+    }
+}
+
+fn to_f16_bytes(values: &[f32]) -> Vec<u8> {
+    values
+        .iter()
+        .flat_map(|&v| half::f16::from_f32(v).to_bits().to_le_bytes())
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::to_f16_bytes;
+
+    #[test]
+    fn test_to_f16_bytes() {
+        let bytes = to_f16_bytes(&[0.0, 1.0, -2.0, 65504.0, 1.0e6]);
+        let values = bytes
+            .chunks_exact(2)
+            .map(|b| u16::from_le_bytes([b[0], b[1]]))
+            .collect::<Vec<_>>();
+        // values above the f16 range become infinity
+        assert_eq!(values, [0x0000, 0x3c00, 0xc000, 0x7bff, 0x7c00]);
+    }
+}
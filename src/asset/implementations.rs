@@ -5,6 +5,8 @@ use crate::{
     render::{self, GpuImage},
     Context,
 };
+use rustc_hash::FxHashMap;
+use serde::{Deserialize, Serialize};
 
 //
 // Mesh
@@ -12,7 +14,11 @@ use crate::{
 
 impl Asset for render::Mesh {}
 
-impl DerivedAsset for render::GpuMesh {}
+impl DerivedAsset for render::GpuMesh {
+    fn gpu_memory_size(&self) -> u64 {
+        self.memory_size()
+    }
+}
 
 pub struct MeshGpuConverter;
 impl AssetConverter for MeshGpuConverter {
@@ -28,7 +34,7 @@ impl AssetConverter for MeshGpuConverter {
     ) -> ConvertAssetStatus<Self::TargetAsset> {
         let source = match source.get(cache) {
             GetAssetResult::Loading => return ConvertAssetStatus::SourceLoading,
-            GetAssetResult::Failed => return ConvertAssetStatus::Failed,
+            GetAssetResult::Failed(_) => return ConvertAssetStatus::Failed,
             GetAssetResult::Success(source) => source,
         };
         let gpu_mesh = render::GpuMesh::new(ctx, source);
@@ -52,7 +58,7 @@ impl AssetConverter for BoundingBoxConverter {
     ) -> ConvertAssetStatus<Self::TargetAsset> {
         let source = match source.get(cache) {
             GetAssetResult::Loading => return ConvertAssetStatus::SourceLoading,
-            GetAssetResult::Failed => return ConvertAssetStatus::Failed,
+            GetAssetResult::Failed(_) => return ConvertAssetStatus::Failed,
             GetAssetResult::Success(source) => source,
         };
 
@@ -67,30 +73,137 @@ impl AssetConverter for BoundingBoxConverter {
 
 impl Asset for render::ShaderBuilder {}
 
-#[derive(Clone)]
+/// Shader defines from `<shader>.wgsl.meta`
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct ShaderSettings {
+    pub defines: render::ShaderDefs,
+}
+
+/// Loads WGSL shaders and resolves `#import`s, imported files hot reload the shader too
+#[derive(Clone, Hash, Default)]
 pub struct ShaderLoader {}
 impl AssetLoader for ShaderLoader {
     type Asset = render::ShaderBuilder;
     type Error = filesystem::LoadFileError;
+    type Settings = ShaderSettings;
 
     async fn load(
         &self,
-        _load_ctx: super::LoadContext,
+        load_ctx: super::LoadContext,
         path: &std::path::Path,
+        settings: &Self::Settings,
     ) -> Result<Self::Asset, Self::Error> {
-        let source = _load_ctx.load_string(path).await?;
-
-        Ok(Self::Asset {
-            label: Some(
-                path.to_str()
-                    .expect("could not convert path to string")
-                    .to_string(),
-            ),
-            source,
-        })
+        let root = filesystem::normalize_path(path);
+
+        // load every reachable file, expanded once all sources are known
+        let mut sources = FxHashMap::default();
+        let mut pending = vec![root.clone()];
+        while let Some(path) = pending.pop() {
+            if sources.contains_key(&path) {
+                continue;
+            }
+            // a processed root has no imports left
+            let source = match path == root {
+                true => load_ctx
+                    .load_processed(&path)
+                    .await
+                    .and_then(|bytes| shader_source(&path, bytes))?,
+                false => {
+                    let source = load_ctx
+                        .load_string(&path)
+                        .await
+                        .map_err(|err| import_error(&path, err))?;
+                    load_ctx.add_file_dependency(&path);
+                    source
+                }
+            };
+
+            pending.extend(
+                source
+                    .lines()
+                    .filter_map(render::ShaderImport::parse)
+                    .map(|import| import.resolve(&path)),
+            );
+            sources.insert(path, source);
+        }
+
+        Ok(
+            render::ShaderBuilder::new(render::expand_imports(&root, &sources))
+                .label(
+                    path.to_str()
+                        .expect("could not convert path to string")
+                        .to_string(),
+                )
+                .defines(settings.defines.clone()),
+        )
+    }
+}
+
+/// Resolves the `#import`s of WGSL shaders into a single source
+#[derive(Clone, Default)]
+pub struct ShaderProcessor {}
+
+impl super::AssetProcessor for ShaderProcessor {
+    const VERSION: u32 = 1;
+    type Error = filesystem::LoadFileError;
+    // defines are applied when the shader is built
+    type Settings = ShaderSettings;
+
+    fn process(
+        &self,
+        ctx: &super::ProcessContext<'_>,
+        path: &std::path::Path,
+        source: &[u8],
+        _settings: &Self::Settings,
+    ) -> Result<Vec<u8>, Self::Error> {
+        let root = filesystem::normalize_path(path);
+
+        let mut sources = FxHashMap::default();
+        let mut pending = vec![root.clone()];
+        while let Some(path) = pending.pop() {
+            if sources.contains_key(&path) {
+                continue;
+            }
+            let source = match path == root {
+                true => shader_source(&path, source.to_vec())?,
+                false => ctx
+                    .read_string(&path)
+                    .map_err(|err| import_error(&path, err))?,
+            };
+
+            pending.extend(
+                source
+                    .lines()
+                    .filter_map(render::ShaderImport::parse)
+                    .map(|import| import.resolve(&path)),
+            );
+            sources.insert(path, source);
+        }
+
+        Ok(render::expand_imports(&root, &sources).into_bytes())
     }
 }
 
+fn shader_source(
+    path: &std::path::Path,
+    bytes: Vec<u8>,
+) -> Result<String, filesystem::LoadFileError> {
+    String::from_utf8(bytes).map_err(|err| {
+        filesystem::LoadFileError::Other(
+            format!("shader {} is not valid utf-8: {}", path.display(), err).into(),
+        )
+    })
+}
+
+fn import_error(
+    path: &std::path::Path,
+    err: filesystem::LoadFileError,
+) -> filesystem::LoadFileError {
+    filesystem::LoadFileError::Other(
+        format!("could not load shader import {}: {}", path.display(), err).into(),
+    )
+}
+
 impl DerivedAsset for wgpu::ShaderModule {}
 
 pub struct ShaderGpuConverter;
@@ -107,7 +220,7 @@ impl AssetConverter for ShaderGpuConverter {
     ) -> ConvertAssetStatus<Self::TargetAsset> {
         let source = match source.get(cache) {
             GetAssetResult::Loading => return ConvertAssetStatus::SourceLoading,
-            GetAssetResult::Failed => return ConvertAssetStatus::Failed,
+            GetAssetResult::Failed(_) => return ConvertAssetStatus::Failed,
             GetAssetResult::Success(source) => source,
         };
 
@@ -136,33 +249,194 @@ impl AssetConverter for ShaderGpuConverter {
 
 impl Asset for render::Image {}
 
-#[derive(Clone)]
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(default)]
+pub struct ImageSettings {
+    /// Disable for non color data, e.g. normal maps
+    pub srgb: bool,
+    /// Generate missing mip levels on the GPU
+    pub mipmaps: bool,
+    pub sampler: SamplerSettings,
+}
+
+impl Default for ImageSettings {
+    fn default() -> Self {
+        Self {
+            srgb: true,
+            mipmaps: true,
+            sampler: SamplerSettings::default(),
+        }
+    }
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(default)]
+pub struct SamplerSettings {
+    pub address_mode_u: wgpu::AddressMode,
+    pub address_mode_v: wgpu::AddressMode,
+    pub address_mode_w: wgpu::AddressMode,
+    pub mag_filter: wgpu::FilterMode,
+    pub min_filter: wgpu::FilterMode,
+    pub mip_map_filter: wgpu::FilterMode,
+}
+
+impl Default for SamplerSettings {
+    fn default() -> Self {
+        Self {
+            address_mode_u: wgpu::AddressMode::ClampToEdge,
+            address_mode_v: wgpu::AddressMode::ClampToEdge,
+            address_mode_w: wgpu::AddressMode::ClampToEdge,
+            mag_filter: wgpu::FilterMode::Linear,
+            min_filter: wgpu::FilterMode::Linear,
+            mip_map_filter: wgpu::FilterMode::Linear,
+        }
+    }
+}
+
+impl SamplerSettings {
+    pub fn builder(&self) -> render::SamplerBuilder {
+        render::SamplerBuilder::new()
+            .address_mode_separate(
+                self.address_mode_u,
+                self.address_mode_v,
+                self.address_mode_w,
+            )
+            .min_mag_filter(self.min_filter, self.mag_filter)
+            .mip_map_filer(self.mip_map_filter)
+    }
+}
+
+#[derive(Clone, Hash, Default)]
 pub struct ImageLoader {}
 impl AssetLoader for ImageLoader {
     type Asset = render::Image;
     type Error = filesystem::LoadFileError;
+    type Settings = ImageSettings;
 
     async fn load(
         &self,
         load_ctx: super::LoadContext,
         path: &std::path::Path,
+        settings: &Self::Settings,
     ) -> Result<Self::Asset, Self::Error> {
-        let bytes = load_ctx.load_bytes(path).await?;
+        let bytes = load_ctx.load_processed(path).await?;
 
-        let img = image::load_from_memory(&bytes)
-            .expect("could not load image")
-            .to_rgba8();
+        let img = match ProcessedImage::decode(&bytes) {
+            Some(img) => img,
+            None => ProcessedImage::from_source(&bytes, false, settings.srgb)?,
+        };
+        let format = match settings.srgb {
+            true => wgpu::TextureFormat::Rgba8UnormSrgb,
+            false => wgpu::TextureFormat::Rgba8Unorm,
+        };
         let texture = render::TextureBuilder::new(render::TextureSource::Data(
-            img.width(),
-            img.height(),
-            img.to_vec(),
-        ));
-        let sampler = render::SamplerBuilder::new();
+            img.width, img.height, img.data,
+        ))
+        .with_format(format)
+        .mip_level_count(img.mip_level_count)
+        .generate_mipmaps(settings.mipmaps);
+        let sampler = settings.sampler.builder();
         Ok(Self::Asset { texture, sampler })
     }
 }
 
-impl DerivedAsset for render::GpuImage {}
+/// Decoded RGBA8 image with all mip levels, the output of [`ImageProcessor`]
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ProcessedImage {
+    pub width: u32,
+    pub height: u32,
+    pub mip_level_count: u32,
+    /// All mip levels, largest first
+    pub data: Vec<u8>,
+}
+
+impl ProcessedImage {
+    const MAGIC: &[u8; 8] = b"GBIMAGE1";
+    const HEADER_SIZE: usize = 8 + 3 * 4;
+
+    /// Decode an encoded image, e.g. PNG, optionally with all mip levels down to 1x1
+    ///
+    /// `srgb` mip levels are filtered in linear space
+    pub fn from_source(
+        source: &[u8],
+        mipmaps: bool,
+        srgb: bool,
+    ) -> Result<Self, filesystem::LoadFileError> {
+        let img = image::load_from_memory(source)
+            .map_err(|err| filesystem::LoadFileError::Other(Box::new(err)))?
+            .to_rgba8();
+        let (width, height) = img.dimensions();
+        let mip_level_count = match mipmaps {
+            true => u32::BITS - width.max(height).max(1).leading_zeros(),
+            false => 1,
+        };
+        let data = match mipmaps {
+            true => render::generate_mipmaps_rgba8(width, height, &img, srgb),
+            false => img.into_raw(),
+        };
+
+        Ok(Self {
+            width,
+            height,
+            mip_level_count,
+            data,
+        })
+    }
+
+    pub fn encode(&self) -> Vec<u8> {
+        let mut bytes = Vec::with_capacity(Self::HEADER_SIZE + self.data.len());
+        bytes.extend_from_slice(Self::MAGIC);
+        for value in [self.width, self.height, self.mip_level_count] {
+            bytes.extend_from_slice(&value.to_le_bytes());
+        }
+        bytes.extend_from_slice(&self.data);
+        bytes
+    }
+
+    /// Returns `None` if the bytes are not a processed image
+    pub fn decode(bytes: &[u8]) -> Option<Self> {
+        if bytes.len() < Self::HEADER_SIZE || !bytes.starts_with(Self::MAGIC) {
+            return None;
+        }
+        let value = |index: usize| {
+            let start = Self::MAGIC.len() + index * 4;
+            u32::from_le_bytes(bytes[start..start + 4].try_into().unwrap())
+        };
+
+        Some(Self {
+            width: value(0),
+            height: value(1),
+            mip_level_count: value(2),
+            data: bytes[Self::HEADER_SIZE..].to_vec(),
+        })
+    }
+}
+
+/// Decodes images and generates all mip levels, as configured by their [`ImageSettings`]
+#[derive(Clone, Default)]
+pub struct ImageProcessor {}
+
+impl super::AssetProcessor for ImageProcessor {
+    const VERSION: u32 = 3;
+    type Error = filesystem::LoadFileError;
+    type Settings = ImageSettings;
+
+    fn process(
+        &self,
+        _ctx: &super::ProcessContext<'_>,
+        _path: &std::path::Path,
+        source: &[u8],
+        settings: &Self::Settings,
+    ) -> Result<Vec<u8>, Self::Error> {
+        Ok(ProcessedImage::from_source(source, settings.mipmaps, settings.srgb)?.encode())
+    }
+}
+
+impl DerivedAsset for render::GpuImage {
+    fn gpu_memory_size(&self) -> u64 {
+        self.memory_size()
+    }
+}
 
 pub struct ImageGpuConverter;
 impl AssetConverter for ImageGpuConverter {
@@ -178,13 +452,22 @@ impl AssetConverter for ImageGpuConverter {
     ) -> ConvertAssetStatus<Self::TargetAsset> {
         let source = match source.get(cache) {
             GetAssetResult::Loading => return ConvertAssetStatus::SourceLoading,
-            GetAssetResult::Failed => return ConvertAssetStatus::Failed,
+            GetAssetResult::Failed(_) => return ConvertAssetStatus::Failed,
             GetAssetResult::Success(source) => source,
         };
 
+        if !source.texture.is_supported(ctx) {
+            tracing::error!("texture format is not supported by the device");
+            return ConvertAssetStatus::Failed;
+        }
+
         let sampler = source.sampler.clone().build(ctx);
         let texture = source.texture.build(ctx);
-        let view = render::TextureViewBuilder::new(texture.clone()).build(ctx);
+        let mut view = render::TextureViewBuilder::new(texture.clone());
+        if let Some(dimension) = source.texture.view_dimension() {
+            view = view.dimension(dimension);
+        }
+        let view = view.build(ctx);
 
         let gpu_image = GpuImage::new(texture, view, sampler);
         ConvertAssetStatus::Success(gpu_image)
@@ -0,0 +1,121 @@
+use super::AssetId;
+use crate::filesystem;
+use rustc_hash::FxHashMap;
+use std::{
+    any::TypeId,
+    path::{Path, PathBuf},
+    sync::{Arc, Mutex},
+};
+
+/// Split a labeled path, e.g. `models/ship.glb#Mesh/Hull` into `models/ship.glb` and `Mesh/Hull`
+pub fn split_label(path: &Path) -> (PathBuf, Option<String>) {
+    let path_str = path.to_string_lossy();
+    match path_str.split_once('#') {
+        Some((parent, label)) => (PathBuf::from(parent), Some(label.to_string())),
+        None => (path.to_path_buf(), None),
+    }
+}
+
+/// Path of a sub asset, e.g. `models/ship.glb#Mesh/Hull`
+pub fn labeled_path(path: &Path, label: &str) -> PathBuf {
+    let path = filesystem::normalize_path(path);
+    PathBuf::from(format!("{}#{}", path.to_string_lossy(), label))
+}
+
+//
+// Labels
+//
+
+/// Ids of labeled sub assets, shared between the cache and loaders
+///
+/// Labels keep their id across reloads of the parent asset
+#[derive(Debug, Clone, Default)]
+pub(crate) struct AssetLabels {
+    inner: Arc<Mutex<AssetLabelsInner>>,
+}
+
+#[derive(Debug, Default)]
+struct AssetLabelsInner {
+    ids: FxHashMap<(PathBuf, TypeId), AssetId>,
+    paths: FxHashMap<AssetId, PathBuf>,
+}
+
+impl AssetLabels {
+    pub(crate) fn get_or_insert(
+        &self,
+        path: PathBuf,
+        ty: TypeId,
+        next_id: impl FnOnce() -> AssetId,
+    ) -> AssetId {
+        let mut inner = self.inner.lock().expect("could not unlock asset labels");
+        if let Some(id) = inner.ids.get(&(path.clone(), ty)) {
+            return *id;
+        }
+
+        let id = next_id();
+        inner.ids.insert((path.clone(), ty), id);
+        inner.paths.insert(id, path);
+        id
+    }
+
+    pub(crate) fn path(&self, id: AssetId) -> Option<PathBuf> {
+        let inner = self.inner.lock().expect("could not unlock asset labels");
+        inner.paths.get(&id).cloned()
+    }
+
+    pub(crate) fn remove(&self, id: AssetId) {
+        let mut inner = self.inner.lock().expect("could not unlock asset labels");
+        if inner.paths.remove(&id).is_some() {
+            inner.ids.retain(|_, value| *value != id);
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{labeled_path, split_label, AssetLabels};
+    use std::{
+        any::TypeId,
+        path::{Path, PathBuf},
+    };
+
+    #[test]
+    fn test_split_label() {
+        assert_eq!(
+            split_label(Path::new("models/ship.glb#Mesh/Hull")),
+            (
+                PathBuf::from("models/ship.glb"),
+                Some("Mesh/Hull".to_string())
+            )
+        );
+        assert_eq!(
+            split_label(Path::new("models/ship.glb")),
+            (PathBuf::from("models/ship.glb"), None)
+        );
+        assert_eq!(
+            labeled_path(Path::new("./models/ship.glb"), "Material/0"),
+            PathBuf::from("models/ship.glb#Material/0")
+        );
+    }
+
+    #[test]
+    fn test_labels_keep_id() {
+        let labels = AssetLabels::default();
+        let path = labeled_path(Path::new("ship.glb"), "Mesh/Hull");
+
+        let id = labels.get_or_insert(path.clone(), TypeId::of::<u32>(), || 1);
+        assert_eq!(
+            labels.get_or_insert(path.clone(), TypeId::of::<u32>(), || 2),
+            id
+        );
+        assert_eq!(
+            labels.get_or_insert(path.clone(), TypeId::of::<f32>(), || 3),
+            3
+        );
+        assert_eq!(labels.path(id), Some(path.clone()));
+
+        labels.remove(id);
+        assert_eq!(labels.path(id), None);
+        assert_eq!(labels.get_or_insert(path, TypeId::of::<u32>(), || 4), 4);
+    }
+}
@@ -0,0 +1,74 @@
+use super::{AssetLoadError, AssetLoader, LoadAssetResult, LoadContext};
+use crate::filesystem;
+use std::path::{Path, PathBuf};
+
+/// Path of the settings file of an asset, e.g. `textures/grass.png.meta`
+pub fn meta_path(path: &Path) -> PathBuf {
+    let mut meta = path.as_os_str().to_owned();
+    meta.push(".meta");
+    PathBuf::from(meta)
+}
+
+/// Path of the asset a settings file belongs to
+pub fn asset_path_from_meta(path: &Path) -> Option<PathBuf> {
+    match path.extension() {
+        Some(extension) if extension == "meta" => Some(path.with_extension("")),
+        _ => None,
+    }
+}
+
+#[derive(thiserror::Error, Debug)]
+pub enum MetaError {
+    #[error("could not read {0}: {1}")]
+    Load(PathBuf, filesystem::LoadFileError),
+    #[error("could not parse {0}: {1}")]
+    Parse(PathBuf, ron::error::SpannedError),
+}
+
+/// Read the settings of an asset from its `.meta` file
+///
+/// Falls back to the settings of the loader if the asset has no `.meta` file
+pub async fn load_settings<L: AssetLoader>(
+    load_ctx: &LoadContext,
+    path: &Path,
+    loader: &L,
+) -> Result<L::Settings, MetaError> {
+    let meta_path = meta_path(path);
+    match load_ctx.load_string(&meta_path).await {
+        Ok(source) => ron::from_str(&source).map_err(|err| MetaError::Parse(meta_path, err)),
+        Err(filesystem::LoadFileError::FileNotFound) => Ok(loader.settings()),
+        Err(err) => Err(MetaError::Load(meta_path, err)),
+    }
+}
+
+/// Load an asset using the settings from its `.meta` file
+pub(crate) async fn load_asset<L: AssetLoader>(
+    loader: &L,
+    load_ctx: LoadContext,
+    path: &Path,
+) -> LoadAssetResult {
+    let settings = match load_settings(&load_ctx, path, loader).await {
+        Ok(settings) => settings,
+        Err(err) => return LoadAssetResult::Error(AssetLoadError::new(Some(path), err)),
+    };
+
+    match loader.load(load_ctx.with_path(path), path, &settings).await {
+        Ok(asset) => LoadAssetResult::Success(Box::new(asset)),
+        Err(err) => LoadAssetResult::Error(AssetLoadError::new(Some(path), err)),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{asset_path_from_meta, meta_path};
+    use std::path::{Path, PathBuf};
+
+    #[test]
+    fn test_meta_path_roundtrip() {
+        let path = Path::new("textures/grass.png");
+        let meta = meta_path(path);
+        assert_eq!(meta, PathBuf::from("textures/grass.png.meta"));
+        assert_eq!(asset_path_from_meta(&meta), Some(path.to_path_buf()));
+        assert_eq!(asset_path_from_meta(path), None);
+    }
+}
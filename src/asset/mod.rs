@@ -1,16 +1,46 @@
+mod budget;
 mod builders;
 mod cache;
+mod compressed_image;
+mod data;
+mod events;
+mod group;
 mod handle;
+mod hdr_image;
 mod implementations;
+mod label;
+mod meta;
+mod process;
+mod registry;
 mod types;
+mod wait;
+#[cfg(feature = "wesl")]
+mod wesl_loader;
 
+pub use budget::*;
 pub use builders::*;
 pub use cache::*;
+pub use compressed_image::*;
+pub use data::*;
+pub use events::*;
+pub use group::*;
 pub use handle::*;
+pub use hdr_image::*;
 pub use implementations::*;
+pub use label::*;
+pub use meta::*;
+pub use process::*;
+pub use registry::*;
 pub use types::*;
+pub use wait::*;
+#[cfg(feature = "wesl")]
+pub use wesl_loader::*;
 
 use crate::Context;
+use std::{
+    path::{Path, PathBuf},
+    sync::Arc,
+};
 
 //
 // Errors
@@ -20,6 +50,79 @@ use crate::Context;
 pub enum AssetError {
     #[error("asset path not found")]
     PathNotFound,
+    #[error("asset not found")]
+    NotFound,
+    #[error("asset failed to load")]
+    LoadFailed,
+    #[error("no loader registered for the extension of {0}")]
+    UnknownExtension(PathBuf),
+    #[error("no registered loader of {0} produces the requested asset type")]
+    LoaderNotFound(PathBuf),
+    #[error("labeled path {0} can only be loaded with a known asset type")]
+    UntypedLabel(PathBuf),
+    #[error("no sub asset of the requested type found at {0}")]
+    LabelNotFound(PathBuf),
+    /// Error of the loader, only returned by loads which don't go through the cache
+    #[error(transparent)]
+    Load(#[from] AssetLoadError),
+}
+
+/// Type erased error returned by an asset loader
+#[derive(Debug, Clone)]
+pub struct AssetLoadError {
+    path: Option<PathBuf>,
+    error: Arc<dyn std::error::Error + Send + Sync>,
+}
+
+impl AssetLoadError {
+    pub fn new(path: Option<&Path>, error: impl std::error::Error + Send + Sync + 'static) -> Self {
+        Self {
+            path: path.map(Path::to_path_buf),
+            error: Arc::new(error),
+        }
+    }
+
+    /// Path of the asset which failed to load
+    pub fn path(&self) -> Option<&Path> {
+        self.path.as_deref()
+    }
+
+    /// The error returned by the loader
+    pub fn error(&self) -> &(dyn std::error::Error + Send + Sync + 'static) {
+        self.error.as_ref()
+    }
+}
+
+impl std::fmt::Display for AssetLoadError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match &self.path {
+            Some(path) => write!(f, "could not load asset {}: {}", path.display(), self.error),
+            None => write!(f, "could not load asset: {}", self.error),
+        }
+    }
+}
+
+impl std::error::Error for AssetLoadError {
+    fn source(&self) -> Option<&(dyn std::error::Error + 'static)> {
+        Some(self.error.as_ref())
+    }
+}
+
+/// One based line and column of a byte offset, for parse error locations
+pub(crate) fn line_column(source: &str, offset: usize) -> (usize, usize) {
+    let mut offset = offset.min(source.len());
+    while !source.is_char_boundary(offset) {
+        offset -= 1;
+    }
+    let before = &source[..offset];
+    let line = before.matches('\n').count() + 1;
+    let column = before
+        .rfind('\n')
+        .map_or(before, |start| &before[start + 1..])
+        .chars()
+        .count()
+        + 1;
+    (line, column)
 }
 
 //
@@ -43,6 +146,16 @@ pub fn handle_loaded<T: Asset>(cache: &AssetCache, handle: AssetHandle<T>) -> bo
     cache.handle_loaded(handle.clone())
 }
 
+/// Check the load state of a specific asset
+pub fn load_state<T: 'static>(cache: &AssetCache, handle: AssetHandle<T>) -> AssetLoadState {
+    cache.load_state(handle)
+}
+
+/// Wait for a specific asset to finish loading
+pub fn wait<T: Asset>(cache: &AssetCache, handle: AssetHandle<T>) -> AssetWait<T> {
+    cache.wait(handle)
+}
+
 /// Check if a specific asset is loaded
 pub fn handle_just_loaded<T: Asset>(cache: &AssetCache, handle: AssetHandle<T>) -> bool {
     cache.handle_just_loaded(handle.clone())
@@ -51,7 +164,7 @@ pub fn handle_just_loaded<T: Asset>(cache: &AssetCache, handle: AssetHandle<T>)
 pub enum GetAssetResult<'a, T: Asset> {
     Loading,
     Success(&'a T),
-    Failed,
+    Failed(&'a dyn std::error::Error),
 }
 
 impl<'a, T: Asset> GetAssetResult<'a, T> {
@@ -59,7 +172,7 @@ impl<'a, T: Asset> GetAssetResult<'a, T> {
         match self {
             GetAssetResult::Success(asset) => asset,
             GetAssetResult::Loading => panic!("Asset is still loading"),
-            GetAssetResult::Failed => panic!("Asset failed to load"),
+            GetAssetResult::Failed(err) => panic!("Asset failed to load: {}", err),
         }
     }
 }
@@ -75,7 +188,7 @@ pub fn get<'a, T: Asset + 'static>(
 pub enum GetAssetResultMut<'a, T: Asset> {
     Loading,
     Loaded(&'a mut T),
-    Failed,
+    Failed(&'a dyn std::error::Error),
 }
 
 impl<'a, T: Asset> GetAssetResultMut<'a, T> {
@@ -83,7 +196,7 @@ impl<'a, T: Asset> GetAssetResultMut<'a, T> {
         match self {
             GetAssetResultMut::Loaded(asset) => asset,
             GetAssetResultMut::Loading => panic!("Asset is still loading"),
-            GetAssetResultMut::Failed => panic!("Asset failed to load"),
+            GetAssetResultMut::Failed(err) => panic!("Asset failed to load: {}", err),
         }
     }
 }
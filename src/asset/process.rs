@@ -0,0 +1,447 @@
+use super::AssetSettings;
+use crate::filesystem;
+use rustc_hash::FxHashMap;
+use std::{
+    cell::RefCell,
+    error,
+    path::{Path, PathBuf},
+    sync::Arc,
+};
+
+/// Turns source assets into engine ready data ahead of loading, e.g. decoded images with mipmaps
+pub trait AssetProcessor: Send + Sync + 'static {
+    /// Bump when the output changes so previously processed assets are not reused
+    const VERSION: u32;
+    type Error: error::Error + Send + Sync + 'static;
+    /// Read from `<asset>.meta`, usually the settings of the loader of the asset
+    type Settings: AssetSettings;
+
+    fn process(
+        &self,
+        ctx: &ProcessContext<'_>,
+        path: &Path,
+        source: &[u8],
+        settings: &Self::Settings,
+    ) -> Result<Vec<u8>, Self::Error>;
+}
+
+type ReadFn<'a> = dyn Fn(&Path) -> Result<Vec<u8>, filesystem::LoadFileError> + 'a;
+
+/// Gives processors access to other files, e.g. shader imports
+///
+/// Files read here are dependencies of the processed asset
+pub struct ProcessContext<'a> {
+    read: &'a ReadFn<'a>,
+    dependencies: RefCell<Vec<PathBuf>>,
+}
+
+impl<'a> ProcessContext<'a> {
+    pub fn new(read: &'a ReadFn<'a>) -> Self {
+        Self {
+            read,
+            dependencies: RefCell::new(Vec::new()),
+        }
+    }
+
+    pub fn read(&self, path: impl AsRef<Path>) -> Result<Vec<u8>, filesystem::LoadFileError> {
+        let path = path.as_ref();
+        self.dependencies.borrow_mut().push(path.to_path_buf());
+        (self.read)(path)
+    }
+
+    pub fn read_string(&self, path: impl AsRef<Path>) -> Result<String, filesystem::LoadFileError> {
+        String::from_utf8(self.read(path)?)
+            .map_err(|err| filesystem::LoadFileError::Other(Box::new(err)))
+    }
+
+    /// Every file read so far
+    pub fn dependencies(&self) -> Vec<PathBuf> {
+        self.dependencies.borrow().clone()
+    }
+}
+
+/// Output of a processor and the other files it was made from
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ProcessedAsset {
+    pub data: Vec<u8>,
+    pub dependencies: Vec<PathBuf>,
+}
+
+/// Path processed assets are stored at by `asset_cooker`, e.g. `textures/grass.png.processed`
+///
+/// Loaders prefer it over the source if it exists
+pub fn processed_path(path: &Path) -> PathBuf {
+    let mut processed = path.as_os_str().to_owned();
+    processed.push(".processed");
+    PathBuf::from(processed)
+}
+
+type DynProcessFn = Arc<
+    dyn Fn(&ProcessContext<'_>, &Path, &[u8], Option<&str>) -> Result<Vec<u8>, ProcessError>
+        + Send
+        + Sync,
+>;
+
+#[derive(Clone)]
+struct RegisteredProcessor {
+    name: &'static str,
+    version: u32,
+    process: DynProcessFn,
+}
+
+impl RegisteredProcessor {
+    fn process(
+        &self,
+        read: &ReadFn<'_>,
+        path: &Path,
+        source: &[u8],
+        meta: Option<&str>,
+    ) -> Result<ProcessedAsset, ProcessError> {
+        let ctx = ProcessContext::new(read);
+        let data = (self.process)(&ctx, path, source, meta)?;
+        Ok(ProcessedAsset {
+            data,
+            dependencies: ctx.dependencies(),
+        })
+    }
+}
+
+#[derive(thiserror::Error, Debug)]
+pub enum ProcessError {
+    #[error("could not process {0}: {1}")]
+    Process(PathBuf, Box<dyn error::Error + Send + Sync>),
+    #[error("could not parse settings of {0}: {1}")]
+    Settings(PathBuf, ron::error::SpannedError),
+    #[error("could not access processed asset {0}: {1}")]
+    Io(PathBuf, std::io::Error),
+}
+
+/// Processors registered by file extension
+#[derive(Clone)]
+pub struct AssetProcessors {
+    processors: FxHashMap<String, RegisteredProcessor>,
+}
+
+impl AssetProcessors {
+    /// No registered processors
+    pub fn empty() -> Self {
+        Self {
+            processors: FxHashMap::default(),
+        }
+    }
+
+    /// Register a processor for files with any of the extensions, replaces previous ones
+    pub fn register<P: AssetProcessor>(&mut self, processor: P, extensions: &[&str]) {
+        let processor = Arc::new(processor);
+        let registered = RegisteredProcessor {
+            name: std::any::type_name::<P>(),
+            version: P::VERSION,
+            process: Arc::new(move |ctx, path, source, meta| {
+                let settings = match meta {
+                    Some(meta) => ron::from_str(meta)
+                        .map_err(|err| ProcessError::Settings(path.to_path_buf(), err))?,
+                    None => P::Settings::default(),
+                };
+                processor
+                    .process(ctx, path, source, &settings)
+                    .map_err(|err| ProcessError::Process(path.to_path_buf(), Box::new(err)))
+            }),
+        };
+
+        for extension in extensions {
+            self.processors.insert(
+                extension.trim_start_matches('.').to_lowercase(),
+                registered.clone(),
+            );
+        }
+    }
+
+    fn find(&self, path: &Path) -> Option<&RegisteredProcessor> {
+        let extension = path.extension()?.to_str()?.to_lowercase();
+        self.processors.get(&extension)
+    }
+
+    /// Process an asset without caching, returns `None` if no processor is registered for it
+    ///
+    /// `meta` is the content of the `.meta` file of the asset and `read` loads its dependencies
+    pub fn process(
+        &self,
+        path: &Path,
+        source: &[u8],
+        meta: Option<&str>,
+        read: &ReadFn<'_>,
+    ) -> Option<Result<ProcessedAsset, ProcessError>> {
+        let processor = self.find(path)?;
+        Some(processor.process(read, path, source, meta))
+    }
+}
+
+impl std::fmt::Debug for AssetProcessors {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_set().entries(self.processors.keys()).finish()
+    }
+}
+
+/// Built-in processors for images and shaders
+impl Default for AssetProcessors {
+    fn default() -> Self {
+        let mut processors = Self::empty();
+        processors.register(
+            super::ImageProcessor::default(),
+            &["png", "jpg", "jpeg", "bmp", "tga"],
+        );
+        processors.register(super::ShaderProcessor::default(), &["wgsl"]);
+        processors
+    }
+}
+
+//
+// Cache
+//
+
+/// Content hashed directory of processed assets
+///
+/// Results are reused as long as the source, its settings, the files it was made from
+/// and the processor version are unchanged
+#[cfg(not(target_arch = "wasm32"))]
+#[derive(Debug, Clone)]
+pub struct ProcessedAssetCache {
+    dir: PathBuf,
+    processors: Arc<AssetProcessors>,
+}
+
+#[cfg(not(target_arch = "wasm32"))]
+impl ProcessedAssetCache {
+    pub fn new(dir: impl Into<PathBuf>, processors: AssetProcessors) -> Self {
+        Self {
+            dir: dir.into(),
+            processors: Arc::new(processors),
+        }
+    }
+
+    pub fn dir(&self) -> &Path {
+        &self.dir
+    }
+
+    /// Processed data of an asset, returns `None` if no processor is registered for it
+    ///
+    /// `meta` is the content of the `.meta` file of the asset and `read` loads its dependencies
+    pub fn process(
+        &self,
+        path: &Path,
+        source: &[u8],
+        meta: Option<&str>,
+        read: &ReadFn<'_>,
+    ) -> Option<Result<ProcessedAsset, ProcessError>> {
+        let processor = self.processors.find(path)?;
+        let processed_path = self.dir.join(format!(
+            "{:016x}",
+            processed_key(processor, path, source, meta)
+        ));
+
+        if let Some(processed) = load_cached(&processed_path, read) {
+            return Some(Ok(processed));
+        }
+
+        Some(self.process_uncached(processor, read, path, source, meta, &processed_path))
+    }
+
+    fn process_uncached(
+        &self,
+        processor: &RegisteredProcessor,
+        read: &ReadFn<'_>,
+        path: &Path,
+        source: &[u8],
+        meta: Option<&str>,
+        processed_path: &Path,
+    ) -> Result<ProcessedAsset, ProcessError> {
+        // remember the content of every dependency to tell when it changes
+        let dependencies = RefCell::new(Vec::new());
+        let read_dependency = |dependency: &Path| {
+            let bytes = read(dependency)?;
+            dependencies.borrow_mut().push(format!(
+                "{:016x} {}",
+                xxhash_rust::xxh3::xxh3_64(&bytes),
+                dependency.display()
+            ));
+            Ok(bytes)
+        };
+        let processed = processor.process(&read_dependency, path, source, meta)?;
+        let dependencies = dependencies.into_inner().join("\n");
+
+        // dependencies first since the processed data marks a complete entry
+        for (target, bytes) in [
+            (dependencies_path(processed_path), dependencies.as_bytes()),
+            (processed_path.to_path_buf(), processed.data.as_slice()),
+        ] {
+            // write to a temporary file first since other threads might read the same asset
+            let temporary_path =
+                target.with_extension(format!("{:?}.tmp", std::thread::current().id()));
+            std::fs::create_dir_all(&self.dir)
+                .and_then(|_| std::fs::write(&temporary_path, bytes))
+                .and_then(|_| std::fs::rename(&temporary_path, &target))
+                .map_err(|err| ProcessError::Io(target.clone(), err))?;
+        }
+
+        Ok(processed)
+    }
+}
+
+#[cfg(not(target_arch = "wasm32"))]
+fn dependencies_path(processed_path: &Path) -> PathBuf {
+    processed_path.with_extension("deps")
+}
+
+// previously processed data, if all of its dependencies are unchanged
+#[cfg(not(target_arch = "wasm32"))]
+fn load_cached(processed_path: &Path, read: &ReadFn<'_>) -> Option<ProcessedAsset> {
+    let data = std::fs::read(processed_path).ok()?;
+    let manifest = std::fs::read_to_string(dependencies_path(processed_path)).ok()?;
+
+    let mut dependencies = Vec::new();
+    for line in manifest.lines() {
+        let (hash, dependency) = line.split_once(' ')?;
+        let dependency = PathBuf::from(dependency);
+        let current = read(&dependency).ok()?;
+        if u64::from_str_radix(hash, 16).ok()? != xxhash_rust::xxh3::xxh3_64(&current) {
+            return None;
+        }
+        dependencies.push(dependency);
+    }
+
+    Some(ProcessedAsset { data, dependencies })
+}
+
+// hash of the source, its path and settings and the processor which produced it
+//
+// processors may resolve dependencies relative to the path, e.g. shader imports
+#[cfg(not(target_arch = "wasm32"))]
+fn processed_key(
+    processor: &RegisteredProcessor,
+    path: &Path,
+    source: &[u8],
+    meta: Option<&str>,
+) -> u64 {
+    let mut hasher = xxhash_rust::xxh3::Xxh3::new();
+    let path = filesystem::normalize_path(path);
+    let path = path.to_string_lossy();
+    hasher.update(&(path.len() as u64).to_le_bytes());
+    hasher.update(path.as_bytes());
+    hasher.update(&(source.len() as u64).to_le_bytes());
+    hasher.update(source);
+    hasher.update(processor.name.as_bytes());
+    hasher.update(&processor.version.to_le_bytes());
+    // a missing .meta file is not the same as an empty one
+    match meta {
+        Some(meta) => {
+            hasher.update(&[1]);
+            hasher.update(meta.as_bytes());
+        }
+        None => hasher.update(&[0]),
+    }
+    hasher.digest()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{AssetProcessor, AssetProcessors, ProcessContext, ProcessedAssetCache};
+    use crate::{asset::EmptyError, filesystem};
+    use serde::{Deserialize, Serialize};
+    use std::{
+        cell::RefCell,
+        path::Path,
+        sync::atomic::{AtomicU32, Ordering},
+    };
+
+    static PROCESSED: AtomicU32 = AtomicU32::new(0);
+
+    #[derive(Debug, Clone, Default, Serialize, Deserialize)]
+    struct AppendSettings {
+        suffix: String,
+    }
+
+    // uppercases the source and appends the suffix and the include file next to it
+    struct AppendProcessor;
+    impl AssetProcessor for AppendProcessor {
+        const VERSION: u32 = 1;
+        type Error = EmptyError;
+        type Settings = AppendSettings;
+
+        fn process(
+            &self,
+            ctx: &ProcessContext<'_>,
+            path: &Path,
+            source: &[u8],
+            settings: &Self::Settings,
+        ) -> Result<Vec<u8>, Self::Error> {
+            PROCESSED.fetch_add(1, Ordering::Relaxed);
+            let mut processed = source.to_ascii_uppercase();
+            processed.extend_from_slice(settings.suffix.as_bytes());
+            if let Ok(included) = ctx.read(path.with_file_name("include.txt")) {
+                processed.extend_from_slice(&included);
+            }
+            Ok(processed)
+        }
+    }
+
+    #[test]
+    fn test_processed_assets_are_reused() {
+        let dir = std::env::temp_dir().join(format!("gbase_processed_{}", std::process::id()));
+        let mut processors = AssetProcessors::empty();
+        processors.register(AppendProcessor, &["txt"]);
+        let cache = ProcessedAssetCache::new(&dir, processors);
+
+        let include = RefCell::new(b"!".to_vec());
+        let read = |path: &Path| match path.to_str() {
+            Some("include.txt") => Ok(include.borrow().clone()),
+            Some("a/include.txt") => Ok(b"a".to_vec()),
+            Some("b/include.txt") => Ok(b"b".to_vec()),
+            _ => Err(filesystem::LoadFileError::FileNotFound),
+        };
+        let process = |source: &[u8], meta: Option<&str>| {
+            cache
+                .process(Path::new("notes.txt"), source, meta, &read)
+                .unwrap()
+                .unwrap()
+        };
+
+        let processed = process(b"abc", None);
+        assert_eq!(processed.data, b"ABC!");
+        assert_eq!(processed.dependencies, [Path::new("include.txt")]);
+        assert_eq!(process(b"abc", None).data, b"ABC!");
+        assert_eq!(PROCESSED.load(Ordering::Relaxed), 1);
+
+        // source, settings and dependencies are part of the key
+        assert_eq!(process(b"abcd", None).data, b"ABCD!");
+        assert_eq!(PROCESSED.load(Ordering::Relaxed), 2);
+        assert_eq!(process(b"abc", Some("(suffix: \"?\")")).data, b"ABC?!");
+        assert_eq!(PROCESSED.load(Ordering::Relaxed), 3);
+        *include.borrow_mut() = b"#".to_vec();
+        assert_eq!(process(b"abc", None).data, b"ABC#");
+        assert_eq!(PROCESSED.load(Ordering::Relaxed), 4);
+        assert_eq!(process(b"abc", None).data, b"ABC#");
+        assert_eq!(PROCESSED.load(Ordering::Relaxed), 4);
+
+        // identical sources in sibling directories include different files
+        let process_at = |path: &str| {
+            cache
+                .process(Path::new(path), b"abc", None, &read)
+                .unwrap()
+                .unwrap()
+        };
+        assert_eq!(process_at("a/notes.txt").data, b"ABCa");
+        assert_eq!(process_at("b/notes.txt").data, b"ABCb");
+        assert_eq!(process_at("./a/notes.txt").data, b"ABCa");
+        assert_eq!(PROCESSED.load(Ordering::Relaxed), 6);
+
+        assert!(cache
+            .process(Path::new("notes.md"), b"abc", None, &read)
+            .is_none());
+        assert!(cache
+            .process(Path::new("notes.txt"), b"abc", Some("(suffix: 1)"), &read)
+            .unwrap()
+            .is_err());
+
+        std::fs::remove_dir_all(dir).unwrap();
+    }
+}
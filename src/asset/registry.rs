@@ -0,0 +1,106 @@
+use super::{AssetBuilder, AssetCache, AssetError, AssetLoader, DynAssetHandle};
+use rustc_hash::FxHashMap;
+use std::{
+    any::TypeId,
+    path::{Path, PathBuf},
+    sync::Arc,
+};
+
+pub type DynRegisteredLoadFn = Arc<dyn Fn(&mut AssetCache, PathBuf) -> DynAssetHandle>;
+
+#[derive(Clone)]
+pub(crate) struct RegisteredLoader {
+    pub(crate) asset_type: TypeId,
+    pub(crate) load: DynRegisteredLoadFn,
+}
+
+/// Loaders registered by file extension
+#[derive(Default)]
+pub(crate) struct LoaderRegistry {
+    loaders: FxHashMap<String, Vec<RegisteredLoader>>,
+}
+
+impl LoaderRegistry {
+    pub(crate) fn register<L: AssetLoader + Default + 'static>(&mut self, extensions: &[&str]) {
+        let loader = RegisteredLoader {
+            asset_type: TypeId::of::<L::Asset>(),
+            load: Arc::new(|cache, path| {
+                AssetBuilder::load(cache, path, L::default())
+                    .build(cache)
+                    .as_any()
+            }),
+        };
+
+        for extension in extensions {
+            let loaders = self
+                .loaders
+                .entry(extension.trim_start_matches('.').to_lowercase())
+                .or_default();
+
+            // replace loaders producing the same asset type
+            loaders.retain(|registered| registered.asset_type != loader.asset_type);
+            loaders.push(loader.clone());
+        }
+    }
+
+    /// Find a loader for a path, prefers the most recently registered one
+    pub(crate) fn find(
+        &self,
+        path: &Path,
+        asset_type: Option<TypeId>,
+    ) -> Result<RegisteredLoader, AssetError> {
+        let extension = path
+            .extension()
+            .and_then(|extension| extension.to_str())
+            .map(|extension| extension.to_lowercase())
+            .ok_or_else(|| AssetError::UnknownExtension(path.to_path_buf()))?;
+
+        let loaders = self
+            .loaders
+            .get(&extension)
+            .ok_or_else(|| AssetError::UnknownExtension(path.to_path_buf()))?;
+
+        loaders
+            .iter()
+            .rev()
+            .find(|loader| asset_type.is_none_or(|ty| ty == loader.asset_type))
+            .cloned()
+            .ok_or_else(|| AssetError::LoaderNotFound(path.to_path_buf()))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::LoaderRegistry;
+    use crate::{
+        asset::{AssetError, ImageLoader, ShaderLoader},
+        render,
+    };
+    use std::{any::TypeId, path::Path};
+
+    #[test]
+    fn test_find_loader_by_extension() {
+        let mut registry = LoaderRegistry::default();
+        registry.register::<ImageLoader>(&["png", "jpg"]);
+        registry.register::<ShaderLoader>(&[".wgsl"]);
+
+        let loader = registry
+            .find(Path::new("textures/grass.PNG"), None)
+            .unwrap();
+        assert_eq!(loader.asset_type, TypeId::of::<render::Image>());
+        let loader = registry.find(Path::new("shader.wgsl"), None).unwrap();
+        assert_eq!(loader.asset_type, TypeId::of::<render::ShaderBuilder>());
+
+        assert!(matches!(
+            registry.find(Path::new("notes.txt"), None),
+            Err(AssetError::UnknownExtension(_))
+        ));
+        assert!(matches!(
+            registry.find(
+                Path::new("grass.jpg"),
+                Some(TypeId::of::<render::ShaderBuilder>())
+            ),
+            Err(AssetError::LoaderNotFound(_))
+        ));
+    }
+}
@@ -1,11 +1,13 @@
-use super::{AssetCache, AssetHandle, LoadContext};
+use super::{AssetCache, AssetHandle, AssetId, LoadContext};
 use crate::{asset::LoadAssetResult, render::ArcHandle, Context};
 use core::error;
+use serde::{de::DeserializeOwned, Serialize};
 use std::{
     any::{Any, TypeId},
     fmt::Debug,
     future::Future,
-    path::Path,
+    hash::Hash,
+    path::{Path, PathBuf},
 };
 
 //
@@ -19,8 +21,12 @@ pub type DynAssetLoadFn = Box<dyn Fn(LoadContext, &Path) -> LoadAssetResult>;
 pub type DynAssetWriteFn = Box<dyn Fn(&mut DynAsset, &Path)>;
 pub type DynAssetOnLoadFn = Box<dyn Fn(&mut DynAsset)>;
 pub type TypedAssetOnLoadFn<T> = Box<dyn Fn(&mut T)>;
-pub type RenderAssetKey = (DynAssetHandle, TypeId);
+pub type RenderAssetKey = (AssetId, TypeId);
 pub type DynLoader = Box<dyn Any>;
+pub type LoadRequest = Box<dyn FnOnce(&mut AssetCache) + Send>;
+pub type DynAssetRetryFn = Box<dyn Fn(&mut AssetCache)>;
+/// (normalized path, loader type, loader settings hash)
+pub type AssetPathKey = (PathBuf, TypeId, u64);
 
 //
 // Traits
@@ -28,14 +34,33 @@ pub type DynLoader = Box<dyn Any>;
 
 pub trait Asset: Any + Send + Sync {} // TODO: is this even needed? or maybe rename
 
-pub trait AssetLoader: Send + Sync + Clone {
+/// Import settings which can be stored in a `.meta` file next to an asset
+pub trait AssetSettings:
+    Serialize + DeserializeOwned + Default + Clone + Send + Sync + 'static
+{
+}
+impl<T: Serialize + DeserializeOwned + Default + Clone + Send + Sync + 'static> AssetSettings
+    for T
+{
+}
+
+/// Loaders are hashed to tell apart loads of the same path with different settings
+pub trait AssetLoader: Send + Sync + Clone + Hash {
     type Asset: Asset;
-    type Error: error::Error;
+    type Error: error::Error + Send + Sync + 'static;
+    /// Read from `<asset>.meta` if it exists
+    type Settings: AssetSettings;
+
+    /// Settings used for assets without a `.meta` file
+    fn settings(&self) -> Self::Settings {
+        Self::Settings::default()
+    }
 
     fn load(
         &self,
         load_ctx: LoadContext,
         path: &Path,
+        settings: &Self::Settings,
     ) -> impl Future<Output = Result<Self::Asset, Self::Error>>;
 }
 
@@ -43,7 +68,12 @@ pub trait AssetWriter: AssetLoader {
     fn write(asset: &Self::Asset, path: &Path);
 }
 
-pub trait DerivedAsset: Any {} // TODO: is this even needed? or maybe rename
+pub trait DerivedAsset: Any {
+    /// Bytes of GPU memory held, counted against the asset cache GPU budget
+    fn gpu_memory_size(&self) -> u64 {
+        0
+    }
+}
 
 pub trait AssetConverter {
     type SourceAsset: Asset;
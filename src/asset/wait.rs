@@ -0,0 +1,109 @@
+use super::{AssetError, AssetHandle, AssetId};
+use rustc_hash::FxHashMap;
+use std::{
+    future::Future,
+    pin::Pin,
+    sync::{Arc, Mutex},
+    task::{Context, Poll, Waker},
+};
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum AssetLoadState {
+    Loading,
+    Loaded,
+    Failed,
+}
+
+//
+// Waiters
+//
+
+/// Load states shared between the cache and futures waiting on assets
+#[derive(Debug, Clone, Default)]
+pub(crate) struct AssetWaiters {
+    inner: Arc<Mutex<AssetWaitersInner>>,
+}
+
+#[derive(Debug, Default)]
+struct AssetWaitersInner {
+    states: FxHashMap<AssetId, AssetLoadState>,
+    wakers: FxHashMap<AssetId, Vec<Waker>>,
+}
+
+impl AssetWaiters {
+    pub(crate) fn set(&self, id: AssetId, state: AssetLoadState) {
+        let mut inner = self.inner.lock().expect("could not unlock asset waiters");
+        inner.states.insert(id, state);
+        if state != AssetLoadState::Loading {
+            wake(&mut inner, id);
+        }
+    }
+
+    pub(crate) fn remove(&self, id: AssetId) {
+        let mut inner = self.inner.lock().expect("could not unlock asset waiters");
+        inner.states.remove(&id);
+        wake(&mut inner, id);
+    }
+}
+
+fn wake(inner: &mut AssetWaitersInner, id: AssetId) {
+    if let Some(wakers) = inner.wakers.remove(&id) {
+        for waker in wakers {
+            waker.wake();
+        }
+    }
+}
+
+//
+// Future
+//
+
+/// Future which resolves when an asset is done loading
+///
+/// Does not borrow the cache, so it can be moved into spawned tasks
+pub struct AssetWait<T: 'static> {
+    handle: Option<AssetHandle<T>>,
+    waiters: AssetWaiters,
+}
+
+impl<T: 'static> AssetWait<T> {
+    pub(crate) fn new(handle: AssetHandle<T>, waiters: AssetWaiters) -> Self {
+        Self {
+            handle: Some(handle),
+            waiters,
+        }
+    }
+}
+
+// never structurally pinned, the handle only holds a phantom `T`
+impl<T: 'static> Unpin for AssetWait<T> {}
+
+impl<T: 'static> Future for AssetWait<T> {
+    type Output = Result<AssetHandle<T>, AssetError>;
+
+    fn poll(self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Self::Output> {
+        let this = self.get_mut();
+        let id = this
+            .handle
+            .as_ref()
+            .expect("asset wait polled after completion")
+            .id();
+
+        let mut inner = this
+            .waiters
+            .inner
+            .lock()
+            .expect("could not unlock asset waiters");
+        match inner.states.get(&id) {
+            Some(AssetLoadState::Loaded) => {
+                Poll::Ready(Ok(this.handle.take().expect("handle already taken")))
+            }
+            Some(AssetLoadState::Failed) => Poll::Ready(Err(AssetError::LoadFailed)),
+            None => Poll::Ready(Err(AssetError::NotFound)),
+            Some(AssetLoadState::Loading) => {
+                inner.wakers.entry(id).or_default().push(cx.waker().clone());
+                Poll::Pending
+            }
+        }
+    }
+}
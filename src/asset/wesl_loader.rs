@@ -0,0 +1,322 @@
+use super::{line_column, AssetLoader, LoadContext};
+use crate::{filesystem, render};
+use rustc_hash::FxHashMap;
+use serde::{Deserialize, Serialize};
+use std::{
+    borrow::Cow,
+    cell::RefCell,
+    collections::BTreeMap,
+    hash::{Hash, Hasher},
+    path::{Path, PathBuf},
+};
+use wesl::{syntax::PathOrigin, ModulePath, Resolver};
+
+#[derive(thiserror::Error, Debug)]
+pub enum WeslShaderError {
+    #[error(transparent)]
+    Load(#[from] filesystem::LoadFileError),
+    #[error("{path:?} is not inside the WESL package root {root:?}")]
+    OutsideRoot { path: PathBuf, root: PathBuf },
+    #[error("{}", format_location(.path, .line, .column, .message))]
+    Compile {
+        /// Asset path, or package module path, of the module with the error
+        path: Option<String>,
+        line: Option<usize>,
+        column: Option<usize>,
+        message: String,
+    },
+}
+
+fn format_location(
+    path: &Option<String>,
+    line: &Option<usize>,
+    column: &Option<usize>,
+    message: &str,
+) -> String {
+    match (path, line, column) {
+        (Some(path), Some(line), Some(column)) => format!("{path}:{line}:{column}: {message}"),
+        (Some(path), _, _) => format!("{path}: {message}"),
+        _ => message.to_string(),
+    }
+}
+
+/// WESL features from `<shader>.wesl.meta`, used by `@if` attributes
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct WeslShaderSettings {
+    pub features: BTreeMap<String, bool>,
+}
+
+/// Compiles WESL shaders, imported modules hot reload the shader too
+///
+/// `package::` imports resolve from [`root`](Self::root) in the assets folder,
+/// trying `.wesl` then `.wgsl`. Other imports resolve from the added packages,
+/// e.g. ones built with `wesl::PkgBuilder`
+#[derive(Debug, Clone, Default)]
+pub struct WeslShaderLoader {
+    root: PathBuf,
+    packages: Vec<&'static wesl::CodegenPkg>,
+}
+
+impl WeslShaderLoader {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Asset path of the package root, defaults to the assets folder
+    pub fn root(mut self, value: impl Into<PathBuf>) -> Self {
+        self.root = value.into();
+        self
+    }
+
+    pub fn package(mut self, value: &'static wesl::CodegenPkg) -> Self {
+        self.packages.push(value);
+        self
+    }
+
+    fn module_path(&self, path: &Path) -> Result<ModulePath, WeslShaderError> {
+        let root = filesystem::normalize_path(&self.root);
+        let relative = path
+            .strip_prefix(&root)
+            .map_err(|_| WeslShaderError::OutsideRoot {
+                path: path.to_path_buf(),
+                root: root.clone(),
+            })?;
+        let components = relative
+            .with_extension("")
+            .iter()
+            .map(|component| component.to_string_lossy().to_string())
+            .collect();
+        Ok(ModulePath::new(PathOrigin::Absolute, components))
+    }
+
+    fn asset_path(&self, module: &ModulePath, extension: &str) -> PathBuf {
+        let path = module
+            .components
+            .iter()
+            .fold(self.root.clone(), |path, component| path.join(component));
+        filesystem::normalize_path(path.with_extension(extension))
+    }
+}
+
+impl Hash for WeslShaderLoader {
+    fn hash<H: Hasher>(&self, state: &mut H) {
+        self.root.hash(state);
+        for package in &self.packages {
+            package.crate_name.hash(state);
+            package.root.name.hash(state);
+        }
+    }
+}
+
+impl AssetLoader for WeslShaderLoader {
+    type Asset = render::ShaderBuilder;
+    type Error = WeslShaderError;
+    type Settings = WeslShaderSettings;
+
+    async fn load(
+        &self,
+        load_ctx: LoadContext,
+        path: &Path,
+        settings: &Self::Settings,
+    ) -> Result<Self::Asset, Self::Error> {
+        let path = filesystem::normalize_path(path);
+        let root = self.module_path(&path)?;
+
+        // the resolver can't load asynchronously, so compile until no module is missing
+        let mut modules = FxHashMap::default();
+        modules.insert(
+            root.clone(),
+            ModuleSource {
+                path: path.clone(),
+                source: load_ctx.load_string(&path).await?,
+            },
+        );
+        let source = loop {
+            let (missing, err) = match compile(&root, &modules, &self.packages, settings) {
+                Ok(source) => break source,
+                Err(CompileError::Missing(missing, err)) => (missing, err),
+                Err(CompileError::Failed(err)) => return Err(*err),
+            };
+
+            let mut loaded = None;
+            for extension in ["wesl", "wgsl"] {
+                let module_path = self.asset_path(&missing, extension);
+                match load_ctx.load_string(&module_path).await {
+                    Ok(source) => {
+                        loaded = Some(ModuleSource {
+                            path: module_path,
+                            source,
+                        });
+                        break;
+                    }
+                    Err(filesystem::LoadFileError::FileNotFound) => {}
+                    Err(err) => return Err(err.into()),
+                }
+            }
+            let Some(module) = loaded else {
+                return Err(*err);
+            };
+            load_ctx.add_file_dependency(&module.path);
+            modules.insert(missing, module);
+        };
+
+        Ok(render::ShaderBuilder::new(source).label(
+            path.to_str()
+                .expect("could not convert path to string")
+                .to_string(),
+        ))
+    }
+}
+
+//
+// Compile
+//
+
+struct ModuleSource {
+    path: PathBuf,
+    source: String,
+}
+
+enum CompileError {
+    /// Local module that has not been loaded yet, with the error if it can't be
+    Missing(ModulePath, Box<WeslShaderError>),
+    Failed(Box<WeslShaderError>),
+}
+
+struct AssetResolver<'a> {
+    modules: &'a FxHashMap<ModulePath, ModuleSource>,
+    packages: wesl::PkgResolver,
+    missing: RefCell<Option<ModulePath>>,
+}
+
+impl Resolver for AssetResolver<'_> {
+    fn resolve_source<'a>(&'a self, path: &ModulePath) -> Result<Cow<'a, str>, wesl::ResolveError> {
+        if let PathOrigin::Package(_) = path.origin {
+            return self.packages.resolve_source(path);
+        }
+        match self.modules.get(path) {
+            Some(module) => Ok(Cow::Borrowed(&module.source)),
+            None => {
+                self.missing
+                    .borrow_mut()
+                    .get_or_insert_with(|| path.clone());
+                Err(wesl::ResolveError::ModuleNotFound(
+                    path.clone(),
+                    String::from("no .wesl or .wgsl file in the assets folder"),
+                ))
+            }
+        }
+    }
+
+    fn display_name(&self, path: &ModulePath) -> Option<String> {
+        match self.modules.get(path) {
+            Some(module) => Some(module.path.display().to_string()),
+            None => Some(path.to_string()),
+        }
+    }
+}
+
+fn compile(
+    root: &ModulePath,
+    modules: &FxHashMap<ModulePath, ModuleSource>,
+    packages: &[&'static wesl::CodegenPkg],
+    settings: &WeslShaderSettings,
+) -> Result<String, CompileError> {
+    let mut resolver = AssetResolver {
+        modules,
+        packages: wesl::PkgResolver::new(),
+        missing: RefCell::new(None),
+    };
+    for package in packages {
+        resolver.packages.add_package(package);
+    }
+
+    let mut compiler = wesl::Wesl::new("").set_custom_resolver(&resolver);
+    for (feature, enabled) in &settings.features {
+        compiler.set_feature(feature, *enabled);
+    }
+
+    match compiler.compile(root) {
+        Ok(result) => Ok(result.to_string()),
+        Err(err) => {
+            let err = Box::new(compile_error(err, &resolver));
+            match resolver.missing.take() {
+                Some(missing) => Err(CompileError::Missing(missing, err)),
+                None => Err(CompileError::Failed(err)),
+            }
+        }
+    }
+}
+
+// point at the module and line of the error rather than the compiled output
+fn compile_error(err: wesl::Error, resolver: &AssetResolver<'_>) -> WeslShaderError {
+    let diagnostic = wesl::Diagnostic::from(err);
+    let detail = &diagnostic.detail;
+    let path = detail
+        .module_path
+        .as_ref()
+        .and_then(|path| resolver.display_name(path))
+        .or_else(|| detail.display_name.clone());
+    let location = detail
+        .span
+        .zip(detail.source.as_deref())
+        .map(|(span, source)| line_column(source, span.start));
+
+    WeslShaderError::Compile {
+        path,
+        line: location.map(|(line, _)| line),
+        column: location.map(|(_, column)| column),
+        message: diagnostic.error.to_string(),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{compile, CompileError, ModuleSource, WeslShaderError, WeslShaderSettings};
+    use rustc_hash::FxHashMap;
+    use std::path::PathBuf;
+    use wesl::ModulePath;
+
+    #[test]
+    fn test_compile_wesl_modules() {
+        let module = |path: &str, source: &str| ModuleSource {
+            path: PathBuf::from(path),
+            source: source.to_string(),
+        };
+        let root = "package::main".parse::<ModulePath>().unwrap();
+        let color = "package::util::color".parse::<ModulePath>().unwrap();
+        let mut modules = FxHashMap::from_iter([(
+            root.clone(),
+            module(
+                "main.wesl",
+                "import package::util::color::RED;\n@fragment fn fs_main() -> @location(0) vec4f { return RED; }",
+            ),
+        )]);
+        let settings = WeslShaderSettings::default();
+
+        // imported modules are requested one at a time
+        match compile(&root, &modules, &[], &settings) {
+            Err(CompileError::Missing(missing, _)) => assert_eq!(missing, color),
+            _ => panic!("expected missing module"),
+        }
+
+        modules.insert(
+            color.clone(),
+            module("util/color.wesl", "const RED = vec4f(1.0, 0.0, 0.0, 1.0)"),
+        );
+        let Err(CompileError::Failed(err)) = compile(&root, &modules, &[], &settings) else {
+            panic!("expected compile error");
+        };
+        let WeslShaderError::Compile { path, line, .. } = *err else {
+            panic!("expected compile error");
+        };
+        assert_eq!(path.as_deref(), Some("util/color.wesl"));
+        assert_eq!(line, Some(1));
+
+        modules.get_mut(&color).unwrap().source.push(';');
+        let Ok(source) = compile(&root, &modules, &[], &settings) else {
+            panic!("expected shader to compile");
+        };
+        assert!(source.contains("fs_main"));
+    }
+}
@@ -1,7 +1,15 @@
+mod pack;
 mod platforms;
+mod save;
+mod vfs;
+mod watch;
 use std::path::{self, PathBuf};
 
+pub use pack::*;
 pub use platforms::*;
+pub use save::*;
+pub use vfs::*;
+pub use watch::*;
 
 pub fn normalize_path(path: impl AsRef<std::path::Path>) -> PathBuf {
     let mut out = PathBuf::new();
@@ -64,11 +72,36 @@ pub fn load_temporary_string(
     ctx.filesystem.load_temporary_string(path)
 }
 
-// TODO: use filesystem context
+/// Path to temporary storage folder, configured with `ContextBuilder::temporary_path`
+pub fn temporary_path(ctx: &crate::Context) -> &std::path::Path {
+    ctx.filesystem.temporary_path()
+}
+
+/// Mount a source into the virtual filesystem used for loading assets
+///
+/// The assets folder is mounted at the root with priority 0
+pub fn mount(
+    ctx: &crate::Context,
+    prefix: impl AsRef<std::path::Path>,
+    priority: i32,
+    source: impl MountSource,
+) {
+    ctx.filesystem.vfs().mount(prefix, priority, source);
+}
 
-/// Path to temporary storage folder
-pub fn tmp_path() -> &'static str {
-    "assets/tmp"
+pub fn unmount(ctx: &crate::Context, prefix: impl AsRef<std::path::Path>) {
+    ctx.filesystem.vfs().unmount(prefix);
+}
+
+/// Watch files or directories outside of the asset system, relative paths start at the working dir
+///
+/// Poll the returned watcher for changes, e.g. once per frame. Not supported on wasm
+pub fn watch(
+    ctx: &crate::Context,
+    path: impl AsRef<std::path::Path>,
+    recursive: bool,
+) -> Result<FileWatcher, WatchError> {
+    ctx.filesystem.watch(path.as_ref(), recursive)
 }
 
 /// Load bytes from assets folder
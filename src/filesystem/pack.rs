@@ -0,0 +1,469 @@
+use super::{normalize_path, LoadFileError, LoadFileFuture, MountSource};
+use rustc_hash::FxHashMap;
+use std::{
+    io::{Read, Write},
+    path::{Path, PathBuf},
+    sync::Arc,
+};
+
+const PACK_MAGIC: &[u8; 8] = b"GBPACK\0\0";
+const PACK_VERSION: u32 = 2;
+const PACK_HEADER_SIZE: usize = 8 + 4 + 4 + 8;
+// sizes come from the index, larger outputs grow as they are decompressed
+const MAX_PREALLOCATION: u64 = 16 * 1024 * 1024;
+
+#[derive(thiserror::Error, Debug)]
+pub enum PackError {
+    #[error("not a gbase pack")]
+    InvalidMagic,
+    #[error("unsupported pack version {0}")]
+    UnsupportedVersion(u32),
+    #[error("pack index is corrupted")]
+    CorruptedIndex,
+    #[error("checksum mismatch for {0}")]
+    ChecksumMismatch(PathBuf),
+    #[error("could not decompress {0}: {1}")]
+    Decompress(PathBuf, std::io::Error),
+    #[error(transparent)]
+    Io(#[from] std::io::Error),
+}
+
+/// Compression of a single pack entry
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum PackCompression {
+    None,
+    Deflate,
+    Zstd,
+}
+
+impl PackCompression {
+    fn from_u8(value: u8) -> Option<Self> {
+        match value {
+            0 => Some(Self::None),
+            1 => Some(Self::Deflate),
+            2 => Some(Self::Zstd),
+            _ => None,
+        }
+    }
+
+    fn as_u8(self) -> u8 {
+        match self {
+            Self::None => 0,
+            Self::Deflate => 1,
+            Self::Zstd => 2,
+        }
+    }
+
+    fn compress(self, data: &[u8]) -> std::io::Result<Vec<u8>> {
+        match self {
+            Self::None => Ok(data.to_vec()),
+            Self::Deflate => {
+                let mut encoder =
+                    flate2::write::DeflateEncoder::new(Vec::new(), flate2::Compression::best());
+                encoder.write_all(data)?;
+                encoder.finish()
+            }
+            Self::Zstd => zstd::bulk::compress(data, zstd::DEFAULT_COMPRESSION_LEVEL),
+        }
+    }
+
+    /// Streams the data, which must decompress to exactly `size` bytes
+    fn decompress(self, data: Vec<u8>, size: u64) -> std::io::Result<Vec<u8>> {
+        // one byte more than expected is enough to tell the size is wrong
+        let read_limited = |reader: &mut dyn Read| {
+            let mut decompressed = Vec::with_capacity(size.min(MAX_PREALLOCATION) as usize);
+            reader
+                .take(size.saturating_add(1))
+                .read_to_end(&mut decompressed)?;
+            Ok::<_, std::io::Error>(decompressed)
+        };
+        let decompressed = match self {
+            Self::None => data,
+            Self::Deflate => read_limited(&mut flate2::read::DeflateDecoder::new(data.as_slice()))?,
+            Self::Zstd => read_limited(&mut zstd::stream::read::Decoder::with_buffer(
+                data.as_slice(),
+            )?)?,
+        };
+
+        if decompressed.len() as u64 != size {
+            return Err(std::io::Error::new(
+                std::io::ErrorKind::InvalidData,
+                format!(
+                    "decompressed to {} bytes, index says {}",
+                    decompressed.len(),
+                    size
+                ),
+            ));
+        }
+        Ok(decompressed)
+    }
+}
+
+#[derive(Debug, Clone)]
+struct PackEntry {
+    offset: u64,
+    stored_size: u64,
+    size: u64,
+    compression: PackCompression,
+    checksum: u32,
+}
+
+// storage of the pack, entries are read on demand
+#[derive(Debug)]
+enum PackData {
+    Memory(Arc<[u8]>),
+    #[cfg(not(target_arch = "wasm32"))]
+    File {
+        file: std::sync::Mutex<std::fs::File>,
+        len: u64,
+    },
+}
+
+impl PackData {
+    fn len(&self) -> u64 {
+        match self {
+            PackData::Memory(data) => data.len() as u64,
+            #[cfg(not(target_arch = "wasm32"))]
+            PackData::File { len, .. } => *len,
+        }
+    }
+
+    /// Ranges outside of the pack are a corrupted index
+    fn read(&self, offset: u64, size: u64) -> Result<Vec<u8>, PackError> {
+        match offset.checked_add(size) {
+            Some(end) if end <= self.len() => {}
+            _ => return Err(PackError::CorruptedIndex),
+        }
+
+        match self {
+            PackData::Memory(data) => Ok(data[offset as usize..(offset + size) as usize].to_vec()),
+            #[cfg(not(target_arch = "wasm32"))]
+            PackData::File { file, .. } => {
+                use std::io::Seek;
+                let mut file = file.lock().expect("could not lock pack file");
+                let mut bytes = vec![0; size as usize];
+                file.seek(std::io::SeekFrom::Start(offset))?;
+                file.read_exact(&mut bytes)?;
+                Ok(bytes)
+            }
+        }
+    }
+}
+
+/// Single file archive with a path index and per entry compression and checksums
+///
+/// Layout (little endian):
+/// - magic, version: u32, entry count: u32, index size: u64
+/// - per entry: path length: u32, path: utf8, offset: u64, stored size: u64, size: u64,
+///   compression: u8, crc32 of the uncompressed data: u32
+/// - entry data
+///
+/// Only the index is read up front, entries are read and decompressed when loaded
+#[derive(Debug, Clone)]
+pub struct PackMount {
+    data: Arc<PackData>,
+    index: Arc<FxHashMap<PathBuf, PackEntry>>,
+}
+
+impl PackMount {
+    pub fn from_bytes(data: impl Into<Arc<[u8]>>) -> Result<Self, PackError> {
+        Self::new(PackData::Memory(data.into()))
+    }
+
+    /// Open a pack on disk, entries are read with random access
+    #[cfg(not(target_arch = "wasm32"))]
+    pub fn open(path: impl AsRef<Path>) -> Result<Self, PackError> {
+        let file = std::fs::File::open(path)?;
+        let len = file.metadata()?.len();
+        Self::new(PackData::File {
+            file: std::sync::Mutex::new(file),
+            len,
+        })
+    }
+
+    /// Load a whole pack with a single request through the filesystem, e.g. a fetch on wasm
+    pub async fn load(
+        filesystem: &super::FileSystemContext,
+        path: impl AsRef<Path>,
+    ) -> Result<Self, LoadFileError> {
+        let bytes = filesystem.load_asset_bytes(path).await?;
+        Self::from_bytes(bytes).map_err(|err| LoadFileError::Other(Box::new(err)))
+    }
+
+    fn new(data: PackData) -> Result<Self, PackError> {
+        // too small to be a pack
+        let header = match data.read(0, PACK_HEADER_SIZE as u64) {
+            Err(PackError::CorruptedIndex) => return Err(PackError::InvalidMagic),
+            result => result?,
+        };
+        let mut reader = PackReader {
+            data: &header,
+            pos: 0,
+        };
+        if reader.bytes(PACK_MAGIC.len())? != PACK_MAGIC {
+            return Err(PackError::InvalidMagic);
+        }
+        let version = reader.u32()?;
+        if version != PACK_VERSION {
+            return Err(PackError::UnsupportedVersion(version));
+        }
+        let count = reader.u32()?;
+        let index_size = reader.u64()?;
+
+        let index_bytes = data.read(PACK_HEADER_SIZE as u64, index_size)?;
+        let mut reader = PackReader {
+            data: &index_bytes,
+            pos: 0,
+        };
+        let mut index = FxHashMap::default();
+        for _ in 0..count {
+            let path_len = reader.u32()? as usize;
+            let path = std::str::from_utf8(reader.bytes(path_len)?)
+                .map_err(|_| PackError::CorruptedIndex)?;
+            let entry = PackEntry {
+                offset: reader.u64()?,
+                stored_size: reader.u64()?,
+                size: reader.u64()?,
+                compression: PackCompression::from_u8(reader.u8()?)
+                    .ok_or(PackError::CorruptedIndex)?,
+                checksum: reader.u32()?,
+            };
+            match entry.offset.checked_add(entry.stored_size) {
+                Some(end) if end <= data.len() => {}
+                _ => return Err(PackError::CorruptedIndex),
+            }
+            index.insert(normalize_path(path), entry);
+        }
+
+        Ok(Self {
+            data: Arc::new(data),
+            index: Arc::new(index),
+        })
+    }
+
+    /// Paths of all files in the pack
+    pub fn paths(&self) -> impl Iterator<Item = &Path> {
+        self.index.keys().map(|path| path.as_path())
+    }
+
+    /// Read, decompress and verify a single entry
+    pub fn read(&self, path: impl AsRef<Path>) -> Result<Option<Vec<u8>>, PackError> {
+        let path = normalize_path(path);
+        let Some(entry) = self.index.get(&path) else {
+            return Ok(None);
+        };
+
+        let stored = self.data.read(entry.offset, entry.stored_size)?;
+        let data = entry
+            .compression
+            .decompress(stored, entry.size)
+            .map_err(|err| PackError::Decompress(path.clone(), err))?;
+        if crc32fast::hash(&data) != entry.checksum {
+            return Err(PackError::ChecksumMismatch(path));
+        }
+
+        Ok(Some(data))
+    }
+}
+
+impl MountSource for PackMount {
+    fn load<'a>(&'a self, path: &'a Path) -> LoadFileFuture<'a> {
+        let result = match self.read(path) {
+            Ok(Some(data)) => Ok(data),
+            Ok(None) => Err(LoadFileError::FileNotFound),
+            Err(err) => Err(LoadFileError::Other(Box::new(err))),
+        };
+        Box::pin(std::future::ready(result))
+    }
+}
+
+struct PackReader<'a> {
+    data: &'a [u8],
+    pos: usize,
+}
+
+impl<'a> PackReader<'a> {
+    fn bytes(&mut self, len: usize) -> Result<&'a [u8], PackError> {
+        let bytes = self
+            .data
+            .get(self.pos..self.pos + len)
+            .ok_or(PackError::CorruptedIndex)?;
+        self.pos += len;
+        Ok(bytes)
+    }
+    fn u8(&mut self) -> Result<u8, PackError> {
+        Ok(self.bytes(1)?[0])
+    }
+    fn u32(&mut self) -> Result<u32, PackError> {
+        Ok(u32::from_le_bytes(self.bytes(4)?.try_into().unwrap()))
+    }
+    fn u64(&mut self) -> Result<u64, PackError> {
+        Ok(u64::from_le_bytes(self.bytes(8)?.try_into().unwrap()))
+    }
+}
+
+//
+// Builder
+//
+
+#[derive(Debug, Clone)]
+pub struct PackBuilder {
+    files: Vec<(PathBuf, Vec<u8>, PackCompression)>,
+    compression: PackCompression,
+}
+
+impl Default for PackBuilder {
+    fn default() -> Self {
+        Self {
+            files: Vec::new(),
+            compression: PackCompression::Zstd,
+        }
+    }
+}
+
+impl PackBuilder {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Compression of files added afterwards, zstd by default
+    pub fn compression(mut self, value: PackCompression) -> Self {
+        self.compression = value;
+        self
+    }
+
+    pub fn file(mut self, path: impl AsRef<Path>, data: Vec<u8>) -> Self {
+        self.add_file(path, data);
+        self
+    }
+
+    pub fn add_file(&mut self, path: impl AsRef<Path>, data: Vec<u8>) {
+        self.files
+            .push((normalize_path(path), data, self.compression));
+    }
+
+    /// Entries which do not get smaller are stored uncompressed
+    pub fn build(&self) -> std::io::Result<Vec<u8>> {
+        let mut index = Vec::new();
+        let mut offset_positions = Vec::new();
+        let mut stored_data = Vec::new();
+        for (path, data, compression) in &self.files {
+            let (stored, compression) = match compression.compress(data)? {
+                compressed if compressed.len() < data.len() => (compressed, *compression),
+                _ => (data.clone(), PackCompression::None),
+            };
+
+            let path = path.to_string_lossy().replace('\\', "/");
+            index.extend_from_slice(&(path.len() as u32).to_le_bytes());
+            index.extend_from_slice(path.as_bytes());
+            // offset is patched once the index size is known
+            offset_positions.push(index.len());
+            index.extend_from_slice(&[0; 8]);
+            index.extend_from_slice(&(stored.len() as u64).to_le_bytes());
+            index.extend_from_slice(&(data.len() as u64).to_le_bytes());
+            index.push(compression.as_u8());
+            index.extend_from_slice(&crc32fast::hash(data).to_le_bytes());
+            stored_data.push(stored);
+        }
+
+        let mut offset = (PACK_HEADER_SIZE + index.len()) as u64;
+        for (position, stored) in offset_positions.into_iter().zip(&stored_data) {
+            index[position..position + 8].copy_from_slice(&offset.to_le_bytes());
+            offset += stored.len() as u64;
+        }
+
+        let mut bytes = Vec::with_capacity(offset as usize);
+        bytes.extend_from_slice(PACK_MAGIC);
+        bytes.extend_from_slice(&PACK_VERSION.to_le_bytes());
+        bytes.extend_from_slice(&(self.files.len() as u32).to_le_bytes());
+        bytes.extend_from_slice(&(index.len() as u64).to_le_bytes());
+        bytes.extend_from_slice(&index);
+        for stored in stored_data {
+            bytes.extend_from_slice(&stored);
+        }
+
+        Ok(bytes)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{PackBuilder, PackCompression, PackError, PackMount};
+    use crate::filesystem::{LoadFileError, MountSource};
+    use std::path::Path;
+
+    #[test]
+    fn test_pack_roundtrip() {
+        let text = "fn main() {}\n".repeat(64).into_bytes();
+        let bytes = PackBuilder::new()
+            .file("shaders/zstd.wgsl", text.clone())
+            .compression(PackCompression::Deflate)
+            .file("shaders/deflate.wgsl", text.clone())
+            .compression(PackCompression::None)
+            .file("./textures/grass.png", vec![1, 2, 3])
+            .build()
+            .unwrap();
+        assert!(bytes.len() < text.len());
+        let pack = PackMount::from_bytes(bytes.clone()).unwrap();
+
+        let load = |path| pollster::block_on(pack.load(Path::new(path)));
+        assert_eq!(load("shaders/zstd.wgsl").unwrap(), text);
+        assert_eq!(load("shaders/deflate.wgsl").unwrap(), text);
+        assert_eq!(load("textures/grass.png").unwrap(), vec![1, 2, 3]);
+        assert!(matches!(load("missing"), Err(LoadFileError::FileNotFound)));
+
+        // flip the last byte of the uncompressed entry
+        let mut corrupted = bytes;
+        *corrupted.last_mut().unwrap() ^= 0xff;
+        let pack = PackMount::from_bytes(corrupted).unwrap();
+        assert!(matches!(
+            pack.read("textures/grass.png"),
+            Err(PackError::ChecksumMismatch(_))
+        ));
+
+        assert!(matches!(
+            PackMount::from_bytes(b"not a pack".to_vec()),
+            Err(PackError::InvalidMagic)
+        ));
+    }
+
+    #[test]
+    fn test_pack_corrupted_sizes() {
+        let text = "fn main() {}\n".repeat(64).into_bytes();
+        let bytes = PackBuilder::new()
+            .file("a.wgsl", text.clone())
+            .build()
+            .unwrap();
+        // index layout: header, path length, path, offset, stored size, size
+        let stored_size_position = super::PACK_HEADER_SIZE + 4 + "a.wgsl".len() + 8;
+        let size_position = stored_size_position + 8;
+        let with_u64 = |position: usize, value: u64| {
+            let mut bytes = bytes.clone();
+            bytes[position..position + 8].copy_from_slice(&value.to_le_bytes());
+            bytes
+        };
+
+        // truncated data and entries pointing past the end
+        assert!(matches!(
+            PackMount::from_bytes(bytes[..bytes.len() - 1].to_vec()),
+            Err(PackError::CorruptedIndex)
+        ));
+        assert!(matches!(
+            PackMount::from_bytes(with_u64(stored_size_position, u64::MAX)),
+            Err(PackError::CorruptedIndex)
+        ));
+        assert!(matches!(
+            PackMount::from_bytes(with_u64(super::PACK_HEADER_SIZE - 8, u64::MAX)),
+            Err(PackError::CorruptedIndex)
+        ));
+
+        // the uncompressed size does not allocate up front and must match
+        for size in [u64::MAX, text.len() as u64 - 1, text.len() as u64 + 1] {
+            let pack = PackMount::from_bytes(with_u64(size_position, size)).unwrap();
+            assert!(matches!(
+                pack.read("a.wgsl"),
+                Err(PackError::Decompress(..))
+            ));
+        }
+    }
+}
@@ -1,8 +1,11 @@
 use crate::{
-    filesystem::{platforms::LoadFileError, WriteFileError},
+    filesystem::{
+        platforms::LoadFileError, FileWatcher, LoadFileFuture, MountSource, Vfs, WatchError,
+        WriteFileError,
+    },
     ContextBuilder,
 };
-use std::path::PathBuf;
+use std::path::{Path, PathBuf};
 
 #[derive(Clone, Debug)]
 pub struct FileSystemContext {
@@ -13,6 +16,8 @@ pub struct FileSystemContext {
 pub struct FileSystemConfig {
     asset_folder_path: PathBuf,
     temporary_folder_path: PathBuf,
+    save_folder_path: PathBuf,
+    vfs: Vfs,
 }
 
 impl FileSystemContext {
@@ -42,10 +47,20 @@ impl FileSystemContext {
                 .expect("could not create temporary folder");
         }
 
+        let save_folder_path = builder
+            .save_path
+            .clone()
+            .unwrap_or_else(default_save_folder_path);
+
+        let vfs = Vfs::default();
+        vfs.mount("", 0, DirectoryMount::new(&asset_folder_path));
+
         Self {
             config: std::sync::Arc::new(FileSystemConfig {
                 asset_folder_path,
                 temporary_folder_path,
+                save_folder_path,
+                vfs,
             }),
         }
     }
@@ -56,30 +71,34 @@ impl FileSystemContext {
         self.config.asset_folder_path.join(path)
     }
 
+    /// Location on disk an asset path resolves to through the mounted sources
+    pub fn asset_disk_path(&self, path: impl AsRef<std::path::Path>) -> PathBuf {
+        self.config
+            .vfs
+            .disk_path(&path)
+            .unwrap_or_else(|| self.format_asset_path(path))
+    }
+
+    pub fn vfs(&self) -> &Vfs {
+        &self.config.vfs
+    }
+
+    pub fn temporary_path(&self) -> &Path {
+        &self.config.temporary_folder_path
+    }
+
     pub async fn load_asset_bytes(
         &self,
         path: impl AsRef<std::path::Path>,
     ) -> Result<Vec<u8>, LoadFileError> {
-        let path = self.format_asset_path(path);
-        let bytes = std::fs::read(&path).map_err(|err| match err.kind() {
-            std::io::ErrorKind::NotFound => LoadFileError::FileNotFound,
-            _ => LoadFileError::Other(Box::new(err)),
-        })?;
-
-        Ok(bytes)
+        self.config.vfs.load(path).await
     }
 
     pub async fn load_asset_string(
         &self,
         path: impl AsRef<std::path::Path>,
     ) -> Result<String, LoadFileError> {
-        let path = self.format_asset_path(path);
-        let str = std::fs::read_to_string(&path).map_err(|err| match err.kind() {
-            std::io::ErrorKind::NotFound => LoadFileError::FileNotFound,
-            _ => LoadFileError::Other(Box::new(err)),
-        })?;
-
-        Ok(str)
+        self.config.vfs.load_string(path).await
     }
 
     pub fn write_temporary_bytes(
@@ -133,3 +152,122 @@ impl FileSystemContext {
         Ok(str)
     }
 }
+
+impl FileSystemContext {
+    pub(crate) fn watch(&self, path: &Path, recursive: bool) -> Result<FileWatcher, WatchError> {
+        // notify reports absolute paths, so watch absolute paths too
+        let path = std::path::absolute(path).map_err(|err| WatchError::Watch {
+            path: path.to_path_buf(),
+            message: err.to_string(),
+        })?;
+        FileWatcher::new(&path, recursive)
+    }
+}
+
+//
+// Saves
+//
+
+// e.g. ~/.local/share/<executable name>/saves
+fn default_save_folder_path() -> PathBuf {
+    let name = std::env::current_exe()
+        .ok()
+        .and_then(|path| {
+            path.file_stem()
+                .map(|name| name.to_string_lossy().into_owned())
+        })
+        .unwrap_or_else(|| String::from("gbase"));
+    dirs::data_dir()
+        .unwrap_or_else(|| std::env::current_dir().expect("could not get current working dir"))
+        .join(name)
+        .join("saves")
+}
+
+impl FileSystemContext {
+    fn save_file_path(&self, slot: &str) -> PathBuf {
+        self.config.save_folder_path.join(format!("{slot}.save"))
+    }
+
+    /// Write to a temporary file first so a crash never leaves a partial save
+    pub(crate) fn write_save(&self, slot: &str, data: &[u8]) -> Result<(), WriteFileError> {
+        use std::io::Write;
+
+        let path = self.save_file_path(slot);
+        let temporary_path = path.with_extension("save.tmp");
+        std::fs::create_dir_all(&self.config.save_folder_path)
+            .and_then(|_| {
+                let mut file = std::fs::File::create(&temporary_path)?;
+                file.write_all(data)?;
+                file.sync_all()
+            })
+            .and_then(|_| std::fs::rename(&temporary_path, &path))
+            .map_err(|err| WriteFileError::Other(Box::new(err)))
+    }
+
+    pub(crate) fn load_save(&self, slot: &str) -> Result<Vec<u8>, LoadFileError> {
+        std::fs::read(self.save_file_path(slot)).map_err(|err| match err.kind() {
+            std::io::ErrorKind::NotFound => LoadFileError::FileNotFound,
+            _ => LoadFileError::Other(Box::new(err)),
+        })
+    }
+
+    pub(crate) fn list_saves(&self) -> Result<Vec<String>, LoadFileError> {
+        let entries = match std::fs::read_dir(&self.config.save_folder_path) {
+            Ok(entries) => entries,
+            Err(err) if err.kind() == std::io::ErrorKind::NotFound => return Ok(Vec::new()),
+            Err(err) => return Err(LoadFileError::Other(Box::new(err))),
+        };
+
+        let mut slots = Vec::new();
+        for entry in entries {
+            let path = entry
+                .map_err(|err| LoadFileError::Other(Box::new(err)))?
+                .path();
+            if path
+                .extension()
+                .is_some_and(|extension| extension == "save")
+            {
+                if let Some(slot) = path.file_stem().and_then(|stem| stem.to_str()) {
+                    slots.push(slot.to_string());
+                }
+            }
+        }
+        slots.sort();
+        Ok(slots)
+    }
+
+    pub(crate) fn delete_save(&self, slot: &str) -> Result<(), WriteFileError> {
+        std::fs::remove_file(self.save_file_path(slot))
+            .map_err(|err| WriteFileError::Other(Box::new(err)))
+    }
+}
+
+/// Files in a directory on disk
+#[derive(Debug, Clone)]
+pub struct DirectoryMount {
+    root: PathBuf,
+}
+
+impl DirectoryMount {
+    /// Relative roots are resolved from the current working directory
+    pub fn new(root: impl AsRef<Path>) -> Self {
+        let root = std::env::current_dir()
+            .expect("could not get current working dir")
+            .join(root);
+        Self { root }
+    }
+}
+
+impl MountSource for DirectoryMount {
+    fn load<'a>(&'a self, path: &'a Path) -> LoadFileFuture<'a> {
+        let result = std::fs::read(self.root.join(path)).map_err(|err| match err.kind() {
+            std::io::ErrorKind::NotFound => LoadFileError::FileNotFound,
+            _ => LoadFileError::Other(Box::new(err)),
+        });
+        Box::pin(std::future::ready(result))
+    }
+
+    fn disk_path(&self, path: &Path) -> Option<PathBuf> {
+        Some(self.root.join(path))
+    }
+}
@@ -1,5 +1,8 @@
 use crate::{
-    filesystem::{platforms::LoadFileError, WriteFileError},
+    filesystem::{
+        platforms::LoadFileError, FileWatcher, LoadFileFuture, MountSource, Vfs, WatchError,
+        WriteFileError,
+    },
     ContextBuilder,
 };
 use base64::Engine;
@@ -14,32 +17,37 @@ pub struct FileSystemContext {
 pub struct FileSystemConfig {
     asset_folder_path: PathBuf,
     temporary_folder_path: PathBuf,
+    save_folder_path: PathBuf,
 
-    base_url: reqwest::Url,
     local_storage: web_sys::Storage,
+    vfs: Vfs,
 }
 
 impl FileSystemContext {
     pub(crate) fn new(builder: &ContextBuilder) -> Self {
         let asset_folder_path = builder.assets_path.clone();
         let temporary_folder_path = builder.temporary_path.clone();
+        let save_folder_path = builder
+            .save_path
+            .clone()
+            .unwrap_or_else(|| PathBuf::from("saves"));
 
         let window = web_sys::window().expect("could not get window");
-        let location = window.location();
-        let origin = location.origin().expect("could not get origin");
-        let base_url = reqwest::Url::parse(&origin).expect("could not base path");
-
         let local_storage = window
             .local_storage()
             .expect("could not get local storage")
             .expect("local storage is empty");
 
+        let vfs = Vfs::default();
+        vfs.mount("", 0, DirectoryMount::new(&asset_folder_path));
+
         Self {
             config: std::sync::Arc::new(FileSystemConfig {
                 asset_folder_path,
                 temporary_folder_path,
-                base_url,
+                save_folder_path,
                 local_storage,
+                vfs,
             }),
         }
     }
@@ -50,48 +58,20 @@ impl FileSystemContext {
         self.config.asset_folder_path.join(path)
     }
 
-    pub async fn load_asset_bytes(&self, path: impl AsRef<Path>) -> Result<Vec<u8>, LoadFileError> {
-        let path = self.format_asset_path(path);
-        let path = path.to_str().ok_or(LoadFileError::InvalidPath)?;
+    pub fn vfs(&self) -> &Vfs {
+        &self.config.vfs
+    }
 
-        let path = self
-            .config
-            .base_url
-            .join(path)
-            .map_err(|_| LoadFileError::InvalidPath)?;
-        let response = reqwest::Client::new()
-            .get(path)
-            .send()
-            .await
-            .map_err(|err| LoadFileError::Other(Box::new(err)))?;
-        let bytes = response
-            .bytes()
-            .await
-            .map_err(|err| LoadFileError::Other(Box::new(err)))?;
+    pub fn temporary_path(&self) -> &Path {
+        &self.config.temporary_folder_path
+    }
 
-        Ok(bytes.to_vec())
+    pub async fn load_asset_bytes(&self, path: impl AsRef<Path>) -> Result<Vec<u8>, LoadFileError> {
+        self.config.vfs.load(path).await
     }
 
     pub async fn load_asset_string(&self, path: impl AsRef<Path>) -> Result<String, LoadFileError> {
-        let path = self.format_asset_path(path);
-        let path = path.to_str().ok_or(LoadFileError::InvalidPath)?;
-
-        let path = self
-            .config
-            .base_url
-            .join(path)
-            .map_err(|_| LoadFileError::InvalidPath)?;
-        let response = reqwest::Client::new()
-            .get(path)
-            .send()
-            .await
-            .map_err(|err| LoadFileError::Other(Box::new(err)))?;
-        let str = response
-            .text()
-            .await
-            .map_err(|err| LoadFileError::Other(Box::new(err)))?;
-
-        Ok(str)
+        self.config.vfs.load_string(path).await
     }
 
     pub fn write_temporary_bytes(
@@ -162,3 +142,115 @@ impl FileSystemContext {
         Ok(data)
     }
 }
+
+impl FileSystemContext {
+    /// There is no filesystem to watch in the browser
+    pub(crate) fn watch(&self, _path: &Path, _recursive: bool) -> Result<FileWatcher, WatchError> {
+        Err(WatchError::Unsupported)
+    }
+}
+
+//
+// Saves
+//
+
+impl FileSystemContext {
+    fn save_key(&self, slot: &str) -> String {
+        format!("{}/{}", self.config.save_folder_path.display(), slot)
+    }
+
+    /// Local storage writes replace the whole value at once
+    pub(crate) fn write_save(&self, slot: &str, data: &[u8]) -> Result<(), WriteFileError> {
+        let encoded = base64::engine::general_purpose::STANDARD.encode(data);
+        self.config
+            .local_storage
+            .set_item(&self.save_key(slot), &encoded)
+            .map_err(|_| WriteFileError::Other("could not write to local storage".into()))
+    }
+
+    pub(crate) fn load_save(&self, slot: &str) -> Result<Vec<u8>, LoadFileError> {
+        let data = self
+            .config
+            .local_storage
+            .get_item(&self.save_key(slot))
+            .map_err(|_| LoadFileError::Other("could not read local storage".into()))?
+            .ok_or(LoadFileError::FileNotFound)?;
+        base64::engine::general_purpose::STANDARD
+            .decode(data)
+            .map_err(|err| LoadFileError::Other(Box::new(err)))
+    }
+
+    pub(crate) fn list_saves(&self) -> Result<Vec<String>, LoadFileError> {
+        let storage = &self.config.local_storage;
+        let prefix = self.save_key("");
+        let len = storage
+            .length()
+            .map_err(|_| LoadFileError::Other("could not read local storage".into()))?;
+
+        let mut slots = (0..len)
+            .filter_map(|index| storage.key(index).ok().flatten())
+            .filter_map(|key| key.strip_prefix(&prefix).map(|slot| slot.to_string()))
+            .collect::<Vec<_>>();
+        slots.sort();
+        Ok(slots)
+    }
+
+    pub(crate) fn delete_save(&self, slot: &str) -> Result<(), WriteFileError> {
+        self.config
+            .local_storage
+            .remove_item(&self.save_key(slot))
+            .map_err(|_| WriteFileError::Other("could not write to local storage".into()))
+    }
+}
+
+/// Files in a directory served next to the page, fetched over http
+#[derive(Debug, Clone)]
+pub struct DirectoryMount {
+    root: PathBuf,
+    base_url: reqwest::Url,
+}
+
+impl DirectoryMount {
+    /// Relative roots are resolved from the origin of the page
+    pub fn new(root: impl AsRef<Path>) -> Self {
+        let origin = web_sys::window()
+            .expect("could not get window")
+            .location()
+            .origin()
+            .expect("could not get origin");
+        let base_url = reqwest::Url::parse(&origin).expect("could not base path");
+
+        Self {
+            root: root.as_ref().to_path_buf(),
+            base_url,
+        }
+    }
+}
+
+impl MountSource for DirectoryMount {
+    fn load<'a>(&'a self, path: &'a Path) -> LoadFileFuture<'a> {
+        Box::pin(async move {
+            let path = self.root.join(path);
+            let path = path.to_str().ok_or(LoadFileError::InvalidPath)?;
+
+            let url = self
+                .base_url
+                .join(path)
+                .map_err(|_| LoadFileError::InvalidPath)?;
+            let response = reqwest::Client::new()
+                .get(url)
+                .send()
+                .await
+                .map_err(|err| LoadFileError::Other(Box::new(err)))?;
+            if response.status() == reqwest::StatusCode::NOT_FOUND {
+                return Err(LoadFileError::FileNotFound);
+            }
+            let bytes = response
+                .bytes()
+                .await
+                .map_err(|err| LoadFileError::Other(Box::new(err)))?;
+
+            Ok(bytes.to_vec())
+        })
+    }
+}
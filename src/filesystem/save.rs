@@ -0,0 +1,271 @@
+use super::{LoadFileError, WriteFileError};
+use rustc_hash::FxHashMap;
+use serde::{de::DeserializeOwned, Serialize};
+
+const SAVE_MAGIC: &[u8; 8] = b"GBSAVE\0\0";
+const SAVE_HEADER_SIZE: usize = 8 + 4 + 4 + 8;
+
+#[derive(thiserror::Error, Debug)]
+pub enum SaveError {
+    #[error("invalid save slot name {0:?}, use letters, digits, '-' and '_'")]
+    InvalidSlot(String),
+    #[error("save slot {0} does not exist")]
+    NotFound(String),
+    #[error("save slot {0} is corrupted")]
+    Corrupted(String),
+    #[error("save slot {slot} has version {version}, newer than {current}")]
+    NewerVersion {
+        slot: String,
+        version: u32,
+        current: u32,
+    },
+    #[error("no migration from version {0}")]
+    MissingMigration(u32),
+    #[error("could not serialize save: {0}")]
+    Serialize(#[from] ron::Error),
+    #[error("could not deserialize save: {0}")]
+    Deserialize(#[from] ron::error::SpannedError),
+    #[error(transparent)]
+    Load(LoadFileError),
+    #[error(transparent)]
+    Write(#[from] WriteFileError),
+}
+
+/// Contents of a save slot
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct SaveData {
+    /// Version passed when the slot was written
+    pub version: u32,
+    pub data: Vec<u8>,
+}
+
+/// Slot names end up in file names, so only a safe subset is allowed
+pub(crate) fn validate_slot(slot: &str) -> Result<(), SaveError> {
+    let valid = !slot.is_empty()
+        && slot
+            .chars()
+            .all(|c| c.is_ascii_alphanumeric() || c == '-' || c == '_');
+    match valid {
+        true => Ok(()),
+        false => Err(SaveError::InvalidSlot(slot.to_string())),
+    }
+}
+
+// header: magic, version: u32, crc32: u32, data length: u64
+fn encode_save(version: u32, data: &[u8]) -> Vec<u8> {
+    let mut bytes = Vec::with_capacity(SAVE_HEADER_SIZE + data.len());
+    bytes.extend_from_slice(SAVE_MAGIC);
+    bytes.extend_from_slice(&version.to_le_bytes());
+    bytes.extend_from_slice(&crc32fast::hash(data).to_le_bytes());
+    bytes.extend_from_slice(&(data.len() as u64).to_le_bytes());
+    bytes.extend_from_slice(data);
+    bytes
+}
+
+fn decode_save(slot: &str, bytes: &[u8]) -> Result<SaveData, SaveError> {
+    let corrupted = || SaveError::Corrupted(slot.to_string());
+    if bytes.len() < SAVE_HEADER_SIZE || !bytes.starts_with(SAVE_MAGIC) {
+        return Err(corrupted());
+    }
+
+    let version = u32::from_le_bytes(bytes[8..12].try_into().unwrap());
+    let checksum = u32::from_le_bytes(bytes[12..16].try_into().unwrap());
+    let len = u64::from_le_bytes(bytes[16..24].try_into().unwrap());
+    let data = &bytes[SAVE_HEADER_SIZE..];
+    if data.len() as u64 != len || crc32fast::hash(data) != checksum {
+        return Err(corrupted());
+    }
+
+    Ok(SaveData {
+        version,
+        data: data.to_vec(),
+    })
+}
+
+//
+// Migrations
+//
+
+type SaveMigrationFn = Box<dyn Fn(&str) -> Result<String, SaveError>>;
+
+/// Upgrades saves written with older versions one version at a time
+#[derive(Default)]
+pub struct SaveMigrations {
+    steps: FxHashMap<u32, SaveMigrationFn>,
+}
+
+impl SaveMigrations {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Convert data saved with version `from` into data of version `from + 1`
+    pub fn migration<Old: DeserializeOwned, New: Serialize>(
+        mut self,
+        from: u32,
+        migrate: impl Fn(Old) -> New + 'static,
+    ) -> Self {
+        self.steps.insert(
+            from,
+            Box::new(move |source| {
+                let old = ron::from_str::<Old>(source)?;
+                Ok(ron::to_string(&migrate(old))?)
+            }),
+        );
+        self
+    }
+
+    fn apply(
+        &self,
+        slot: &str,
+        mut version: u32,
+        mut source: String,
+        current: u32,
+    ) -> Result<String, SaveError> {
+        if version > current {
+            return Err(SaveError::NewerVersion {
+                slot: slot.to_string(),
+                version,
+                current,
+            });
+        }
+
+        while version < current {
+            let step = self
+                .steps
+                .get(&version)
+                .ok_or(SaveError::MissingMigration(version))?;
+            source = step(&source)?;
+            version += 1;
+        }
+
+        Ok(source)
+    }
+}
+
+//
+// Commands
+//
+
+/// Write a save slot, replaces the previous save only once fully written
+pub fn write_save_bytes(
+    ctx: &crate::Context,
+    slot: &str,
+    version: u32,
+    data: &[u8],
+) -> Result<(), SaveError> {
+    validate_slot(slot)?;
+    ctx.filesystem
+        .write_save(slot, &encode_save(version, data))?;
+    Ok(())
+}
+
+/// Read a save slot and verify its checksum
+pub fn load_save_bytes(ctx: &crate::Context, slot: &str) -> Result<SaveData, SaveError> {
+    validate_slot(slot)?;
+    let bytes = ctx.filesystem.load_save(slot).map_err(|err| match err {
+        LoadFileError::FileNotFound => SaveError::NotFound(slot.to_string()),
+        err => SaveError::Load(err),
+    })?;
+    decode_save(slot, &bytes)
+}
+
+/// Serialize a value into a save slot
+pub fn write_save<T: Serialize>(
+    ctx: &crate::Context,
+    slot: &str,
+    version: u32,
+    value: &T,
+) -> Result<(), SaveError> {
+    let source = ron::to_string(value)?;
+    write_save_bytes(ctx, slot, version, source.as_bytes())
+}
+
+/// Deserialize a value from a save slot, migrating older versions to `version`
+pub fn load_save<T: DeserializeOwned>(
+    ctx: &crate::Context,
+    slot: &str,
+    version: u32,
+    migrations: &SaveMigrations,
+) -> Result<T, SaveError> {
+    let save = load_save_bytes(ctx, slot)?;
+    let source =
+        String::from_utf8(save.data).map_err(|_| SaveError::Corrupted(slot.to_string()))?;
+    let source = migrations.apply(slot, save.version, source, version)?;
+    Ok(ron::from_str(&source)?)
+}
+
+/// Names of all save slots
+pub fn list_saves(ctx: &crate::Context) -> Result<Vec<String>, SaveError> {
+    ctx.filesystem.list_saves().map_err(SaveError::Load)
+}
+
+pub fn delete_save(ctx: &crate::Context, slot: &str) -> Result<(), SaveError> {
+    validate_slot(slot)?;
+    ctx.filesystem.delete_save(slot)?;
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{decode_save, encode_save, validate_slot, SaveError, SaveMigrations};
+    use serde::{Deserialize, Serialize};
+
+    #[test]
+    fn test_save_checksum() {
+        let mut bytes = encode_save(3, b"(level: 2)");
+        let save = decode_save("slot", &bytes).unwrap();
+        assert_eq!(
+            (save.version, save.data.as_slice()),
+            (3, &b"(level: 2)"[..])
+        );
+
+        *bytes.last_mut().unwrap() = b']';
+        assert!(matches!(
+            decode_save("slot", &bytes),
+            Err(SaveError::Corrupted(_))
+        ));
+        assert!(validate_slot("slot_1").is_ok());
+        assert!(validate_slot("../slot").is_err());
+    }
+
+    #[test]
+    fn test_save_migrations() {
+        #[derive(Serialize, Deserialize)]
+        struct V0 {
+            level: u32,
+        }
+        #[derive(Serialize, Deserialize)]
+        struct V1 {
+            level: u32,
+            coins: u32,
+        }
+        #[derive(Debug, PartialEq, Serialize, Deserialize)]
+        struct V2 {
+            stage: u32,
+            coins: u32,
+        }
+
+        let migrations = SaveMigrations::new()
+            .migration(0, |old: V0| V1 {
+                level: old.level,
+                coins: 0,
+            })
+            .migration(1, |old: V1| V2 {
+                stage: old.level,
+                coins: old.coins,
+            });
+
+        let source = migrations
+            .apply("slot", 0, "(level: 4)".to_string(), 2)
+            .unwrap();
+        assert_eq!(
+            ron::from_str::<V2>(&source).unwrap(),
+            V2 { stage: 4, coins: 0 }
+        );
+        assert!(matches!(
+            migrations.apply("slot", 3, source, 2),
+            Err(SaveError::NewerVersion { .. })
+        ));
+    }
+}
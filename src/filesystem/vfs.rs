@@ -0,0 +1,230 @@
+use super::{normalize_path, LoadFileError};
+use rustc_hash::FxHashMap;
+use std::{
+    future::Future,
+    path::{Path, PathBuf},
+    pin::Pin,
+    sync::{Arc, RwLock},
+};
+
+pub type LoadFileFuture<'a> = Pin<Box<dyn Future<Output = Result<Vec<u8>, LoadFileError>> + 'a>>;
+
+/// Source of files which can be mounted into the virtual filesystem
+pub trait MountSource: Send + Sync + 'static {
+    /// Load a file relative to the mount point
+    fn load<'a>(&'a self, path: &'a Path) -> LoadFileFuture<'a>;
+
+    /// Location on disk for watching and writing, if the source is backed by a directory
+    fn disk_path(&self, _path: &Path) -> Option<PathBuf> {
+        None
+    }
+}
+
+#[derive(Clone)]
+struct Mount {
+    prefix: PathBuf,
+    priority: i32,
+    source: Arc<dyn MountSource>,
+}
+
+/// Virtual filesystem, overlays all mounted sources
+///
+/// Mounts with higher priority override files of lower ones, e.g. mods or patches over base assets.
+/// Mounts with equal priority are searched in reverse mount order
+#[derive(Clone, Default)]
+pub struct Vfs {
+    mounts: Arc<RwLock<Vec<Mount>>>,
+}
+
+impl Vfs {
+    /// Mount a source at a path prefix, use an empty prefix for the root
+    pub fn mount(&self, prefix: impl AsRef<Path>, priority: i32, source: impl MountSource) {
+        let mut mounts = self.mounts.write().expect("could not lock vfs");
+        let index = mounts
+            .iter()
+            .position(|mount| mount.priority <= priority)
+            .unwrap_or(mounts.len());
+        mounts.insert(
+            index,
+            Mount {
+                prefix: normalize_path(prefix),
+                priority,
+                source: Arc::new(source),
+            },
+        );
+    }
+
+    /// Remove all sources mounted at a path prefix
+    pub fn unmount(&self, prefix: impl AsRef<Path>) {
+        let prefix = normalize_path(prefix);
+        let mut mounts = self.mounts.write().expect("could not lock vfs");
+        mounts.retain(|mount| mount.prefix != prefix);
+    }
+
+    // mounts which could contain the path, highest priority first
+    fn candidates(&self, path: &Path) -> Vec<(PathBuf, Arc<dyn MountSource>)> {
+        let path = normalize_path(path);
+        let mounts = self.mounts.read().expect("could not lock vfs");
+        mounts
+            .iter()
+            .filter_map(|mount| {
+                let relative = path.strip_prefix(&mount.prefix).ok()?;
+                Some((relative.to_path_buf(), mount.source.clone()))
+            })
+            .collect()
+    }
+
+    pub async fn load(&self, path: impl AsRef<Path>) -> Result<Vec<u8>, LoadFileError> {
+        for (relative, source) in self.candidates(path.as_ref()) {
+            match source.load(&relative).await {
+                Err(LoadFileError::FileNotFound) => continue,
+                result => return result,
+            }
+        }
+        Err(LoadFileError::FileNotFound)
+    }
+
+    pub async fn load_string(&self, path: impl AsRef<Path>) -> Result<String, LoadFileError> {
+        let bytes = self.load(path).await?;
+        String::from_utf8(bytes).map_err(|err| LoadFileError::Other(Box::new(err)))
+    }
+
+    /// Location on disk a path resolves to
+    ///
+    /// Prefers existing files, otherwise the highest priority directory which could hold it
+    pub fn disk_path(&self, path: impl AsRef<Path>) -> Option<PathBuf> {
+        let disk_paths = self
+            .candidates(path.as_ref())
+            .into_iter()
+            .filter_map(|(relative, source)| source.disk_path(&relative))
+            .collect::<Vec<_>>();
+        disk_paths
+            .iter()
+            .find(|path| path.exists())
+            .or(disk_paths.first())
+            .cloned()
+    }
+}
+
+impl std::fmt::Debug for Vfs {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        let mounts = self.mounts.read().expect("could not lock vfs");
+        f.debug_list()
+            .entries(mounts.iter().map(|mount| (&mount.prefix, mount.priority)))
+            .finish()
+    }
+}
+
+//
+// Sources
+//
+
+/// Files kept in memory, e.g. generated at runtime
+#[derive(Debug, Clone, Default)]
+pub struct MemoryMount {
+    files: Arc<RwLock<FxHashMap<PathBuf, Arc<[u8]>>>>,
+}
+
+impl MemoryMount {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Insert or replace a file, visible to all clones of this mount
+    pub fn insert(&self, path: impl AsRef<Path>, data: impl Into<Arc<[u8]>>) {
+        let mut files = self.files.write().expect("could not lock memory mount");
+        files.insert(normalize_path(path), data.into());
+    }
+
+    pub fn remove(&self, path: impl AsRef<Path>) {
+        let mut files = self.files.write().expect("could not lock memory mount");
+        files.remove(&normalize_path(path));
+    }
+}
+
+impl MountSource for MemoryMount {
+    fn load<'a>(&'a self, path: &'a Path) -> LoadFileFuture<'a> {
+        let files = self.files.read().expect("could not lock memory mount");
+        let result = files
+            .get(&normalize_path(path))
+            .map(|data| data.to_vec())
+            .ok_or(LoadFileError::FileNotFound);
+        Box::pin(std::future::ready(result))
+    }
+}
+
+/// Files compiled into the binary, see [`embed_assets`](crate::filesystem::embed_assets)
+#[derive(Debug, Clone)]
+pub struct EmbeddedMount {
+    files: FxHashMap<PathBuf, &'static [u8]>,
+}
+
+impl EmbeddedMount {
+    pub fn new(files: &[(&str, &'static [u8])]) -> Self {
+        Self {
+            files: files
+                .iter()
+                .map(|(path, data)| (normalize_path(path), *data))
+                .collect(),
+        }
+    }
+}
+
+impl MountSource for EmbeddedMount {
+    fn load<'a>(&'a self, path: &'a Path) -> LoadFileFuture<'a> {
+        let result = self
+            .files
+            .get(&normalize_path(path))
+            .map(|data| data.to_vec())
+            .ok_or(LoadFileError::FileNotFound);
+        Box::pin(std::future::ready(result))
+    }
+}
+
+/// Embed files from \[PROJECT ROOT\]/assets/ into the binary
+///
+/// # Examples
+/// ```
+/// use gbase::filesystem;
+/// let mount = filesystem::embed_assets!("shaders/shader.wgsl", "textures/grass_normal.png");
+/// ```
+#[macro_export]
+macro_rules! embed_assets {
+    ($($path:literal),* $(,)?) => {
+        $crate::filesystem::EmbeddedMount::new(&[$(
+            ($path, include_bytes!(concat!(env!("CARGO_MANIFEST_DIR"), "/assets/", $path)) as &'static [u8]),
+        )*])
+    };
+}
+
+pub use embed_assets;
+
+#[cfg(test)]
+mod tests {
+    use super::{MemoryMount, Vfs};
+    use crate::filesystem::LoadFileError;
+
+    #[test]
+    fn test_vfs_overlay_priority() {
+        let base = MemoryMount::new();
+        base.insert("textures/grass.png", b"base".to_vec());
+        base.insert("textures/dirt.png", b"base".to_vec());
+        let patch = MemoryMount::new();
+        patch.insert("grass.png", b"patch".to_vec());
+
+        let vfs = Vfs::default();
+        vfs.mount("", 0, base);
+        vfs.mount("textures", 10, patch);
+
+        let load = |path| pollster::block_on(vfs.load(path));
+        assert_eq!(load("textures/grass.png").unwrap(), b"patch");
+        assert_eq!(load("./textures/dirt.png").unwrap(), b"base");
+        assert!(matches!(
+            load("textures/stone.png"),
+            Err(LoadFileError::FileNotFound)
+        ));
+
+        vfs.unmount("textures");
+        assert_eq!(load("textures/grass.png").unwrap(), b"base");
+    }
+}
@@ -0,0 +1,223 @@
+use std::{
+    path::{Path, PathBuf},
+    sync::mpsc,
+};
+
+#[derive(thiserror::Error, Debug)]
+pub enum WatchError {
+    #[error("could not watch {path:?}: {message}")]
+    Watch { path: PathBuf, message: String },
+    #[error("file watching is not supported on this platform")]
+    Unsupported,
+}
+
+/// Change to a watched file or directory
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub enum WatchEvent {
+    Created(PathBuf),
+    Modified(PathBuf),
+    Removed(PathBuf),
+    Renamed { from: PathBuf, to: PathBuf },
+}
+
+impl WatchEvent {
+    /// Path the event leaves behind, the new path for renames
+    pub fn path(&self) -> &Path {
+        match self {
+            WatchEvent::Created(path) | WatchEvent::Modified(path) | WatchEvent::Removed(path) => {
+                path
+            }
+            WatchEvent::Renamed { to, .. } => to,
+        }
+    }
+}
+
+/// Watches files outside of the asset system, stops watching when dropped
+///
+/// Events are queued in the background and drained with [`FileWatcher::poll`], e.g. once per frame
+pub struct FileWatcher {
+    #[cfg(not(target_arch = "wasm32"))]
+    watcher: notify_debouncer_mini::notify::RecommendedWatcher,
+    receiver: mpsc::Receiver<WatchEvent>,
+}
+
+#[cfg(not(target_arch = "wasm32"))]
+impl FileWatcher {
+    pub(crate) fn new(path: &Path, recursive: bool) -> Result<Self, WatchError> {
+        use notify_debouncer_mini::notify;
+
+        let (sender, receiver) = mpsc::channel();
+        let watcher = notify::recommended_watcher(move |res: notify::Result<notify::Event>| {
+            match res {
+                Ok(event) => {
+                    for event in convert_event(event) {
+                        // receiver dropped together with the watcher
+                        let _ = sender.send(event);
+                    }
+                }
+                Err(err) => tracing::error!("file watcher error: {}", err),
+            }
+        })
+        .map_err(|err| WatchError::Watch {
+            path: path.to_path_buf(),
+            message: err.to_string(),
+        })?;
+
+        let mut watcher = Self { watcher, receiver };
+        watcher.watch(path, recursive)?;
+        Ok(watcher)
+    }
+
+    /// Watch another path with the same watcher
+    pub fn watch(&mut self, path: impl AsRef<Path>, recursive: bool) -> Result<(), WatchError> {
+        use notify_debouncer_mini::notify::{self, Watcher};
+
+        let mode = match recursive {
+            true => notify::RecursiveMode::Recursive,
+            false => notify::RecursiveMode::NonRecursive,
+        };
+        self.watcher
+            .watch(path.as_ref(), mode)
+            .map_err(|err| WatchError::Watch {
+                path: path.as_ref().to_path_buf(),
+                message: err.to_string(),
+            })
+    }
+
+    pub fn unwatch(&mut self, path: impl AsRef<Path>) -> Result<(), WatchError> {
+        use notify_debouncer_mini::notify::Watcher;
+
+        self.watcher
+            .unwatch(path.as_ref())
+            .map_err(|err| WatchError::Watch {
+                path: path.as_ref().to_path_buf(),
+                message: err.to_string(),
+            })
+    }
+}
+
+impl FileWatcher {
+    /// Drain all events since the last poll, in order
+    ///
+    /// Directly repeated events are merged, a single save often emits several writes
+    pub fn poll(&self) -> Vec<WatchEvent> {
+        merge_repeated(self.receiver.try_iter())
+    }
+}
+
+// only consecutive duplicates, e.g. created, removed, created must keep all three
+fn merge_repeated(events: impl Iterator<Item = WatchEvent>) -> Vec<WatchEvent> {
+    let mut merged = Vec::<WatchEvent>::new();
+    for event in events {
+        if merged.last() != Some(&event) {
+            merged.push(event);
+        }
+    }
+    merged
+}
+
+impl std::fmt::Debug for FileWatcher {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("FileWatcher").finish_non_exhaustive()
+    }
+}
+
+#[cfg(not(target_arch = "wasm32"))]
+fn convert_event(event: notify_debouncer_mini::notify::Event) -> Vec<WatchEvent> {
+    use notify_debouncer_mini::notify::{
+        event::{ModifyKind, RenameMode},
+        EventKind,
+    };
+
+    let mut paths = event.paths.into_iter();
+    match event.kind {
+        EventKind::Modify(ModifyKind::Name(RenameMode::Both)) => {
+            match (paths.next(), paths.next()) {
+                (Some(from), Some(to)) => vec![WatchEvent::Renamed { from, to }],
+                _ => Vec::new(),
+            }
+        }
+        EventKind::Modify(ModifyKind::Name(RenameMode::From)) => {
+            paths.map(WatchEvent::Removed).collect()
+        }
+        EventKind::Modify(ModifyKind::Name(RenameMode::To)) => {
+            paths.map(WatchEvent::Created).collect()
+        }
+        // platforms which don't pair renames only report that a path changed name
+        EventKind::Modify(ModifyKind::Name(_)) => paths
+            .map(|path| match path.exists() {
+                true => WatchEvent::Created(path),
+                false => WatchEvent::Removed(path),
+            })
+            .collect(),
+        EventKind::Create(_) => paths.map(WatchEvent::Created).collect(),
+        EventKind::Modify(_) => paths.map(WatchEvent::Modified).collect(),
+        EventKind::Remove(_) => paths.map(WatchEvent::Removed).collect(),
+        EventKind::Access(_) | EventKind::Any | EventKind::Other => Vec::new(),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{merge_repeated, FileWatcher, WatchEvent};
+    use std::{
+        path::PathBuf,
+        time::{Duration, Instant},
+    };
+
+    #[test]
+    fn test_merge_repeated_events() {
+        let a = PathBuf::from("a.ron");
+        let b = PathBuf::from("b.ron");
+        let events = [
+            WatchEvent::Modified(a.clone()),
+            WatchEvent::Modified(a.clone()),
+            WatchEvent::Modified(b.clone()),
+            WatchEvent::Created(a.clone()),
+            WatchEvent::Removed(a.clone()),
+            WatchEvent::Created(a.clone()),
+        ];
+        assert_eq!(
+            merge_repeated(events.into_iter()),
+            [
+                WatchEvent::Modified(a.clone()),
+                WatchEvent::Modified(b),
+                WatchEvent::Created(a.clone()),
+                WatchEvent::Removed(a.clone()),
+                WatchEvent::Created(a),
+            ]
+        );
+    }
+
+    #[test]
+    fn test_watch_events() {
+        let dir = std::env::temp_dir().join(format!("gbase_watch_{}", std::process::id()));
+        std::fs::create_dir_all(&dir).unwrap();
+        let dir = dir.canonicalize().unwrap();
+        let watcher = FileWatcher::new(&dir, true).unwrap();
+
+        let file = dir.join("config.ron");
+        let renamed = dir.join("renamed.ron");
+        std::fs::write(&file, "()").unwrap();
+        std::fs::rename(&file, &renamed).unwrap();
+        std::fs::remove_file(&renamed).unwrap();
+
+        let expected = [
+            WatchEvent::Created(file.clone()),
+            WatchEvent::Removed(renamed.clone()),
+        ];
+        let mut events = Vec::new();
+        let start = Instant::now();
+        while start.elapsed() < Duration::from_secs(5)
+            && !expected.iter().all(|event| events.contains(event))
+        {
+            events.extend(watcher.poll());
+            std::thread::sleep(Duration::from_millis(10));
+        }
+        std::fs::remove_dir_all(&dir).unwrap();
+
+        for event in expected {
+            assert!(events.contains(&event), "{event:?} not in {events:?}");
+        }
+    }
+}
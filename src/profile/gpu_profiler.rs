@@ -245,7 +245,7 @@ impl GpuProfiler {
         let Some(query_set) = &self.timestamp_query_set else {
             return None;
         };
-        if self.next_free_timestamp > self.timestamp_capacity {
+        if self.next_free_timestamp + 2 > self.timestamp_capacity {
             tracing::warn!(
                 "reached timestamp query capacity {}, ignoring {}",
                 self.timestamp_capacity,
@@ -280,7 +280,7 @@ impl GpuProfiler {
         let Some(query_set) = &self.timestamp_query_set else {
             return None;
         };
-        if self.next_free_timestamp > self.timestamp_capacity {
+        if self.next_free_timestamp + 2 > self.timestamp_capacity {
             tracing::warn!(
                 "reached timestamp query capacity {}, ignoring {}",
                 self.timestamp_capacity,
@@ -304,7 +304,43 @@ impl GpuProfiler {
         Some(timestamp_writes)
     }
 
-    // TODO: inside encoder
+    /// Write a start timestamp into the encoder, returns the end timestamp index for [`GpuProfiler::end_encoder_scope`]
+    ///
+    /// Measures all commands recorded in between, e.g. several passes of a render graph node
+    pub fn begin_encoder_scope(
+        &mut self,
+        encoder: &mut wgpu::CommandEncoder,
+        label: &'static str,
+    ) -> Option<u32> {
+        if !self.enabled || !self.queries_supported_inside_encoder {
+            return None;
+        }
+        let query_set = self.timestamp_query_set.as_ref()?;
+        if self.next_free_timestamp + 2 > self.timestamp_capacity {
+            tracing::warn!(
+                "reached timestamp query capacity {}, ignoring {}",
+                self.timestamp_capacity,
+                label,
+            );
+            return None;
+        }
+
+        let (start, end) = (self.next_free_timestamp, self.next_free_timestamp + 1);
+        self.next_free_timestamp += 2;
+        encoder.write_timestamp(query_set, start);
+        self.current_queries.push(GpuProfileQuery {
+            label,
+            timestamp_start: start,
+            timestamp_end: end,
+        });
+        Some(end)
+    }
+
+    pub fn end_encoder_scope(&mut self, encoder: &mut wgpu::CommandEncoder, end: Option<u32>) {
+        if let (Some(end), Some(query_set)) = (end, &self.timestamp_query_set) {
+            encoder.write_timestamp(query_set, end);
+        }
+    }
 
     // TODO: inside pass
 
@@ -243,6 +243,15 @@ impl BindGroupBuilder {
         ctx.render.cache.bindgroups.insert(self, bindgroup.clone());
         bindgroup
     }
+
+    /// Check the entries against `group` of a reflected shader
+    pub fn validate(
+        &self,
+        reflection: &render::ShaderReflection,
+        group: u32,
+    ) -> Result<(), render::BindGroupValidationError> {
+        reflection.validate_entries(group, &self.entries)
+    }
 }
 
 impl BindGroupBuilder {
@@ -240,7 +240,8 @@ impl<T: ShaderType + WriteInto> StorageBuffer<T> {
 
 /// DEBUG
 ///
-/// Reads a mapped buffer
+/// Reads a mapped buffer, blocking until the GPU is done.
+/// Use [`render::readback_buffer`] to read without blocking
 ///
 /// Panics if buffer is not mapped
 pub fn read_buffer_sync<T: bytemuck::AnyBitPattern>(
@@ -1,7 +1,8 @@
 use crate::{
     render::{
-        ArcHandle, BindGroupBuilder, BindGroupLayoutBuilder, ComputePipelineBuilder,
-        PipelineLayoutBuilder, RenderPipelineBuilder, SamplerBuilder, TextureViewBuilder,
+        ArcHandle, ArcShaderModule, BindGroupBuilder, BindGroupLayoutBuilder,
+        ComputePipelineBuilder, PipelineLayoutBuilder, RenderPipelineBuilder, SamplerBuilder,
+        ShaderBuilder, TextureViewBuilder,
     },
     Context,
 };
@@ -16,6 +17,8 @@ pub struct RenderCache {
     pub compute_pipelines: FxHashMap<ComputePipelineBuilder, ArcHandle<wgpu::ComputePipeline>>,
     pub samplers: FxHashMap<SamplerBuilder, ArcHandle<wgpu::Sampler>>,
     pub texture_views: FxHashMap<TextureViewBuilder, ArcHandle<wgpu::TextureView>>,
+    /// Kept so pipelines using engine shaders can be found in the cache
+    pub(crate) builtin_shaders: FxHashMap<&'static str, ArcShaderModule>,
 
     /// Unique id for each arc handle
     unique_arc_id: u64,
@@ -31,6 +34,7 @@ impl RenderCache {
             compute_pipelines: FxHashMap::default(),
             samplers: FxHashMap::default(),
             texture_views: FxHashMap::default(),
+            builtin_shaders: FxHashMap::default(),
 
             unique_arc_id: 0,
         }
@@ -75,6 +79,26 @@ pub fn clear_cache(ctx: &mut Context) {
     ctx.render.cache = RenderCache::empty();
 }
 
+/// Shader compiled into the engine, built once per context
+pub(crate) fn builtin_shader(
+    ctx: &mut Context,
+    name: &'static str,
+    source: &'static str,
+) -> ArcShaderModule {
+    if let Some(shader) = ctx.render.cache.builtin_shaders.get(name) {
+        return shader.clone();
+    }
+
+    let shader = ShaderBuilder::new(source)
+        .label(name.to_string())
+        .build(ctx);
+    ctx.render
+        .cache
+        .builtin_shaders
+        .insert(name, shader.clone());
+    shader
+}
+
 // TODO: replace with arc::new to avoid manual creation of arcs
 pub fn next_id(ctx: &mut Context) -> u64 {
     ctx.render.cache.next_id()
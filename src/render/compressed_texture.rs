@@ -0,0 +1,708 @@
+use crate::{render, Context};
+
+/// Device features needed for all compressed formats the adapter supports
+pub(crate) const TEXTURE_COMPRESSION_FEATURES: wgpu::Features =
+    wgpu::Features::TEXTURE_COMPRESSION_BC
+        .union(wgpu::Features::TEXTURE_COMPRESSION_ETC2)
+        .union(wgpu::Features::TEXTURE_COMPRESSION_ASTC);
+
+/// True if textures of the format can be created on this device
+pub fn texture_format_supported(ctx: &Context, format: wgpu::TextureFormat) -> bool {
+    render::device(ctx)
+        .features()
+        .contains(format.required_features())
+}
+
+/// Uncompressed format [`decompress_blocks`] decodes to, `None` if it can't decode `format`
+///
+/// BC1-5, BC7, ETC2 and unsigned EAC can be decoded, EAC to 8 bits per channel.
+/// BC6H, signed BC4/5 and EAC, and ASTC have no CPU decoder
+pub fn decompressed_format(format: wgpu::TextureFormat) -> Option<wgpu::TextureFormat> {
+    match format {
+        wgpu::TextureFormat::Bc1RgbaUnorm
+        | wgpu::TextureFormat::Bc2RgbaUnorm
+        | wgpu::TextureFormat::Bc3RgbaUnorm
+        | wgpu::TextureFormat::Bc4RUnorm
+        | wgpu::TextureFormat::Bc5RgUnorm
+        | wgpu::TextureFormat::Bc7RgbaUnorm
+        | wgpu::TextureFormat::Etc2Rgb8Unorm
+        | wgpu::TextureFormat::Etc2Rgb8A1Unorm
+        | wgpu::TextureFormat::Etc2Rgba8Unorm
+        | wgpu::TextureFormat::EacR11Unorm
+        | wgpu::TextureFormat::EacRg11Unorm => Some(wgpu::TextureFormat::Rgba8Unorm),
+        wgpu::TextureFormat::Bc1RgbaUnormSrgb
+        | wgpu::TextureFormat::Bc2RgbaUnormSrgb
+        | wgpu::TextureFormat::Bc3RgbaUnormSrgb
+        | wgpu::TextureFormat::Bc7RgbaUnormSrgb
+        | wgpu::TextureFormat::Etc2Rgb8UnormSrgb
+        | wgpu::TextureFormat::Etc2Rgb8A1UnormSrgb
+        | wgpu::TextureFormat::Etc2Rgba8UnormSrgb => Some(wgpu::TextureFormat::Rgba8UnormSrgb),
+        _ => None,
+    }
+}
+
+/// Decode one image of block compressed data to RGBA8
+///
+/// `width` and `height` are in texels, partial blocks at the edges are cropped.
+/// `None` if the format is not supported or the data is too short
+pub fn decompress_blocks(
+    format: wgpu::TextureFormat,
+    width: u32,
+    height: u32,
+    data: &[u8],
+) -> Option<Vec<u8>> {
+    decompressed_format(format)?;
+    let block_size = format.block_copy_size(None)? as usize;
+    let (blocks_x, blocks_y) = (width.div_ceil(4), height.div_ceil(4));
+    if data.len() < (blocks_x * blocks_y) as usize * block_size {
+        return None;
+    }
+
+    let mut output = vec![0; (width * height * 4) as usize];
+    for (index, block) in data
+        .chunks_exact(block_size)
+        .take((blocks_x * blocks_y) as usize)
+        .enumerate()
+    {
+        let texels = decode_block(format, block);
+        let (block_x, block_y) = (index as u32 % blocks_x * 4, index as u32 / blocks_x * 4);
+        for (texel, rgba) in texels.iter().enumerate() {
+            let (x, y) = (block_x + texel as u32 % 4, block_y + texel as u32 / 4);
+            if x < width && y < height {
+                let offset = ((y * width + x) * 4) as usize;
+                output[offset..offset + 4].copy_from_slice(rgba);
+            }
+        }
+    }
+    Some(output)
+}
+
+/// 16 RGBA texels of a block, row by row
+fn decode_block(format: wgpu::TextureFormat, block: &[u8]) -> [[u8; 4]; 16] {
+    match format {
+        wgpu::TextureFormat::Bc1RgbaUnorm | wgpu::TextureFormat::Bc1RgbaUnormSrgb => {
+            decode_color(block, true)
+        }
+        wgpu::TextureFormat::Bc2RgbaUnorm | wgpu::TextureFormat::Bc2RgbaUnormSrgb => {
+            let mut texels = decode_color(&block[8..], false);
+            let alpha = u64::from_le_bytes(block[..8].try_into().unwrap());
+            for (i, texel) in texels.iter_mut().enumerate() {
+                texel[3] = ((alpha >> (i * 4)) & 0xf) as u8 * 17;
+            }
+            texels
+        }
+        wgpu::TextureFormat::Bc3RgbaUnorm | wgpu::TextureFormat::Bc3RgbaUnormSrgb => {
+            let mut texels = decode_color(&block[8..], false);
+            for (texel, alpha) in texels.iter_mut().zip(decode_channel(&block[..8])) {
+                texel[3] = alpha;
+            }
+            texels
+        }
+        wgpu::TextureFormat::Bc4RUnorm => decode_channel(block).map(|red| [red, 0, 0, u8::MAX]),
+        wgpu::TextureFormat::Bc5RgUnorm => {
+            let green = decode_channel(&block[8..]);
+            let mut texels = [[0, 0, 0, u8::MAX]; 16];
+            for ((texel, red), green) in texels.iter_mut().zip(decode_channel(block)).zip(green) {
+                texel[0] = red;
+                texel[1] = green;
+            }
+            texels
+        }
+        wgpu::TextureFormat::Bc7RgbaUnorm | wgpu::TextureFormat::Bc7RgbaUnormSrgb => {
+            decode_bc7(block)
+        }
+        wgpu::TextureFormat::Etc2Rgb8Unorm | wgpu::TextureFormat::Etc2Rgb8UnormSrgb => {
+            decode_etc2(block, false)
+        }
+        wgpu::TextureFormat::Etc2Rgb8A1Unorm | wgpu::TextureFormat::Etc2Rgb8A1UnormSrgb => {
+            decode_etc2(block, true)
+        }
+        wgpu::TextureFormat::Etc2Rgba8Unorm | wgpu::TextureFormat::Etc2Rgba8UnormSrgb => {
+            let mut texels = decode_etc2(&block[8..], false);
+            for (texel, alpha) in texels.iter_mut().zip(decode_eac(&block[..8], false)) {
+                texel[3] = alpha as u8;
+            }
+            texels
+        }
+        wgpu::TextureFormat::EacR11Unorm => {
+            decode_eac(block, true).map(|red| [to_unorm8(red), 0, 0, u8::MAX])
+        }
+        wgpu::TextureFormat::EacRg11Unorm => {
+            let green = decode_eac(&block[8..], true);
+            let mut texels = [[0, 0, 0, u8::MAX]; 16];
+            for ((texel, red), green) in texels.iter_mut().zip(decode_eac(block, true)).zip(green) {
+                texel[0] = to_unorm8(red);
+                texel[1] = to_unorm8(green);
+            }
+            texels
+        }
+        _ => unreachable!("checked by decompressed_format"),
+    }
+}
+
+/// BC1 color block, BC2 and BC3 always use four colors
+fn decode_color(block: &[u8], bc1: bool) -> [[u8; 4]; 16] {
+    let color0 = u16::from_le_bytes([block[0], block[1]]);
+    let color1 = u16::from_le_bytes([block[2], block[3]]);
+    let indices = u32::from_le_bytes(block[4..8].try_into().unwrap());
+
+    let (c0, c1) = (rgb565(color0), rgb565(color1));
+    let mix = |a: u8, b: u8, weight_a: u32, weight_b: u32| {
+        ((a as u32 * weight_a + b as u32 * weight_b) / (weight_a + weight_b)) as u8
+    };
+    let palette = match !bc1 || color0 > color1 {
+        true => [
+            c0,
+            c1,
+            [0, 1, 2, 3].map(|i| mix(c0[i], c1[i], 2, 1)),
+            [0, 1, 2, 3].map(|i| mix(c0[i], c1[i], 1, 2)),
+        ],
+        false => [
+            c0,
+            c1,
+            [0, 1, 2, 3].map(|i| mix(c0[i], c1[i], 1, 1)),
+            [0, 0, 0, 0],
+        ],
+    };
+
+    std::array::from_fn(|i| palette[((indices >> (i * 2)) & 0b11) as usize])
+}
+
+fn rgb565(color: u16) -> [u8; 4] {
+    let r = ((color >> 11) & 0x1f) as u32;
+    let g = ((color >> 5) & 0x3f) as u32;
+    let b = (color & 0x1f) as u32;
+    [
+        (r * 255 / 31) as u8,
+        (g * 255 / 63) as u8,
+        (b * 255 / 31) as u8,
+        u8::MAX,
+    ]
+}
+
+/// BC4 block, also the alpha of BC3 and both channels of BC5
+fn decode_channel(block: &[u8]) -> [u8; 16] {
+    let (a0, a1) = (block[0] as u32, block[1] as u32);
+    let mut bits = [0; 8];
+    bits[..6].copy_from_slice(&block[2..8]);
+    let indices = u64::from_le_bytes(bits);
+
+    let palette: [u8; 8] = match a0 > a1 {
+        true => std::array::from_fn(|i| match i {
+            0 => a0 as u8,
+            1 => a1 as u8,
+            i => (((8 - i as u32) * a0 + (i as u32 - 1) * a1) / 7) as u8,
+        }),
+        false => std::array::from_fn(|i| match i {
+            0 => a0 as u8,
+            1 => a1 as u8,
+            6 => 0,
+            7 => u8::MAX,
+            i => (((6 - i as u32) * a0 + (i as u32 - 1) * a1) / 5) as u8,
+        }),
+    };
+
+    std::array::from_fn(|i| palette[((indices >> (i * 3)) & 0b111) as usize])
+}
+
+//
+// BC7
+//
+
+struct Bc7Mode {
+    subsets: usize,
+    partition_bits: u32,
+    rotation_bits: u32,
+    index_selection_bits: u32,
+    color_bits: u32,
+    alpha_bits: u32,
+    endpoint_pbits: bool,
+    shared_pbits: bool,
+    index_bits: u32,
+    secondary_index_bits: u32,
+}
+
+#[rustfmt::skip]
+const BC7_MODES: [Bc7Mode; 8] = [
+    Bc7Mode { subsets: 3, partition_bits: 4, rotation_bits: 0, index_selection_bits: 0, color_bits: 4, alpha_bits: 0, endpoint_pbits: true, shared_pbits: false, index_bits: 3, secondary_index_bits: 0 },
+    Bc7Mode { subsets: 2, partition_bits: 6, rotation_bits: 0, index_selection_bits: 0, color_bits: 6, alpha_bits: 0, endpoint_pbits: false, shared_pbits: true, index_bits: 3, secondary_index_bits: 0 },
+    Bc7Mode { subsets: 3, partition_bits: 6, rotation_bits: 0, index_selection_bits: 0, color_bits: 5, alpha_bits: 0, endpoint_pbits: false, shared_pbits: false, index_bits: 2, secondary_index_bits: 0 },
+    Bc7Mode { subsets: 2, partition_bits: 6, rotation_bits: 0, index_selection_bits: 0, color_bits: 7, alpha_bits: 0, endpoint_pbits: true, shared_pbits: false, index_bits: 2, secondary_index_bits: 0 },
+    Bc7Mode { subsets: 1, partition_bits: 0, rotation_bits: 2, index_selection_bits: 1, color_bits: 5, alpha_bits: 6, endpoint_pbits: false, shared_pbits: false, index_bits: 2, secondary_index_bits: 3 },
+    Bc7Mode { subsets: 1, partition_bits: 0, rotation_bits: 2, index_selection_bits: 0, color_bits: 7, alpha_bits: 8, endpoint_pbits: false, shared_pbits: false, index_bits: 2, secondary_index_bits: 2 },
+    Bc7Mode { subsets: 1, partition_bits: 0, rotation_bits: 0, index_selection_bits: 0, color_bits: 7, alpha_bits: 7, endpoint_pbits: true, shared_pbits: false, index_bits: 4, secondary_index_bits: 0 },
+    Bc7Mode { subsets: 2, partition_bits: 6, rotation_bits: 0, index_selection_bits: 0, color_bits: 5, alpha_bits: 5, endpoint_pbits: true, shared_pbits: false, index_bits: 2, secondary_index_bits: 0 },
+];
+
+/// Subset 1 texels of the two subset partitions, one bit per texel
+#[rustfmt::skip]
+const BC7_PARTITIONS_2: [u16; 64] = [
+    0xcccc, 0x8888, 0xeeee, 0xecc8, 0xc880, 0xfeec, 0xfec8, 0xec80,
+    0xc800, 0xffec, 0xfe80, 0xe800, 0xffe8, 0xff00, 0xfff0, 0xf000,
+    0xf710, 0x008e, 0x7100, 0x08ce, 0x008c, 0x7310, 0x3100, 0x8cce,
+    0x088c, 0x3110, 0x6666, 0x366c, 0x17e8, 0x0ff0, 0x718e, 0x399c,
+    0xaaaa, 0xf0f0, 0x5a5a, 0x33cc, 0x3c3c, 0x55aa, 0x9696, 0xa55a,
+    0x73ce, 0x13c8, 0x324c, 0x3bdc, 0x6996, 0xc33c, 0x9966, 0x0660,
+    0x0272, 0x04e4, 0x4e40, 0x2720, 0xc936, 0x936c, 0x39c6, 0x639c,
+    0x9336, 0x9cc6, 0x817e, 0xe718, 0xccf0, 0x0fcc, 0x7744, 0xee22,
+];
+
+/// Subset of each texel of the three subset partitions
+#[rustfmt::skip]
+const BC7_PARTITIONS_3: [[u8; 16]; 64] = [
+    [0, 0, 1, 1, 0, 0, 1, 1, 0, 2, 2, 1, 2, 2, 2, 2], [0, 0, 0, 1, 0, 0, 1, 1, 2, 2, 1, 1, 2, 2, 2, 1],
+    [0, 0, 0, 0, 2, 0, 0, 1, 2, 2, 1, 1, 2, 2, 1, 1], [0, 2, 2, 2, 0, 0, 2, 2, 0, 0, 1, 1, 0, 1, 1, 1],
+    [0, 0, 0, 0, 0, 0, 0, 0, 1, 1, 2, 2, 1, 1, 2, 2], [0, 0, 1, 1, 0, 0, 1, 1, 0, 0, 2, 2, 0, 0, 2, 2],
+    [0, 0, 2, 2, 0, 0, 2, 2, 1, 1, 1, 1, 1, 1, 1, 1], [0, 0, 1, 1, 0, 0, 1, 1, 2, 2, 1, 1, 2, 2, 1, 1],
+    [0, 0, 0, 0, 0, 0, 0, 0, 1, 1, 1, 1, 2, 2, 2, 2], [0, 0, 0, 0, 1, 1, 1, 1, 1, 1, 1, 1, 2, 2, 2, 2],
+    [0, 0, 0, 0, 1, 1, 1, 1, 2, 2, 2, 2, 2, 2, 2, 2], [0, 0, 1, 2, 0, 0, 1, 2, 0, 0, 1, 2, 0, 0, 1, 2],
+    [0, 1, 1, 2, 0, 1, 1, 2, 0, 1, 1, 2, 0, 1, 1, 2], [0, 1, 2, 2, 0, 1, 2, 2, 0, 1, 2, 2, 0, 1, 2, 2],
+    [0, 0, 1, 1, 0, 1, 1, 2, 1, 1, 2, 2, 1, 2, 2, 2], [0, 0, 1, 1, 2, 0, 0, 1, 2, 2, 0, 0, 2, 2, 2, 0],
+    [0, 0, 0, 1, 0, 0, 1, 1, 0, 1, 1, 2, 1, 1, 2, 2], [0, 1, 1, 1, 0, 0, 1, 1, 2, 0, 0, 1, 2, 2, 0, 0],
+    [0, 0, 0, 0, 1, 1, 2, 2, 1, 1, 2, 2, 1, 1, 2, 2], [0, 0, 2, 2, 0, 0, 2, 2, 0, 0, 2, 2, 1, 1, 1, 1],
+    [0, 1, 1, 1, 0, 1, 1, 1, 0, 2, 2, 2, 0, 2, 2, 2], [0, 0, 0, 1, 0, 0, 0, 1, 2, 2, 2, 1, 2, 2, 2, 1],
+    [0, 0, 0, 0, 0, 0, 1, 1, 0, 1, 2, 2, 0, 1, 2, 2], [0, 0, 0, 0, 1, 1, 0, 0, 2, 2, 1, 0, 2, 2, 1, 0],
+    [0, 1, 2, 2, 0, 1, 2, 2, 0, 0, 1, 1, 0, 0, 0, 0], [0, 0, 1, 2, 0, 0, 1, 2, 1, 1, 2, 2, 2, 2, 2, 2],
+    [0, 1, 1, 0, 1, 2, 2, 1, 1, 2, 2, 1, 0, 1, 1, 0], [0, 0, 0, 0, 0, 1, 1, 0, 1, 2, 2, 1, 1, 2, 2, 1],
+    [0, 0, 2, 2, 1, 1, 0, 2, 1, 1, 0, 2, 0, 0, 2, 2], [0, 1, 1, 0, 0, 1, 1, 0, 2, 0, 0, 2, 2, 2, 2, 2],
+    [0, 0, 1, 1, 0, 1, 2, 2, 0, 1, 2, 2, 0, 0, 1, 1], [0, 0, 0, 0, 2, 0, 0, 0, 2, 2, 1, 1, 2, 2, 2, 1],
+    [0, 0, 0, 0, 0, 0, 0, 2, 1, 1, 2, 2, 1, 2, 2, 2], [0, 2, 2, 2, 0, 0, 2, 2, 0, 0, 1, 2, 0, 0, 1, 1],
+    [0, 0, 1, 1, 0, 0, 1, 2, 0, 0, 2, 2, 0, 2, 2, 2], [0, 1, 2, 0, 0, 1, 2, 0, 0, 1, 2, 0, 0, 1, 2, 0],
+    [0, 0, 0, 0, 1, 1, 1, 1, 2, 2, 2, 2, 0, 0, 0, 0], [0, 1, 2, 0, 1, 2, 0, 1, 2, 0, 1, 2, 0, 1, 2, 0],
+    [0, 1, 2, 0, 2, 0, 1, 2, 1, 2, 0, 1, 0, 1, 2, 0], [0, 0, 1, 1, 2, 2, 0, 0, 1, 1, 2, 2, 0, 0, 1, 1],
+    [0, 0, 1, 1, 1, 1, 2, 2, 2, 2, 0, 0, 0, 0, 1, 1], [0, 1, 0, 1, 0, 1, 0, 1, 2, 2, 2, 2, 2, 2, 2, 2],
+    [0, 0, 0, 0, 0, 0, 0, 0, 2, 1, 2, 1, 2, 1, 2, 1], [0, 0, 2, 2, 1, 1, 2, 2, 0, 0, 2, 2, 1, 1, 2, 2],
+    [0, 0, 2, 2, 0, 0, 1, 1, 0, 0, 2, 2, 0, 0, 1, 1], [0, 2, 2, 0, 1, 2, 2, 1, 0, 2, 2, 0, 1, 2, 2, 1],
+    [0, 1, 0, 1, 2, 2, 2, 2, 2, 2, 2, 2, 0, 1, 0, 1], [0, 0, 0, 0, 2, 1, 2, 1, 2, 1, 2, 1, 2, 1, 2, 1],
+    [0, 1, 0, 1, 0, 1, 0, 1, 0, 1, 0, 1, 2, 2, 2, 2], [0, 2, 2, 2, 0, 1, 1, 1, 0, 2, 2, 2, 0, 1, 1, 1],
+    [0, 0, 0, 2, 1, 1, 1, 2, 0, 0, 0, 2, 1, 1, 1, 2], [0, 0, 0, 0, 2, 1, 1, 2, 2, 1, 1, 2, 2, 1, 1, 2],
+    [0, 2, 2, 2, 0, 1, 1, 1, 0, 1, 1, 1, 0, 2, 2, 2], [0, 0, 0, 2, 1, 1, 1, 2, 1, 1, 1, 2, 0, 0, 0, 2],
+    [0, 1, 1, 0, 0, 1, 1, 0, 0, 1, 1, 0, 2, 2, 2, 2], [0, 0, 0, 0, 0, 0, 0, 0, 2, 1, 1, 2, 2, 1, 1, 2],
+    [0, 1, 1, 0, 0, 1, 1, 0, 2, 2, 2, 2, 2, 2, 2, 2], [0, 0, 2, 2, 0, 0, 1, 1, 0, 0, 1, 1, 0, 0, 2, 2],
+    [0, 0, 2, 2, 1, 1, 2, 2, 1, 1, 2, 2, 0, 0, 2, 2], [0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 2, 1, 1, 2],
+    [0, 0, 0, 2, 0, 0, 0, 1, 0, 0, 0, 2, 0, 0, 0, 1], [0, 2, 2, 2, 1, 2, 2, 2, 0, 2, 2, 2, 1, 2, 2, 2],
+    [0, 1, 0, 1, 2, 2, 2, 2, 2, 2, 2, 2, 2, 2, 2, 2], [0, 1, 1, 1, 2, 0, 1, 1, 2, 2, 0, 1, 2, 2, 2, 0],
+];
+
+/// Anchor texel of subset 1 of the two subset partitions
+#[rustfmt::skip]
+const BC7_ANCHORS_2: [u8; 64] = [
+    15, 15, 15, 15, 15, 15, 15, 15, 15, 15, 15, 15, 15, 15, 15, 15,
+    15, 2, 8, 2, 2, 8, 8, 15, 2, 8, 2, 2, 8, 8, 2, 2,
+    15, 15, 6, 8, 2, 8, 15, 15, 2, 8, 2, 2, 2, 15, 15, 6,
+    6, 2, 6, 8, 15, 15, 2, 2, 15, 15, 15, 15, 15, 2, 2, 15,
+];
+
+/// Anchor texels of subsets 1 and 2 of the three subset partitions
+#[rustfmt::skip]
+const BC7_ANCHORS_3: [[u8; 64]; 2] = [
+    [
+        3, 3, 15, 15, 8, 3, 15, 15, 8, 8, 6, 6, 6, 5, 3, 3,
+        3, 3, 8, 15, 3, 3, 6, 10, 5, 8, 8, 6, 8, 5, 15, 15,
+        8, 15, 3, 5, 6, 10, 8, 15, 15, 3, 15, 5, 15, 15, 15, 15,
+        3, 15, 5, 5, 5, 8, 5, 10, 5, 10, 8, 13, 15, 12, 3, 3,
+    ],
+    [
+        15, 8, 8, 3, 15, 15, 3, 8, 15, 15, 15, 15, 15, 15, 15, 8,
+        15, 8, 15, 3, 15, 8, 15, 8, 3, 15, 6, 10, 15, 15, 10, 8,
+        15, 3, 15, 10, 10, 8, 9, 10, 6, 15, 8, 15, 3, 6, 6, 8,
+        15, 3, 15, 15, 15, 15, 15, 15, 15, 15, 15, 15, 3, 15, 15, 8,
+    ],
+];
+
+fn decode_bc7(block: &[u8]) -> [[u8; 4]; 16] {
+    let mut bits = BlockBits {
+        value: u128::from_le_bytes(block.try_into().unwrap()),
+        position: 0,
+    };
+    // the mode is the position of the lowest set bit, a zero byte is reserved
+    let mode_index = block[0].trailing_zeros() as usize;
+    let Some(mode) = BC7_MODES.get(mode_index) else {
+        return [[0; 4]; 16];
+    };
+    bits.read(mode_index as u32 + 1);
+    let partition = bits.read(mode.partition_bits) as usize;
+    let rotation = bits.read(mode.rotation_bits);
+    let index_selection = bits.read(mode.index_selection_bits);
+
+    // all reds, then all greens, blues and alphas of every endpoint
+    let endpoint_count = mode.subsets * 2;
+    let mut endpoints = [[0u32; 4]; 6];
+    for channel in 0..4 {
+        let channel_bits = match channel {
+            3 => mode.alpha_bits,
+            _ => mode.color_bits,
+        };
+        for endpoint in &mut endpoints[..endpoint_count] {
+            endpoint[channel] = bits.read(channel_bits);
+        }
+    }
+
+    // p-bits are an extra lowest bit of every channel
+    let pbit = mode.endpoint_pbits || mode.shared_pbits;
+    if mode.endpoint_pbits {
+        for endpoint in &mut endpoints[..endpoint_count] {
+            let pbit = bits.read(1);
+            endpoint
+                .iter_mut()
+                .for_each(|value| *value = *value << 1 | pbit);
+        }
+    }
+    if mode.shared_pbits {
+        for subset in endpoints[..endpoint_count].chunks_exact_mut(2) {
+            let pbit = bits.read(1);
+            for endpoint in subset {
+                endpoint
+                    .iter_mut()
+                    .for_each(|value| *value = *value << 1 | pbit);
+            }
+        }
+    }
+    let endpoints = endpoints.map(|endpoint| {
+        let color_bits = mode.color_bits + pbit as u32;
+        let alpha = match mode.alpha_bits {
+            0 => u8::MAX,
+            alpha_bits => expand_bits(endpoint[3], alpha_bits + pbit as u32),
+        };
+        [
+            expand_bits(endpoint[0], color_bits),
+            expand_bits(endpoint[1], color_bits),
+            expand_bits(endpoint[2], color_bits),
+            alpha,
+        ]
+    });
+
+    let subset = |texel: usize| match mode.subsets {
+        1 => 0,
+        2 => (BC7_PARTITIONS_2[partition] >> texel) as usize & 1,
+        _ => BC7_PARTITIONS_3[partition][texel] as usize,
+    };
+    // the highest bit of the first index of every subset is implicitly zero
+    let anchor = |texel: usize| {
+        texel == 0
+            || match mode.subsets {
+                2 => texel == BC7_ANCHORS_2[partition] as usize,
+                3 => {
+                    texel == BC7_ANCHORS_3[0][partition] as usize
+                        || texel == BC7_ANCHORS_3[1][partition] as usize
+                }
+                _ => false,
+            }
+    };
+    let indices: [u32; 16] =
+        std::array::from_fn(|texel| bits.read(mode.index_bits - anchor(texel) as u32));
+    let secondary_indices: [u32; 16] = match mode.secondary_index_bits {
+        0 => indices,
+        index_bits => std::array::from_fn(|texel| bits.read(index_bits - (texel == 0) as u32)),
+    };
+
+    std::array::from_fn(|texel| {
+        let [start, end] = [0, 1].map(|i| endpoints[subset(texel) * 2 + i]);
+        let (color_index, color_bits, alpha_index, alpha_bits) = match index_selection {
+            0 => (
+                indices[texel],
+                mode.index_bits,
+                secondary_indices[texel],
+                mode.secondary_index_bits.max(mode.index_bits),
+            ),
+            _ => (
+                secondary_indices[texel],
+                mode.secondary_index_bits,
+                indices[texel],
+                mode.index_bits,
+            ),
+        };
+        let color_weight = bc7_weight(color_index, color_bits);
+        let alpha_weight = bc7_weight(alpha_index, alpha_bits);
+        let mut texel: [u8; 4] = std::array::from_fn(|channel| {
+            let weight = match channel {
+                3 => alpha_weight,
+                _ => color_weight,
+            };
+            ((start[channel] as u32 * (64 - weight) + end[channel] as u32 * weight + 32) >> 6) as u8
+        });
+        match rotation {
+            1 => texel.swap(0, 3),
+            2 => texel.swap(1, 3),
+            3 => texel.swap(2, 3),
+            _ => {}
+        }
+        texel
+    })
+}
+
+fn bc7_weight(index: u32, bits: u32) -> u32 {
+    const WEIGHTS_2: [u32; 4] = [0, 21, 43, 64];
+    const WEIGHTS_3: [u32; 8] = [0, 9, 18, 27, 37, 46, 55, 64];
+    const WEIGHTS_4: [u32; 16] = [0, 4, 9, 13, 17, 21, 26, 30, 34, 38, 43, 47, 51, 55, 60, 64];
+    match bits {
+        2 => WEIGHTS_2[index as usize],
+        3 => WEIGHTS_3[index as usize],
+        _ => WEIGHTS_4[index as usize],
+    }
+}
+
+/// Little endian bit stream of a 128 bit block
+struct BlockBits {
+    value: u128,
+    position: u32,
+}
+
+impl BlockBits {
+    fn read(&mut self, count: u32) -> u32 {
+        let value = (self.value >> self.position) as u32 & ((1 << count) - 1);
+        self.position += count;
+        value
+    }
+}
+
+/// Replicate the highest bits of a `bits` wide value into the missing low bits of a byte
+fn expand_bits(value: u32, bits: u32) -> u8 {
+    let value = value << (8 - bits);
+    (value | value >> bits) as u8
+}
+
+//
+// ETC2
+//
+
+const ETC_MODIFIERS: [[i32; 2]; 8] = [
+    [2, 8],
+    [5, 17],
+    [9, 29],
+    [13, 42],
+    [18, 60],
+    [24, 80],
+    [33, 106],
+    [47, 183],
+];
+const ETC_DISTANCES: [i32; 8] = [3, 6, 11, 16, 23, 32, 41, 64];
+
+/// ETC2 RGB block, `punchthrough` for the 1 bit alpha of `Etc2Rgb8A1`
+fn decode_etc2(block: &[u8], punchthrough: bool) -> [[u8; 4]; 16] {
+    let b = |i: usize| block[i] as i32;
+    // the indices of texels are stored column by column
+    let indices = u32::from_be_bytes(block[4..8].try_into().unwrap());
+    let index = |texel: usize| {
+        let bit = texel % 4 * 4 + texel / 4;
+        ((indices >> (bit + 16)) & 1) << 1 | (indices >> bit) & 1
+    };
+    // without punchthrough alpha the flag selects the differential mode
+    let flag = b(3) & 0b10 != 0;
+    let (differential, opaque) = match punchthrough {
+        true => (true, flag),
+        false => (flag, true),
+    };
+    let transparent = |texel: usize| !opaque && index(texel) == 2;
+    let clamp = |value: i32| value.clamp(0, 255);
+    let extend4 = |value: i32| value << 4 | value;
+    let extend5 = |value: i32| value << 3 | value >> 2;
+    let paint = |palette: [[i32; 3]; 4]| -> [[u8; 4]; 16] {
+        std::array::from_fn(|texel| match transparent(texel) {
+            true => [0; 4],
+            false => {
+                let [r, g, b] = palette[index(texel) as usize];
+                [clamp(r) as u8, clamp(g) as u8, clamp(b) as u8, u8::MAX]
+            }
+        })
+    };
+    let offset = |color: [i32; 3], distance: i32| color.map(|channel| channel + distance);
+
+    let base = [b(0) >> 3, b(1) >> 3, b(2) >> 3];
+    // 3 bit two's complement
+    let delta = [b(0), b(1), b(2)].map(|value| (value & 0b111) << 29 >> 29);
+    let second = [0, 1, 2].map(|i| base[i] + delta[i]);
+    let overflow = |i: usize| !(0..32).contains(&second[i]);
+
+    if !differential || !(overflow(0) || overflow(1) || overflow(2)) {
+        // two sub blocks with a base color and an intensity modifier table each
+        let colors = match differential {
+            true => [base.map(extend5), second.map(extend5)],
+            false => [
+                [b(0) >> 4, b(1) >> 4, b(2) >> 4].map(extend4),
+                [b(0) & 0xf, b(1) & 0xf, b(2) & 0xf].map(extend4),
+            ],
+        };
+        let tables = [b(3) >> 5, (b(3) >> 2) & 0b111].map(|table| ETC_MODIFIERS[table as usize]);
+        let flip = b(3) & 1 != 0;
+        return std::array::from_fn(|texel| {
+            if transparent(texel) {
+                return [0; 4];
+            }
+            let (x, y) = (texel % 4, texel / 4);
+            let sub_block = match flip {
+                true => y >= 2,
+                false => x >= 2,
+            } as usize;
+            let [small, large] = tables[sub_block];
+            let modifier = match (index(texel), opaque) {
+                (0 | 2, false) => 0,
+                (0, true) => small,
+                (1, _) => large,
+                (2, true) => -small,
+                _ => -large,
+            };
+            let [r, g, b] = offset(colors[sub_block], modifier);
+            [clamp(r) as u8, clamp(g) as u8, clamp(b) as u8, u8::MAX]
+        });
+    }
+
+    if overflow(0) {
+        // T mode
+        let color0 = [(b(0) >> 1) & 0b1100 | b(0) & 0b11, b(1) >> 4, b(1) & 0xf].map(extend4);
+        let color1 = [b(2) >> 4, b(2) & 0xf, b(3) >> 4].map(extend4);
+        let distance = ETC_DISTANCES[((b(3) >> 1) & 0b110 | b(3) & 1) as usize];
+        return paint([
+            color0,
+            offset(color1, distance),
+            color1,
+            offset(color1, -distance),
+        ]);
+    }
+
+    if overflow(1) {
+        // H mode
+        let color0 = [
+            (b(0) >> 3) & 0xf,
+            (b(0) & 0b111) << 1 | (b(1) >> 4) & 1,
+            b(1) & 0b1000 | (b(1) & 0b11) << 1 | b(2) >> 7,
+        ];
+        let color1 = [
+            (b(2) >> 3) & 0xf,
+            (b(2) & 0b111) << 1 | b(3) >> 7,
+            (b(3) >> 3) & 0xf,
+        ];
+        let order = |[r, g, b]: [i32; 3]| r << 8 | g << 4 | b;
+        let distance = ETC_DISTANCES
+            [(b(3) & 0b100 | (b(3) & 1) << 1 | (order(color0) >= order(color1)) as i32) as usize];
+        let (color0, color1) = (color0.map(extend4), color1.map(extend4));
+        return paint([
+            offset(color0, distance),
+            offset(color0, -distance),
+            offset(color1, distance),
+            offset(color1, -distance),
+        ]);
+    }
+
+    // planar mode, always opaque
+    let extend6 = |value: i32| value << 2 | value >> 4;
+    let extend7 = |value: i32| value << 1 | value >> 6;
+    let origin = [
+        extend6((b(0) >> 1) & 0x3f),
+        extend7((b(0) & 1) << 6 | (b(1) >> 1) & 0x3f),
+        extend6((b(1) & 1) << 5 | b(2) & 0b11000 | (b(2) & 0b11) << 1 | b(3) >> 7),
+    ];
+    let horizontal = [
+        extend6(((b(3) >> 2) & 0x1f) << 1 | b(3) & 1),
+        extend7(b(4) >> 1),
+        extend6((b(4) & 1) << 5 | b(5) >> 3),
+    ];
+    let vertical = [
+        extend6((b(5) & 0b111) << 3 | b(6) >> 5),
+        extend7((b(6) & 0x1f) << 2 | b(7) >> 6),
+        extend6(b(7) & 0x3f),
+    ];
+    std::array::from_fn(|texel| {
+        let (x, y) = ((texel % 4) as i32, (texel / 4) as i32);
+        let [r, g, b] = [0, 1, 2].map(|i| {
+            let value = x * (horizontal[i] - origin[i]) + y * (vertical[i] - origin[i]);
+            clamp((value + 4 * origin[i] + 2) >> 2) as u8
+        });
+        [r, g, b, u8::MAX]
+    })
+}
+
+#[rustfmt::skip]
+const EAC_MODIFIERS: [[i32; 8]; 16] = [
+    [-3, -6, -9, -15, 2, 5, 8, 14], [-3, -7, -10, -13, 2, 6, 9, 12],
+    [-2, -5, -8, -13, 1, 4, 7, 12], [-2, -4, -6, -13, 1, 3, 5, 12],
+    [-3, -6, -8, -12, 2, 5, 7, 11], [-3, -7, -9, -11, 2, 6, 8, 10],
+    [-4, -7, -8, -11, 3, 6, 7, 10], [-3, -5, -8, -11, 2, 4, 7, 10],
+    [-2, -6, -8, -10, 1, 5, 7, 9], [-2, -5, -8, -10, 1, 4, 7, 9],
+    [-2, -4, -8, -10, 1, 3, 7, 9], [-2, -5, -7, -10, 1, 4, 6, 9],
+    [-3, -4, -7, -10, 2, 3, 6, 9], [-1, -2, -3, -10, 0, 1, 2, 9],
+    [-4, -6, -8, -9, 3, 5, 7, 8], [-3, -5, -7, -9, 2, 4, 6, 8],
+];
+
+/// Unsigned EAC block as 11 bit values, or 8 bit values for the alpha of `Etc2Rgba8`
+fn decode_eac(block: &[u8], eleven_bit: bool) -> [u16; 16] {
+    let base = block[0] as i32;
+    let multiplier = (block[1] >> 4) as i32;
+    let modifiers = EAC_MODIFIERS[(block[1] & 0xf) as usize];
+    let mut bits = [0; 8];
+    bits[2..].copy_from_slice(&block[2..8]);
+    let indices = u64::from_be_bytes(bits);
+
+    std::array::from_fn(|texel| {
+        // stored column by column, first texel in the highest bits
+        let position = 45 - (texel % 4 * 4 + texel / 4) * 3;
+        let modifier = modifiers[((indices >> position) & 0b111) as usize];
+        match (eleven_bit, multiplier) {
+            (false, _) => (base + modifier * multiplier).clamp(0, 255) as u16,
+            (true, 0) => (base * 8 + 4 + modifier).clamp(0, 2047) as u16,
+            (true, _) => (base * 8 + 4 + modifier * multiplier * 8).clamp(0, 2047) as u16,
+        }
+    })
+}
+
+fn to_unorm8(value: u16) -> u8 {
+    ((value as u32 * 255 + 1023) / 2047) as u8
+}
+
+#[cfg(test)]
+mod tests {
+    use super::decompress_blocks;
+
+    #[test]
+    fn test_decompress_blocks() {
+        // BC1, red and blue endpoints, texel 0 red, texel 1 blue, rest interpolated
+        let block = [0x00, 0xf8, 0x1f, 0x00, 0b1110_0100, 0xff, 0xff, 0xff];
+        let rgba = decompress_blocks(wgpu::TextureFormat::Bc1RgbaUnorm, 4, 4, &block).unwrap();
+        assert_eq!(&rgba[0..4], &[255, 0, 0, 255]);
+        assert_eq!(&rgba[4..8], &[0, 0, 255, 255]);
+        assert_eq!(&rgba[8..12], &[170, 0, 85, 255]);
+        assert_eq!(&rgba[12..16], &[85, 0, 170, 255]);
+
+        // BC4 on a 2x2 image crops the block, 6 interpolated values when a0 > a1
+        let block = [255, 0, 0b1101_0001, 0b0000_0000, 0, 0, 0, 0];
+        let rgba = decompress_blocks(wgpu::TextureFormat::Bc4RUnorm, 2, 2, &block).unwrap();
+        assert_eq!(rgba.len(), 2 * 2 * 4);
+        assert_eq!(rgba[0], 0);
+        assert_eq!(rgba[4], 218);
+        assert_eq!(rgba[8], 255);
+
+        // too little data
+        assert!(decompress_blocks(wgpu::TextureFormat::Bc3RgbaUnorm, 8, 4, &[0; 16]).is_none());
+        // BC7 mode 6, black transparent and white opaque endpoints, texel 1 at the white end
+        let block: u128 =
+            1 << 6 | 0x7f << 14 | 0x7f << 28 | 0x7f << 42 | 0x7f << 56 | 1 << 64 | 0xf << 68;
+        let rgba = decompress_blocks(
+            wgpu::TextureFormat::Bc7RgbaUnorm,
+            4,
+            4,
+            &block.to_le_bytes(),
+        )
+        .unwrap();
+        assert_eq!(&rgba[0..4], &[0, 0, 0, 0]);
+        assert_eq!(&rgba[4..8], &[255, 255, 255, 255]);
+        assert_eq!(&rgba[8..12], &[0, 0, 0, 0]);
+
+        // ETC2 individual mode, red left and black right sub block, smallest modifier
+        let block = [0xf0, 0, 0, 0, 0, 0, 0, 0];
+        let rgba = decompress_blocks(wgpu::TextureFormat::Etc2Rgb8Unorm, 4, 4, &block).unwrap();
+        assert_eq!(&rgba[0..4], &[255, 2, 2, 255]);
+        assert_eq!(&rgba[8..12], &[2, 2, 2, 255]);
+
+        // EAC R11, base 128 with multiplier 1 and modifier -3
+        let block = [128, 0x10, 0, 0, 0, 0, 0, 0];
+        let rgba = decompress_blocks(wgpu::TextureFormat::EacR11Unorm, 4, 4, &block).unwrap();
+        assert_eq!(&rgba[0..4], &[125, 0, 0, 255]);
+
+        // no CPU decoder
+        let astc = wgpu::TextureFormat::Astc {
+            block: wgpu::AstcBlock::B4x4,
+            channel: wgpu::AstcChannel::Unorm,
+        };
+        assert!(decompress_blocks(astc, 4, 4, &[0; 16]).is_none());
+    }
+}
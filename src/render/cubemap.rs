@@ -0,0 +1,128 @@
+use crate::{render, Context};
+
+#[derive(thiserror::Error, Debug)]
+pub enum EquirectangularError {
+    #[error("target must be a 2D texture with 6 layers, got {dimension:?} with {layers} layers")]
+    NotCube {
+        dimension: wgpu::TextureDimension,
+        layers: u32,
+    },
+    #[error("can not convert to {0:?}, it must be a renderable float format")]
+    UnsupportedFormat(wgpu::TextureFormat),
+    #[error("source needs TEXTURE_BINDING and target RENDER_ATTACHMENT usage")]
+    MissingUsage,
+}
+
+/// Render an equirectangular (latitude-longitude) panorama into mip level 0 of a cubemap
+///
+/// Faces are written in +X, -X, +Y, -Y, +Z, -Z order, the same as cube views sample them.
+/// The source is filtered in the shader so non-filterable float formats work
+pub fn equirectangular_to_cubemap(
+    ctx: &mut Context,
+    source: &wgpu::Texture,
+    target: &wgpu::Texture,
+) -> Result<(), EquirectangularError> {
+    if target.dimension() != wgpu::TextureDimension::D2 || target.depth_or_array_layers() != 6 {
+        return Err(EquirectangularError::NotCube {
+            dimension: target.dimension(),
+            layers: target.depth_or_array_layers(),
+        });
+    }
+    let format = target.format();
+    let features = ctx.render.adapter.get_texture_format_features(format);
+    let float = matches!(
+        format.sample_type(None, None),
+        Some(wgpu::TextureSampleType::Float { .. })
+    );
+    if !float
+        || !features
+            .allowed_usages
+            .contains(wgpu::TextureUsages::RENDER_ATTACHMENT)
+    {
+        return Err(EquirectangularError::UnsupportedFormat(format));
+    }
+    if !source
+        .usage()
+        .contains(wgpu::TextureUsages::TEXTURE_BINDING)
+        || !target
+            .usage()
+            .contains(wgpu::TextureUsages::RENDER_ATTACHMENT)
+    {
+        return Err(EquirectangularError::MissingUsage);
+    }
+    let shader =
+        render::builtin_shader(ctx, "equirectangular", include_str!("equirectangular.wgsl"));
+    let bindgroup_layout = render::BindGroupLayoutBuilder::new()
+        .label("equirectangular")
+        .entries(vec![
+            // panorama
+            render::BindGroupLayoutEntry::new()
+                .texture_float_nonfilterable()
+                .fragment(),
+        ])
+        .build(ctx);
+    let pipeline_layout = render::PipelineLayoutBuilder::new()
+        .label("equirectangular")
+        .bind_groups(vec![bindgroup_layout.clone()])
+        .build(ctx);
+    let pipeline = render::RenderPipelineBuilder::new(shader, pipeline_layout)
+        .label("equirectangular")
+        .single_target(render::ColorTargetState::new().format(format))
+        .build(ctx);
+
+    let source_view = render::ArcTextureView::new(
+        ctx,
+        source.create_view(&wgpu::TextureViewDescriptor {
+            label: Some("equirectangular source"),
+            dimension: Some(wgpu::TextureViewDimension::D2),
+            mip_level_count: Some(1),
+            array_layer_count: Some(1),
+            ..Default::default()
+        }),
+    );
+    let bindgroup = render::BindGroupBuilder::new(bindgroup_layout)
+        .entries(vec![render::BindGroupEntry::Texture(source_view)])
+        .build_uncached(ctx);
+
+    let mut encoder = render::EncoderBuilder::new().build_new(ctx);
+    for face in 0..6 {
+        let face_view = target.create_view(&wgpu::TextureViewDescriptor {
+            label: Some("cubemap face"),
+            dimension: Some(wgpu::TextureViewDimension::D2),
+            base_mip_level: 0,
+            mip_level_count: Some(1),
+            base_array_layer: face,
+            array_layer_count: Some(1),
+            ..Default::default()
+        });
+
+        render::RenderPassBuilder::new()
+            .label("equirectangular")
+            .color_attachments(&[Some(
+                render::RenderPassColorAttachment::new(&face_view).clear(wgpu::Color::BLACK),
+            )])
+            .build_run(ctx, &mut encoder, |_, mut render_pass| {
+                render_pass.set_pipeline(&pipeline);
+                render_pass.set_bind_group(0, Some(bindgroup.as_ref()), &[]);
+                // the instance index selects the face in the shader
+                render_pass.draw(0..3, face..face + 1);
+            });
+    }
+    encoder.submit(ctx);
+
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::render;
+
+    #[test]
+    fn test_equirectangular_shader() {
+        let reflection = render::ShaderReflection::from_wgsl(include_str!("equirectangular.wgsl"))
+            .expect("equirectangular shader is invalid");
+        assert!(reflection.entry_point("vs_main").is_some());
+        assert!(reflection.entry_point("fs_main").is_some());
+        assert!(reflection.bind_group_layout(0).is_ok());
+    }
+}
@@ -0,0 +1,59 @@
+@group(0) @binding(0) var source: texture_2d<f32>;
+
+const PI = 3.14159265359;
+
+struct VertexOutput {
+    @builtin(position) position: vec4f,
+    // -1 to 1 across the face, y down like the framebuffer
+    @location(0) uv: vec2f,
+    @location(1) @interpolate(flat) face: u32,
+};
+
+// fullscreen triangle, the instance is the cube face
+@vertex
+fn vs_main(
+    @builtin(vertex_index) index: u32,
+    @builtin(instance_index) face: u32,
+) -> VertexOutput {
+    let uv = vec2f(f32((index << 1u) & 2u), f32(index & 2u));
+    let ndc = uv * 2.0 - 1.0;
+    return VertexOutput(vec4f(ndc, 0.0, 1.0), vec2f(ndc.x, -ndc.y), face);
+}
+
+// direction through a texel of a face in +X, -X, +Y, -Y, +Z, -Z order
+fn face_direction(face: u32, uv: vec2f) -> vec3f {
+    switch face {
+        case 0u: { return vec3f(1.0, -uv.y, -uv.x); }
+        case 1u: { return vec3f(-1.0, -uv.y, uv.x); }
+        case 2u: { return vec3f(uv.x, 1.0, uv.y); }
+        case 3u: { return vec3f(uv.x, -1.0, -uv.y); }
+        case 4u: { return vec3f(uv.x, -uv.y, 1.0); }
+        default: { return vec3f(-uv.x, -uv.y, -1.0); }
+    }
+}
+
+// bilinear filter with loads, float32 textures are not always filterable
+fn sample_bilinear(position: vec2f) -> vec4f {
+    let size = vec2i(textureDimensions(source));
+    let texel = position * vec2f(size) - 0.5;
+    let base = vec2i(floor(texel));
+    let weight = fract(texel);
+
+    // longitude wraps around, latitude clamps at the poles
+    let x0 = (base.x % size.x + size.x) % size.x;
+    let x1 = (x0 + 1) % size.x;
+    let y0 = clamp(base.y, 0, size.y - 1);
+    let y1 = clamp(base.y + 1, 0, size.y - 1);
+
+    let top = mix(textureLoad(source, vec2i(x0, y0), 0), textureLoad(source, vec2i(x1, y0), 0), weight.x);
+    let bottom = mix(textureLoad(source, vec2i(x0, y1), 0), textureLoad(source, vec2i(x1, y1), 0), weight.x);
+    return mix(top, bottom, weight.y);
+}
+
+@fragment
+fn fs_main(in: VertexOutput) -> @location(0) vec4f {
+    let direction = normalize(face_direction(in.face, in.uv));
+    let longitude = atan2(direction.z, direction.x);
+    let latitude = asin(clamp(direction.y, -1.0, 1.0));
+    return sample_bilinear(vec2f(longitude / (2.0 * PI) + 0.5, 0.5 - latitude / PI));
+}
@@ -11,6 +11,7 @@ pub struct FrameBufferBuilder {
     format: wgpu::TextureFormat,
     size: wgpu::Extent3d,
     mip_level_count: u32,
+    sample_count: u32,
 }
 
 impl FrameBufferBuilder {
@@ -27,35 +28,55 @@ impl FrameBufferBuilder {
                 depth_or_array_layers: 0,
             },
             mip_level_count: 1,
+            sample_count: 1,
         }
     }
     pub fn build(self, ctx: &mut Context) -> FrameBuffer {
-        let device = render::device(ctx);
-        let texture = device.create_texture(&wgpu::TextureDescriptor {
-            label: self.label.as_deref(),
-            size: self.size,
-            format: self.format,
-            usage: self.usage,
-            mip_level_count: self.mip_level_count,
-            sample_count: 1,
-            dimension: wgpu::TextureDimension::D2,
-            view_formats: &[],
-        });
-        let view = texture.create_view(&wgpu::TextureViewDescriptor {
-            label: self.label.as_deref(),
-            aspect: wgpu::TextureAspect::All,
-            format: None,
-            dimension: None,
-            mip_level_count: None,
-            array_layer_count: None,
-            base_mip_level: 0,
-            base_array_layer: 0,
-            usage: None,
+        let multisampled = self.sample_count > 1;
+        // multisampled textures only have one mip level and can't be copied
+        let (usage, mip_level_count) = match multisampled {
+            true => (
+                self.usage
+                    & (wgpu::TextureUsages::RENDER_ATTACHMENT
+                        | wgpu::TextureUsages::TEXTURE_BINDING),
+                1,
+            ),
+            false => (self.usage, self.mip_level_count),
+        };
+        let (texture, view) = create_target(
+            ctx,
+            &wgpu::TextureDescriptor {
+                label: self.label.as_deref(),
+                size: self.size,
+                format: self.format,
+                usage,
+                mip_level_count,
+                sample_count: self.sample_count,
+                dimension: wgpu::TextureDimension::D2,
+                view_formats: &[],
+            },
+        );
+        // color is resolved into a single sampled texture, depth can't be resolved
+        let resolve = (multisampled && !self.format.is_depth_stencil_format()).then(|| {
+            create_target(
+                ctx,
+                &wgpu::TextureDescriptor {
+                    label: self.label.as_deref(),
+                    size: self.size,
+                    format: self.format,
+                    usage: self.usage,
+                    mip_level_count: self.mip_level_count,
+                    sample_count: 1,
+                    dimension: wgpu::TextureDimension::D2,
+                    view_formats: &[],
+                },
+            )
         });
         FrameBuffer {
             label: self.label,
-            texture: render::ArcTexture::new(ctx, texture),
-            view: render::ArcTextureView::new(ctx, view),
+            texture,
+            view,
+            resolve,
         }
     }
 
@@ -75,6 +96,12 @@ impl FrameBufferBuilder {
         self.mip_level_count = mip_level_count;
         self
     }
+    /// Multisample with `sample_count` samples per pixel, color is resolved automatically
+    /// by [`FrameBuffer::attachment`] into [`FrameBuffer::resolved_view`]
+    pub fn sample_count(mut self, sample_count: u32) -> Self {
+        self.sample_count = sample_count;
+        self
+    }
     pub fn size(mut self, width: u32, height: u32) -> Self {
         self.size = wgpu::Extent3d {
             width,
@@ -89,12 +116,36 @@ impl FrameBufferBuilder {
     }
 }
 
+fn create_target(
+    ctx: &mut Context,
+    desc: &wgpu::TextureDescriptor<'_>,
+) -> (render::ArcTexture, render::ArcTextureView) {
+    let texture = render::device(ctx).create_texture(desc);
+    let view = texture.create_view(&wgpu::TextureViewDescriptor {
+        label: desc.label,
+        aspect: wgpu::TextureAspect::All,
+        format: None,
+        dimension: None,
+        mip_level_count: None,
+        array_layer_count: None,
+        base_mip_level: 0,
+        base_array_layer: 0,
+        usage: None,
+    });
+    (
+        ArcHandle::new(ctx, texture),
+        render::ArcTextureView::new(ctx, view),
+    )
+}
+
 // TODO: sampler also?
 #[derive(Debug)]
 pub struct FrameBuffer {
     label: Option<String>,
     texture: render::ArcTexture,
     view: render::ArcTextureView,
+    /// Single sampled texture multisampled color is resolved into
+    resolve: Option<(render::ArcTexture, render::ArcTextureView)>,
 }
 
 impl FrameBuffer {
@@ -110,6 +161,23 @@ impl FrameBuffer {
     pub fn view_ref(&self) -> &wgpu::TextureView {
         &self.view
     }
+    /// Texture to sample after rendering, the resolve target if multisampled
+    pub fn resolved_texture(&self) -> render::ArcTexture {
+        match &self.resolve {
+            Some((texture, _)) => texture.clone(),
+            None => self.texture(),
+        }
+    }
+    /// View to sample after rendering, the resolve target if multisampled
+    pub fn resolved_view(&self) -> render::ArcTextureView {
+        match &self.resolve {
+            Some((_, view)) => view.clone(),
+            None => self.view(),
+        }
+    }
+    pub fn sample_count(&self) -> u32 {
+        self.texture.sample_count()
+    }
     pub fn target_blend(&self, blend: wgpu::BlendState) -> render::ColorTargetState {
         render::ColorTargetState::new()
             .format(self.format())
@@ -118,52 +186,53 @@ impl FrameBuffer {
     pub fn target(&self) -> render::ColorTargetState {
         render::ColorTargetState::new().format(self.format())
     }
+    /// Resolves into [`resolved_view`](Self::resolved_view) if multisampled
     pub fn attachment(&self) -> wgpu::RenderPassColorAttachment<'_> {
-        wgpu::RenderPassColorAttachment {
-            view: self.view_ref(),
-            resolve_target: None,
-            ops: wgpu::Operations {
-                load: wgpu::LoadOp::Load,
-                store: wgpu::StoreOp::Store,
-            },
+        self.color_attachment().into()
+    }
+    /// Resolves into [`resolved_view`](Self::resolved_view) if multisampled
+    pub fn color_attachment(&self) -> render::RenderPassColorAttachment<'_> {
+        let attachment = render::RenderPassColorAttachment::new(self.view_ref());
+        match &self.resolve {
+            Some((_, view)) => attachment.resolve_target(view),
+            None => attachment,
         }
     }
     pub fn resize(&mut self, ctx: &mut Context, new_size: winit::dpi::PhysicalSize<u32>) {
         if self.size() == new_size {
             return;
         }
-        self.texture.destroy(); // TODO: needed?
 
-        let device = render::device(ctx);
-        let texture = device.create_texture(&wgpu::TextureDescriptor {
-            label: self.label.as_deref(), // TODO:
-            size: wgpu::Extent3d {
-                width: new_size.width,
-                height: new_size.height,
-                depth_or_array_layers: self.texture().depth_or_array_layers(),
-            },
-            format: self.texture().format(),
-            usage: self.texture().usage(),
-            mip_level_count: self.texture().mip_level_count(),
-            sample_count: self.texture().sample_count(),
-            dimension: self.texture().dimension(),
-            view_formats: &[],
-        });
-        let view = texture.create_view(&wgpu::TextureViewDescriptor {
-            label: self.label.as_deref(),
-            aspect: wgpu::TextureAspect::All,
-            format: None,
-            dimension: None,
-            mip_level_count: None,
-            array_layer_count: None,
-            base_mip_level: 0,
-            base_array_layer: 0,
-            usage: None,
-        });
+        let resize = |ctx: &mut Context, texture: &wgpu::Texture| {
+            texture.destroy(); // TODO: needed?
+            create_target(
+                ctx,
+                &wgpu::TextureDescriptor {
+                    label: self.label.as_deref(), // TODO:
+                    size: wgpu::Extent3d {
+                        width: new_size.width,
+                        height: new_size.height,
+                        depth_or_array_layers: texture.depth_or_array_layers(),
+                    },
+                    format: texture.format(),
+                    usage: texture.usage(),
+                    mip_level_count: texture.mip_level_count(),
+                    sample_count: texture.sample_count(),
+                    dimension: texture.dimension(),
+                    view_formats: &[],
+                },
+            )
+        };
+        let (texture, view) = resize(ctx, &self.texture);
+        let resolve = self
+            .resolve
+            .as_ref()
+            .map(|(texture, _)| resize(ctx, texture));
         *self = FrameBuffer {
             label: self.label.clone(),
-            texture: ArcHandle::new(ctx, texture),
-            view: ArcHandle::new(ctx, view),
+            texture,
+            view,
+            resolve,
         }
     }
     pub fn format(&self) -> wgpu::TextureFormat {
@@ -182,9 +251,7 @@ impl FrameBuffer {
     pub fn clear(&self, ctx: &mut Context, color: wgpu::Color) {
         let mut encoder = render::EncoderBuilder::new().build(ctx);
         render::RenderPassBuilder::new()
-            .color_attachments(&[Some(
-                render::RenderPassColorAttachment::new(self.view_ref()).clear(color),
-            )])
+            .color_attachments(&[Some(self.color_attachment().clear(color))])
             .build(ctx, &mut encoder);
         render::queue(ctx).submit(Some(encoder.finish()));
     }
@@ -251,6 +318,11 @@ impl DepthBufferBuilder {
         self.framebuffer_builder.format = format;
         self
     }
+    /// Must match the color attachments it is used with, see [`render::msaa_samples`]
+    pub fn sample_count(mut self, sample_count: u32) -> Self {
+        self.framebuffer_builder.sample_count = sample_count;
+        self
+    }
 }
 
 pub struct DepthBuffer {
@@ -292,6 +364,9 @@ impl DepthBuffer {
     pub fn framebuffer(&self) -> &FrameBuffer {
         &self.framebuffer
     }
+    pub fn sample_count(&self) -> u32 {
+        self.framebuffer.sample_count()
+    }
     pub fn resize(&mut self, ctx: &mut Context, new_size: winit::dpi::PhysicalSize<u32>) {
         self.framebuffer.resize(ctx, new_size);
     }
@@ -0,0 +1,711 @@
+use crate::{
+    render::{self, ArcBuffer, FrameBuffer, FrameBufferBuilder},
+    Context,
+};
+use std::{cmp::Reverse, collections::BinaryHeap};
+
+//
+// Resources
+//
+
+/// Texture declared in a render graph, valid for one frame
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub struct GraphTexture(usize);
+
+/// Buffer declared in a render graph, valid for one frame
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub struct GraphBuffer(usize);
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum GraphResource {
+    Texture(GraphTexture),
+    Buffer(GraphBuffer),
+}
+
+impl From<GraphTexture> for GraphResource {
+    fn from(value: GraphTexture) -> Self {
+        GraphResource::Texture(value)
+    }
+}
+
+impl From<GraphBuffer> for GraphResource {
+    fn from(value: GraphBuffer) -> Self {
+        GraphResource::Buffer(value)
+    }
+}
+
+/// Size of a transient texture, surface relative sizes follow window resizes
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum GraphSize {
+    Surface,
+    /// Surface size divided by a factor, e.g. 2 for half resolution
+    SurfaceDivided(u32),
+    Fixed(u32, u32),
+}
+
+impl GraphSize {
+    fn resolve(self, surface: (u32, u32)) -> (u32, u32) {
+        let (width, height) = match self {
+            GraphSize::Surface => surface,
+            GraphSize::SurfaceDivided(divisor) => {
+                (surface.0 / divisor.max(1), surface.1 / divisor.max(1))
+            }
+            GraphSize::Fixed(width, height) => (width, height),
+        };
+        (width.max(1), height.max(1))
+    }
+}
+
+/// Transient texture allocated by the render graph
+///
+/// Textures with equal descriptions and non overlapping lifetimes share memory,
+/// so contents are undefined until first written each frame
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub struct GraphTextureDesc {
+    label: &'static str,
+    format: wgpu::TextureFormat,
+    size: GraphSize,
+    usage: wgpu::TextureUsages,
+    mip_level_count: u32,
+}
+
+impl GraphTextureDesc {
+    pub fn new(label: &'static str, format: wgpu::TextureFormat) -> Self {
+        Self {
+            label,
+            format,
+            size: GraphSize::Surface,
+            usage: wgpu::TextureUsages::RENDER_ATTACHMENT | wgpu::TextureUsages::TEXTURE_BINDING,
+            mip_level_count: 1,
+        }
+    }
+    pub fn size(mut self, value: GraphSize) -> Self {
+        self.size = value;
+        self
+    }
+    pub fn usage(mut self, value: wgpu::TextureUsages) -> Self {
+        self.usage = value;
+        self
+    }
+    pub fn mip_level_count(mut self, value: u32) -> Self {
+        self.mip_level_count = value;
+        self
+    }
+}
+
+/// Transient buffer allocated by the render graph, aliased like textures
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub struct GraphBufferDesc {
+    label: &'static str,
+    size: u64,
+    usage: wgpu::BufferUsages,
+}
+
+impl GraphBufferDesc {
+    pub fn new(label: &'static str, size: u64) -> Self {
+        Self {
+            label,
+            size,
+            usage: wgpu::BufferUsages::STORAGE | wgpu::BufferUsages::COPY_DST,
+        }
+    }
+    pub fn usage(mut self, value: wgpu::BufferUsages) -> Self {
+        self.usage = value;
+        self
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+struct TextureKey {
+    format: wgpu::TextureFormat,
+    width: u32,
+    height: u32,
+    usage: wgpu::TextureUsages,
+    mip_level_count: u32,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+struct BufferKey {
+    size: u64,
+    usage: wgpu::BufferUsages,
+}
+
+enum TextureSlot<'a> {
+    Transient(GraphTextureDesc),
+    Imported(&'a wgpu::TextureView),
+}
+
+enum BufferSlot<'a> {
+    Transient(GraphBufferDesc),
+    Imported(&'a wgpu::Buffer),
+}
+
+//
+// Graph
+//
+
+type GraphPassFn<'a> = Box<dyn FnOnce(&mut Context, &mut GraphPassContext<'_>) + 'a>;
+
+struct GraphPass<'a> {
+    label: &'static str,
+    reads: Vec<GraphResource>,
+    writes: Vec<GraphResource>,
+    run: GraphPassFn<'a>,
+}
+
+struct PooledTexture {
+    key: TextureKey,
+    framebuffer: FrameBuffer,
+}
+
+struct PooledBuffer {
+    key: BufferKey,
+    buffer: ArcBuffer,
+}
+
+/// Keeps transient resources alive between frames, record a frame with [`RenderGraph::begin`]
+///
+/// # Examples
+/// ```ignore
+/// let mut graph = self.graph.begin();
+/// let screen = graph.import_texture(screen_view);
+/// let hdr = graph.create_texture(render::GraphTextureDesc::new("hdr", wgpu::TextureFormat::Rgba16Float));
+/// graph.add_pass("scene").write(hdr).run(|ctx, pass| { /* draw into pass.view(hdr) */ });
+/// graph.add_pass("tonemap").read(hdr).write(screen).run(|ctx, pass| { /* ... */ });
+/// graph.execute(ctx);
+/// ```
+#[derive(Default)]
+pub struct RenderGraph {
+    textures: Vec<PooledTexture>,
+    buffers: Vec<PooledBuffer>,
+}
+
+impl RenderGraph {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Start recording passes for this frame
+    pub fn begin<'a>(&'a mut self) -> RenderGraphBuilder<'a> {
+        RenderGraphBuilder {
+            graph: self,
+            textures: Vec::new(),
+            buffers: Vec::new(),
+            passes: Vec::new(),
+        }
+    }
+}
+
+impl std::fmt::Debug for RenderGraph {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("RenderGraph")
+            .field("textures", &self.textures.len())
+            .field("buffers", &self.buffers.len())
+            .finish()
+    }
+}
+
+pub struct RenderGraphBuilder<'a> {
+    graph: &'a mut RenderGraph,
+    textures: Vec<TextureSlot<'a>>,
+    buffers: Vec<BufferSlot<'a>>,
+    passes: Vec<GraphPass<'a>>,
+}
+
+impl<'a> RenderGraphBuilder<'a> {
+    pub fn create_texture(&mut self, desc: GraphTextureDesc) -> GraphTexture {
+        self.textures.push(TextureSlot::Transient(desc));
+        GraphTexture(self.textures.len() - 1)
+    }
+
+    /// Use a texture owned outside the graph, e.g. the screen view
+    pub fn import_texture(&mut self, view: &'a wgpu::TextureView) -> GraphTexture {
+        self.textures.push(TextureSlot::Imported(view));
+        GraphTexture(self.textures.len() - 1)
+    }
+
+    pub fn create_buffer(&mut self, desc: GraphBufferDesc) -> GraphBuffer {
+        self.buffers.push(BufferSlot::Transient(desc));
+        GraphBuffer(self.buffers.len() - 1)
+    }
+
+    /// Use a buffer owned outside the graph, e.g. camera uniforms
+    pub fn import_buffer(&mut self, buffer: &'a wgpu::Buffer) -> GraphBuffer {
+        self.buffers.push(BufferSlot::Imported(buffer));
+        GraphBuffer(self.buffers.len() - 1)
+    }
+
+    /// Add a pass, declare the resources it uses before setting its function with [`GraphPassBuilder::run`]
+    pub fn add_pass<'b>(&'b mut self, label: &'static str) -> GraphPassBuilder<'b, 'a> {
+        GraphPassBuilder {
+            builder: self,
+            label,
+            reads: Vec::new(),
+            writes: Vec::new(),
+        }
+    }
+
+    /// Order passes, allocate transient resources and run all passes in a single submission
+    ///
+    /// Panics if passes depend on each other in a cycle
+    pub fn execute(self, ctx: &mut Context) {
+        let RenderGraphBuilder {
+            graph,
+            textures,
+            buffers,
+            passes,
+        } = self;
+
+        let accesses = passes
+            .iter()
+            .map(|pass| (pass.reads.as_slice(), pass.writes.as_slice()))
+            .collect::<Vec<_>>();
+        let order = schedule(&accesses).unwrap_or_else(|cycle| {
+            let labels = cycle.iter().map(|&i| passes[i].label).collect::<Vec<_>>();
+            panic!("render graph has a dependency cycle between passes {labels:?}")
+        });
+
+        //
+        // allocate transient resources
+        //
+
+        let surface_config = render::surface_config(ctx);
+        let surface = (surface_config.width, surface_config.height);
+
+        let texture_keys = textures
+            .iter()
+            .map(|slot| match slot {
+                TextureSlot::Transient(desc) => {
+                    let (width, height) = desc.size.resolve(surface);
+                    Some(TextureKey {
+                        format: desc.format,
+                        width,
+                        height,
+                        usage: desc.usage,
+                        mip_level_count: desc.mip_level_count,
+                    })
+                }
+                TextureSlot::Imported(_) => None,
+            })
+            .collect::<Vec<_>>();
+        let buffer_keys = buffers
+            .iter()
+            .map(|slot| match slot {
+                BufferSlot::Transient(desc) => Some(BufferKey {
+                    size: desc.size,
+                    usage: desc.usage,
+                }),
+                BufferSlot::Imported(_) => None,
+            })
+            .collect::<Vec<_>>();
+
+        let texture_lifetimes =
+            lifetimes(
+                &order,
+                &accesses,
+                textures.len(),
+                |resource| match resource {
+                    GraphResource::Texture(texture) => Some(texture.0),
+                    GraphResource::Buffer(_) => None,
+                },
+            );
+        let buffer_lifetimes = lifetimes(
+            &order,
+            &accesses,
+            buffers.len(),
+            |resource| match resource {
+                GraphResource::Buffer(buffer) => Some(buffer.0),
+                GraphResource::Texture(_) => None,
+            },
+        );
+        let (texture_aliases, texture_slots) = alias(&texture_keys, &texture_lifetimes);
+        let (buffer_aliases, buffer_slots) = alias(&buffer_keys, &buffer_lifetimes);
+
+        // reuse pooled resources with the same key, resources for old sizes are dropped
+        let mut pooled_textures = std::mem::take(&mut graph.textures);
+        for (slot, key) in texture_slots.iter().enumerate() {
+            let framebuffer = match pooled_textures.iter().position(|pooled| pooled.key == *key) {
+                Some(index) => pooled_textures.swap_remove(index).framebuffer,
+                None => {
+                    let label = transient_label(&textures, &texture_aliases, slot);
+                    FrameBufferBuilder::new()
+                        .label(label)
+                        .format(key.format)
+                        .usage(key.usage)
+                        .mip_level_count(key.mip_level_count)
+                        .size(key.width, key.height)
+                        .build(ctx)
+                }
+            };
+            graph.textures.push(PooledTexture {
+                key: *key,
+                framebuffer,
+            });
+        }
+
+        let mut pooled_buffers = std::mem::take(&mut graph.buffers);
+        for (slot, key) in buffer_slots.iter().enumerate() {
+            let buffer = match pooled_buffers.iter().position(|pooled| pooled.key == *key) {
+                Some(index) => pooled_buffers.swap_remove(index).buffer,
+                None => {
+                    let label = transient_buffer_label(&buffers, &buffer_aliases, slot);
+                    let buffer = render::device(ctx).create_buffer(&wgpu::BufferDescriptor {
+                        label: Some(label),
+                        size: key.size,
+                        usage: key.usage,
+                        mapped_at_creation: false,
+                    });
+                    ArcBuffer::new(ctx, buffer)
+                }
+            };
+            graph.buffers.push(PooledBuffer { key: *key, buffer });
+        }
+
+        let texture_views = textures
+            .iter()
+            .zip(&texture_aliases)
+            .map(|(slot, alias)| match (slot, alias) {
+                (TextureSlot::Imported(view), _) => Some(GraphTextureView::View(view)),
+                (TextureSlot::Transient(_), Some(alias)) => Some(GraphTextureView::FrameBuffer(
+                    &graph.textures[*alias].framebuffer,
+                )),
+                (TextureSlot::Transient(_), None) => None,
+            })
+            .collect::<Vec<_>>();
+        let buffer_refs = buffers
+            .iter()
+            .zip(&buffer_aliases)
+            .map(|(slot, alias)| match (slot, alias) {
+                (BufferSlot::Imported(buffer), _) => Some(*buffer),
+                (BufferSlot::Transient(_), Some(alias)) => Some(&*graph.buffers[*alias].buffer),
+                (BufferSlot::Transient(_), None) => None,
+            })
+            .collect::<Vec<_>>();
+
+        //
+        // record
+        //
+
+        let mut encoder = render::EncoderBuilder::new().build(ctx);
+        let mut passes = passes.into_iter().map(Some).collect::<Vec<_>>();
+        for index in order {
+            let pass = passes[index].take().expect("pass scheduled twice");
+
+            encoder.push_debug_group(pass.label);
+            let scope = ctx
+                .profile
+                .gpu_profiler
+                .begin_encoder_scope(&mut encoder, pass.label);
+
+            let mut pass_ctx = GraphPassContext {
+                encoder: &mut encoder,
+                label: pass.label,
+                reads: &pass.reads,
+                writes: &pass.writes,
+                textures: &texture_views,
+                buffers: &buffer_refs,
+            };
+            (pass.run)(ctx, &mut pass_ctx);
+
+            ctx.profile
+                .gpu_profiler
+                .end_encoder_scope(&mut encoder, scope);
+            encoder.pop_debug_group();
+        }
+        render::queue(ctx).submit(Some(encoder.finish()));
+    }
+}
+
+fn transient_label(
+    textures: &[TextureSlot<'_>],
+    aliases: &[Option<usize>],
+    slot: usize,
+) -> &'static str {
+    aliases
+        .iter()
+        .position(|alias| *alias == Some(slot))
+        .and_then(|index| match &textures[index] {
+            TextureSlot::Transient(desc) => Some(desc.label),
+            TextureSlot::Imported(_) => None,
+        })
+        .unwrap_or("render graph texture")
+}
+
+fn transient_buffer_label(
+    buffers: &[BufferSlot<'_>],
+    aliases: &[Option<usize>],
+    slot: usize,
+) -> &'static str {
+    aliases
+        .iter()
+        .position(|alias| *alias == Some(slot))
+        .and_then(|index| match &buffers[index] {
+            BufferSlot::Transient(desc) => Some(desc.label),
+            BufferSlot::Imported(_) => None,
+        })
+        .unwrap_or("render graph buffer")
+}
+
+pub struct GraphPassBuilder<'b, 'a> {
+    builder: &'b mut RenderGraphBuilder<'a>,
+    label: &'static str,
+    reads: Vec<GraphResource>,
+    writes: Vec<GraphResource>,
+}
+
+impl<'a> GraphPassBuilder<'_, 'a> {
+    /// Run after all passes writing the resource
+    pub fn read(mut self, resource: impl Into<GraphResource>) -> Self {
+        self.reads.push(resource.into());
+        self
+    }
+
+    /// Run before all passes only reading the resource, passes writing the same resource keep their order
+    pub fn write(mut self, resource: impl Into<GraphResource>) -> Self {
+        self.writes.push(resource.into());
+        self
+    }
+
+    pub fn run(self, run: impl FnOnce(&mut Context, &mut GraphPassContext<'_>) + 'a) {
+        self.builder.passes.push(GraphPass {
+            label: self.label,
+            reads: self.reads,
+            writes: self.writes,
+            run: Box::new(run),
+        });
+    }
+}
+
+#[derive(Clone, Copy)]
+enum GraphTextureView<'p> {
+    FrameBuffer(&'p FrameBuffer),
+    View(&'p wgpu::TextureView),
+}
+
+/// Resources and encoder available to a running pass
+///
+/// Only resources declared with [`GraphPassBuilder::read`] or [`GraphPassBuilder::write`] can be accessed
+pub struct GraphPassContext<'p> {
+    encoder: &'p mut wgpu::CommandEncoder,
+    label: &'static str,
+    reads: &'p [GraphResource],
+    writes: &'p [GraphResource],
+    textures: &'p [Option<GraphTextureView<'p>>],
+    buffers: &'p [Option<&'p wgpu::Buffer>],
+}
+
+impl<'p> GraphPassContext<'p> {
+    pub fn label(&self) -> &'static str {
+        self.label
+    }
+
+    /// Shared encoder, submitted once all passes ran
+    pub fn encoder(&mut self) -> &mut wgpu::CommandEncoder {
+        self.encoder
+    }
+
+    fn check_declared(&self, resource: GraphResource) {
+        if !self.reads.contains(&resource) && !self.writes.contains(&resource) {
+            panic!(
+                "render graph pass {} did not declare {:?}",
+                self.label, resource
+            );
+        }
+    }
+
+    fn texture_view(&self, texture: GraphTexture) -> GraphTextureView<'p> {
+        self.check_declared(texture.into());
+        self.textures[texture.0].expect("graph texture was not allocated")
+    }
+
+    pub fn view(&self, texture: GraphTexture) -> &'p wgpu::TextureView {
+        match self.texture_view(texture) {
+            GraphTextureView::FrameBuffer(framebuffer) => framebuffer.view_ref(),
+            GraphTextureView::View(view) => view,
+        }
+    }
+
+    /// Framebuffer of a transient texture, `None` for imported textures
+    pub fn framebuffer(&self, texture: GraphTexture) -> Option<&'p FrameBuffer> {
+        match self.texture_view(texture) {
+            GraphTextureView::FrameBuffer(framebuffer) => Some(framebuffer),
+            GraphTextureView::View(_) => None,
+        }
+    }
+
+    pub fn buffer(&self, buffer: GraphBuffer) -> &'p wgpu::Buffer {
+        self.check_declared(buffer.into());
+        self.buffers[buffer.0].expect("graph buffer was not allocated")
+    }
+}
+
+//
+// Compilation
+//
+
+/// Execution order, writers of a resource run in declaration order before its readers
+///
+/// Ties are broken by declaration order. Returns the passes left in a cycle on failure
+fn schedule(accesses: &[(&[GraphResource], &[GraphResource])]) -> Result<Vec<usize>, Vec<usize>> {
+    let mut dependents = vec![Vec::new(); accesses.len()];
+    let mut dependency_count = vec![0usize; accesses.len()];
+    let mut add_edge = |from: usize, to: usize| {
+        if from != to && !dependents[from].contains(&to) {
+            dependents[from].push(to);
+            dependency_count[to] += 1;
+        }
+    };
+
+    let resources = accesses
+        .iter()
+        .flat_map(|(reads, writes)| reads.iter().chain(writes.iter()))
+        .collect::<rustc_hash::FxHashSet<_>>();
+    for resource in resources {
+        let writers = (0..accesses.len())
+            .filter(|&i| accesses[i].1.contains(resource))
+            .collect::<Vec<_>>();
+        let readers = (0..accesses.len())
+            .filter(|&i| accesses[i].0.contains(resource) && !writers.contains(&i))
+            .collect::<Vec<_>>();
+
+        for pair in writers.windows(2) {
+            add_edge(pair[0], pair[1]);
+        }
+        if let Some(&last_writer) = writers.last() {
+            for &reader in &readers {
+                add_edge(last_writer, reader);
+            }
+        }
+    }
+
+    let mut ready = (0..accesses.len())
+        .filter(|&i| dependency_count[i] == 0)
+        .map(Reverse)
+        .collect::<BinaryHeap<_>>();
+    let mut order = Vec::with_capacity(accesses.len());
+    while let Some(Reverse(pass)) = ready.pop() {
+        order.push(pass);
+        for &dependent in &dependents[pass] {
+            dependency_count[dependent] -= 1;
+            if dependency_count[dependent] == 0 {
+                ready.push(Reverse(dependent));
+            }
+        }
+    }
+
+    if order.len() != accesses.len() {
+        let cycle = (0..accesses.len())
+            .filter(|&i| dependency_count[i] > 0)
+            .collect();
+        return Err(cycle);
+    }
+    Ok(order)
+}
+
+/// First and last position in the execution order each resource is used at
+fn lifetimes(
+    order: &[usize],
+    accesses: &[(&[GraphResource], &[GraphResource])],
+    count: usize,
+    index: impl Fn(&GraphResource) -> Option<usize>,
+) -> Vec<Option<(usize, usize)>> {
+    let mut lifetimes = vec![None; count];
+    for (position, &pass) in order.iter().enumerate() {
+        let (reads, writes) = accesses[pass];
+        for resource in reads.iter().chain(writes.iter()) {
+            if let Some(index) = index(resource) {
+                let lifetime: &mut Option<(usize, usize)> = &mut lifetimes[index];
+                *lifetime = Some(match *lifetime {
+                    Some((first, _)) => (first, position),
+                    None => (position, position),
+                });
+            }
+        }
+    }
+    lifetimes
+}
+
+/// Assign transient resources to physical slots, sharing slots between equal keys with disjoint lifetimes
+///
+/// Unused and imported resources get no slot
+fn alias<K: Clone + PartialEq>(
+    keys: &[Option<K>],
+    lifetimes: &[Option<(usize, usize)>],
+) -> (Vec<Option<usize>>, Vec<K>) {
+    let mut resources = (0..keys.len())
+        .filter_map(|i| Some((i, keys[i].as_ref()?, lifetimes[i]?)))
+        .collect::<Vec<_>>();
+    resources.sort_by_key(|(_, _, (first, _))| *first);
+
+    let mut assignment = vec![None; keys.len()];
+    let mut slots = Vec::<(K, usize)>::new();
+    for (resource, key, (first, last)) in resources {
+        let free = slots
+            .iter()
+            .position(|(slot_key, free_after)| slot_key == key && *free_after < first);
+        let slot = match free {
+            Some(slot) => {
+                slots[slot].1 = last;
+                slot
+            }
+            None => {
+                slots.push((key.clone(), last));
+                slots.len() - 1
+            }
+        };
+        assignment[resource] = Some(slot);
+    }
+
+    (assignment, slots.into_iter().map(|(key, _)| key).collect())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{alias, lifetimes, schedule, GraphBuffer, GraphResource, GraphTexture};
+
+    #[test]
+    fn test_schedule_order() {
+        let (shadow, hdr, screen) = (
+            GraphResource::Texture(GraphTexture(0)),
+            GraphResource::Texture(GraphTexture(1)),
+            GraphResource::Texture(GraphTexture(2)),
+        );
+        let lights = GraphResource::Buffer(GraphBuffer(0));
+        // declared out of order: tonemap, scene, shadows, ui
+        let accesses: [(&[GraphResource], &[GraphResource]); 4] = [
+            (&[hdr], &[screen]),
+            (&[shadow, lights], &[hdr]),
+            (&[], &[shadow, lights]),
+            (&[], &[screen]),
+        ];
+        assert_eq!(schedule(&accesses), Ok(vec![2, 1, 0, 3]));
+
+        let cycle: [(&[GraphResource], &[GraphResource]); 2] =
+            [(&[hdr], &[shadow]), (&[shadow], &[hdr])];
+        assert_eq!(schedule(&cycle), Err(vec![0, 1]));
+    }
+
+    #[test]
+    fn test_alias_disjoint_lifetimes() {
+        let texture = |i| GraphResource::Texture(GraphTexture(i));
+        // 0 -> 1 -> 2 chain, 0 is dead once 1 is written
+        let accesses: [(&[GraphResource], &[GraphResource]); 3] = [
+            (&[], &[texture(0)]),
+            (&[texture(0)], &[texture(1)]),
+            (&[texture(1)], &[texture(2)]),
+        ];
+        let order = schedule(&accesses).unwrap();
+        let lifetimes = lifetimes(&order, &accesses, 4, |resource| match resource {
+            GraphResource::Texture(texture) => Some(texture.0),
+            GraphResource::Buffer(_) => None,
+        });
+        assert_eq!(lifetimes[3], None);
+
+        let (assignment, slots) = alias(&[Some("a"), Some("a"), Some("a"), Some("a")], &lifetimes);
+        assert_eq!(assignment, vec![Some(0), Some(1), Some(0), None]);
+        assert_eq!(slots, vec!["a", "a"]);
+
+        let (assignment, _) = alias(&[Some("a"), Some("b"), Some("b"), None], &lifetimes);
+        assert_eq!(assignment, vec![Some(0), Some(1), Some(2), None]);
+    }
+}
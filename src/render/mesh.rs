@@ -3,6 +3,7 @@ use crate::{
     render::{self, VertexBufferLayout},
     wgpu, Context,
 };
+use serde::{Deserialize, Serialize};
 use std::collections::{BTreeMap, BTreeSet};
 
 //
@@ -206,8 +207,77 @@ impl Mesh {
         todo!()
     }
 
+    /// Per vertex tangents from the positions, normals and first uv set of a triangle list
+    ///
+    /// `w` holds the handedness of the bitangent, `cross(normal, tangent) * w`
     pub fn generate_tangents(&mut self) {
-        todo!()
+        let (
+            Some(VertexAttributeValues::Float32x3(positions)),
+            Some(VertexAttributeValues::Float32x3(normals)),
+            Some(VertexAttributeValues::Float32x2(uvs)),
+        ) = (
+            self.get_attribute(VertexAttributeId::Position),
+            self.get_attribute(VertexAttributeId::Normal),
+            self.get_attribute(VertexAttributeId::Uv(0)),
+        )
+        else {
+            tracing::error!(
+                "trying to generate tangents for mesh without positions, normals or uvs"
+            );
+            return;
+        };
+        if self.primitive_topology != wgpu::PrimitiveTopology::TriangleList {
+            tracing::error!("trying to generate tangents for mesh which is not a triangle list");
+            return;
+        }
+
+        let count = positions.len();
+        let mut tangents = vec![Vec3::ZERO; count];
+        let mut bitangents = vec![Vec3::ZERO; count];
+        let triangle_count = self.indices.as_ref().map_or(count, |inds| inds.len()) / 3;
+        for triangle in 0..triangle_count {
+            let corners = [0, 1, 2].map(|corner| match &self.indices {
+                Some(inds) => inds[triangle * 3 + corner] as usize,
+                None => triangle * 3 + corner,
+            });
+            let [a, b, c] = corners;
+
+            let edge1 = Vec3::from(positions[b]) - Vec3::from(positions[a]);
+            let edge2 = Vec3::from(positions[c]) - Vec3::from(positions[a]);
+            let (du1, dv1) = (uvs[b][0] - uvs[a][0], uvs[b][1] - uvs[a][1]);
+            let (du2, dv2) = (uvs[c][0] - uvs[a][0], uvs[c][1] - uvs[a][1]);
+            let det = du1 * dv2 - du2 * dv1;
+            if det.abs() <= f32::EPSILON {
+                continue;
+            }
+
+            let tangent = (edge1 * dv2 - edge2 * dv1) / det;
+            let bitangent = (edge2 * du1 - edge1 * du2) / det;
+            for i in corners {
+                tangents[i] += tangent;
+                bitangents[i] += bitangent;
+            }
+        }
+
+        // orthogonalize against the normal, degenerate uvs get any perpendicular tangent
+        let tangents = (0..count)
+            .map(|i| {
+                let normal = Vec3::from(normals[i]);
+                let tangent = (tangents[i] - normal * normal.dot(tangents[i]))
+                    .try_normalize()
+                    .unwrap_or_else(|| normal.any_orthonormal_vector());
+                let handedness = match normal.cross(tangent).dot(bitangents[i]) < 0.0 {
+                    true => -1.0,
+                    false => 1.0,
+                };
+                [tangent.x, tangent.y, tangent.z, handedness]
+            })
+            .collect();
+
+        self.set_attribute(
+            VertexAttributeId::Tangent,
+            VertexAttributeValues::Float32x4(tangents),
+        );
     }
     pub fn generate_colors(&mut self, color_index: u32, color: [f32; 3]) {
         let Some(count) = self.vertex_count() else {
@@ -270,7 +340,7 @@ impl Mesh {
     }
 }
 
-#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash, PartialOrd, Ord)]
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash, PartialOrd, Ord, Serialize, Deserialize)]
 pub enum VertexAttributeId {
     Position,
     Normal,
@@ -424,6 +494,11 @@ impl GpuMesh {
         }
     }
 
+    /// Bytes of GPU memory used by the vertex and index buffers
+    pub fn memory_size(&self) -> u64 {
+        self.attribute_buffer.size() + self.index_buffer.as_ref().map_or(0, |buffer| buffer.size())
+    }
+
     pub fn bind_to_render_pass(&self, render_pass: &mut wgpu::RenderPass<'_>) {
         for (i, (_t, (start, end))) in self.attribute_ranges.iter().enumerate() {
             // tracing::info!("bind {t:?} {i} to {:?}", (start, end));
@@ -824,3 +899,59 @@ impl MeshBuilder {
 //
 //     self
 // }
+
+#[cfg(test)]
+mod tests {
+    use super::{Mesh, VertexAttributeId, VertexAttributeValues};
+
+    #[test]
+    fn test_generate_tangents() {
+        // quad facing +z with u along +x and v along -y
+        let mut mesh = Mesh::new(wgpu::PrimitiveTopology::TriangleList)
+            .with_attribute(
+                VertexAttributeId::Position,
+                VertexAttributeValues::Float32x3(vec![
+                    [0.0, 0.0, 0.0],
+                    [1.0, 0.0, 0.0],
+                    [1.0, 1.0, 0.0],
+                    [0.0, 1.0, 0.0],
+                ]),
+            )
+            .with_attribute(
+                VertexAttributeId::Normal,
+                VertexAttributeValues::Float32x3(vec![[0.0, 0.0, 1.0]; 4]),
+            )
+            .with_attribute(
+                VertexAttributeId::Uv(0),
+                VertexAttributeValues::Float32x2(vec![
+                    [0.0, 1.0],
+                    [1.0, 1.0],
+                    [1.0, 0.0],
+                    [0.0, 0.0],
+                ]),
+            )
+            .with_indices(vec![0, 1, 2, 0, 2, 3]);
+        mesh.generate_tangents();
+
+        let Some(VertexAttributeValues::Float32x4(tangents)) =
+            mesh.get_attribute(VertexAttributeId::Tangent)
+        else {
+            panic!("tangents were not generated");
+        };
+        assert_eq!(tangents, &vec![[1.0, 0.0, 0.0, -1.0]; 4]);
+
+        // mirrored u flips the handedness
+        if let Some(VertexAttributeValues::Float32x2(uvs)) =
+            mesh.attributes.get_mut(&VertexAttributeId::Uv(0))
+        {
+            uvs.iter_mut().for_each(|uv| uv[0] = 1.0 - uv[0]);
+        }
+        mesh.generate_tangents();
+        let Some(VertexAttributeValues::Float32x4(tangents)) =
+            mesh.get_attribute(VertexAttributeId::Tangent)
+        else {
+            panic!("tangents were not generated");
+        };
+        assert_eq!(tangents, &vec![[-1.0, 0.0, 0.0, 1.0]; 4]);
+    }
+}
@@ -0,0 +1,227 @@
+use crate::{render, Context};
+
+#[derive(thiserror::Error, Debug)]
+pub enum GenerateMipmapsError {
+    #[error("can not generate mipmaps for {0:?} textures")]
+    UnsupportedDimension(wgpu::TextureDimension),
+    #[error("can not generate mipmaps for {0:?}, it must be a renderable float format")]
+    UnsupportedFormat(wgpu::TextureFormat),
+    #[error("generating mipmaps requires TEXTURE_BINDING | RENDER_ATTACHMENT usage, got {0:?}")]
+    MissingUsage(wgpu::TextureUsages),
+}
+
+//
+// GPU
+//
+
+/// Fill all mip levels after `base_mip_level` by downsampling the level above
+///
+/// Every array layer is downsampled, sRGB formats are filtered in linear space
+pub fn generate_mipmaps(
+    ctx: &mut Context,
+    texture: &wgpu::Texture,
+    base_mip_level: u32,
+) -> Result<(), GenerateMipmapsError> {
+    if base_mip_level + 1 >= texture.mip_level_count() {
+        return Ok(());
+    }
+    if texture.dimension() != wgpu::TextureDimension::D2 {
+        return Err(GenerateMipmapsError::UnsupportedDimension(
+            texture.dimension(),
+        ));
+    }
+    let format = texture.format();
+    let features = ctx.render.adapter.get_texture_format_features(format);
+    let float = matches!(
+        format.sample_type(None, None),
+        Some(wgpu::TextureSampleType::Float { .. })
+    );
+    if !float
+        || !features
+            .allowed_usages
+            .contains(wgpu::TextureUsages::RENDER_ATTACHMENT)
+    {
+        return Err(GenerateMipmapsError::UnsupportedFormat(format));
+    }
+    let required_usage =
+        wgpu::TextureUsages::TEXTURE_BINDING | wgpu::TextureUsages::RENDER_ATTACHMENT;
+    if !texture.usage().contains(required_usage) {
+        return Err(GenerateMipmapsError::MissingUsage(texture.usage()));
+    }
+    let shader = render::builtin_shader(ctx, "mipmap", include_str!("mipmap.wgsl"));
+    let bindgroup_layout = render::BindGroupLayoutBuilder::new()
+        .label("mipmap")
+        .entries(vec![
+            // source level
+            render::BindGroupLayoutEntry::new()
+                .texture_float_nonfilterable()
+                .fragment(),
+        ])
+        .build(ctx);
+    let pipeline_layout = render::PipelineLayoutBuilder::new()
+        .label("mipmap")
+        .bind_groups(vec![bindgroup_layout.clone()])
+        .build(ctx);
+    let pipeline = render::RenderPipelineBuilder::new(shader, pipeline_layout)
+        .label("mipmap")
+        .single_target(render::ColorTargetState::new().format(format))
+        .build(ctx);
+
+    let level_view = |level: u32, layer: u32| {
+        texture.create_view(&wgpu::TextureViewDescriptor {
+            label: Some("mipmap level"),
+            dimension: Some(wgpu::TextureViewDimension::D2),
+            base_mip_level: level,
+            mip_level_count: Some(1),
+            base_array_layer: layer,
+            array_layer_count: Some(1),
+            ..Default::default()
+        })
+    };
+
+    let mut encoder = render::EncoderBuilder::new().build_new(ctx);
+    for layer in 0..texture.depth_or_array_layers() {
+        for level in base_mip_level + 1..texture.mip_level_count() {
+            let source = render::ArcTextureView::new(ctx, level_view(level - 1, layer));
+            let target = level_view(level, layer);
+            let bindgroup = render::BindGroupBuilder::new(bindgroup_layout.clone())
+                .entries(vec![render::BindGroupEntry::Texture(source)])
+                .build_uncached(ctx);
+
+            render::RenderPassBuilder::new()
+                .label("mipmap")
+                .color_attachments(&[Some(
+                    render::RenderPassColorAttachment::new(&target).clear(wgpu::Color::BLACK),
+                )])
+                .build_run(ctx, &mut encoder, |_, mut render_pass| {
+                    render_pass.set_pipeline(&pipeline);
+                    render_pass.set_bind_group(0, Some(bindgroup.as_ref()), &[]);
+                    render_pass.draw(0..3, 0..1);
+                });
+        }
+    }
+    encoder.submit(ctx);
+
+    Ok(())
+}
+
+//
+// CPU
+//
+
+/// All mip levels of RGBA8 data down to 1x1, largest first and including `data`
+///
+/// Same filter as [`generate_mipmaps`], for cooking mipmaps offline
+pub fn generate_mipmaps_rgba8(width: u32, height: u32, data: &[u8], srgb: bool) -> Vec<u8> {
+    let mip_level_count = u32::BITS - width.max(height).max(1).leading_zeros();
+
+    // filter in linear space and only quantize the output to avoid accumulating rounding
+    let mut level = data
+        .iter()
+        .enumerate()
+        .map(|(i, &value)| match srgb && i % 4 != 3 {
+            true => srgb_to_linear(value),
+            false => value as f32 / 255.0,
+        })
+        .collect::<Vec<_>>();
+    let (mut level_width, mut level_height) = (width, height);
+
+    let mut output = data.to_vec();
+    for _ in 1..mip_level_count {
+        (level, level_width, level_height) = downsample(&level, level_width, level_height);
+        output.extend(
+            level
+                .iter()
+                .enumerate()
+                .map(|(i, &value)| match srgb && i % 4 != 3 {
+                    true => linear_to_srgb(value),
+                    false => (value * 255.0).round() as u8,
+                }),
+        );
+    }
+    output
+}
+
+fn downsample(source: &[f32], width: u32, height: u32) -> (Vec<f32>, u32, u32) {
+    let (target_width, target_height) = ((width / 2).max(1), (height / 2).max(1));
+    let ratio_x = width as f32 / target_width as f32;
+    let ratio_y = height as f32 / target_height as f32;
+
+    let mut target = vec![0.0; (target_width * target_height * 4) as usize];
+    for target_y in 0..target_height {
+        let start_y = target_y as f32 * ratio_y;
+        let end_y = start_y + ratio_y;
+        for target_x in 0..target_width {
+            let start_x = target_x as f32 * ratio_x;
+            let end_x = start_x + ratio_x;
+
+            let texel = ((target_y * target_width + target_x) * 4) as usize;
+            for y in start_y as u32..(end_y.ceil() as u32).min(height) {
+                let weight_y = end_y.min(y as f32 + 1.0) - start_y.max(y as f32);
+                for x in start_x as u32..(end_x.ceil() as u32).min(width) {
+                    let weight_x = end_x.min(x as f32 + 1.0) - start_x.max(x as f32);
+                    let weight = weight_x * weight_y / (ratio_x * ratio_y);
+                    let source_texel = ((y * width + x) * 4) as usize;
+                    for channel in 0..4 {
+                        target[texel + channel] += source[source_texel + channel] * weight;
+                    }
+                }
+            }
+        }
+    }
+    (target, target_width, target_height)
+}
+
+fn srgb_to_linear(value: u8) -> f32 {
+    let value = value as f32 / 255.0;
+    match value <= 0.04045 {
+        true => value / 12.92,
+        false => ((value + 0.055) / 1.055).powf(2.4),
+    }
+}
+
+fn linear_to_srgb(value: f32) -> u8 {
+    let value = match value <= 0.0031308 {
+        true => value * 12.92,
+        false => 1.055 * value.powf(1.0 / 2.4) - 0.055,
+    };
+    (value.clamp(0.0, 1.0) * 255.0).round() as u8
+}
+
+#[cfg(test)]
+mod tests {
+    use super::generate_mipmaps_rgba8;
+    use crate::render;
+
+    #[test]
+    fn test_mipmap_shader() {
+        let reflection = render::ShaderReflection::from_wgsl(include_str!("mipmap.wgsl"))
+            .expect("mipmap shader is invalid");
+        assert!(reflection.entry_point("fs_main").is_some());
+        assert!(reflection.bind_group_layout(0).is_ok());
+    }
+
+    #[test]
+    fn test_generate_mipmaps_rgba8() {
+        // 3x1 -> 1x1, the odd texel is not dropped
+        let data = [
+            [0, 0, 0, 255], //
+            [255, 255, 255, 255],
+            [255, 255, 255, 0],
+        ]
+        .concat();
+        let mips = generate_mipmaps_rgba8(3, 1, &data, false);
+        assert_eq!(mips.len(), data.len() + 4);
+        assert_eq!(&mips[data.len()..], &[170, 170, 170, 170]);
+
+        // sRGB is averaged in linear space, alpha is always linear
+        let data = [[0, 0, 0, 0], [255, 255, 255, 255]].concat();
+        let mips = generate_mipmaps_rgba8(2, 1, &data, true);
+        assert_eq!(&mips[data.len()..], &[188, 188, 188, 128]);
+
+        // non square levels stop at 1x1
+        let mips = generate_mipmaps_rgba8(5, 2, &[255; 5 * 2 * 4], true);
+        assert_eq!(mips.len(), (5 * 2 + 2 + 1) * 4);
+        assert!(mips.iter().all(|&value| value == 255));
+    }
+}
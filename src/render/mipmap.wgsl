@@ -0,0 +1,30 @@
+@group(0) @binding(0) var source: texture_2d<f32>;
+
+// fullscreen triangle
+@vertex
+fn vs_main(@builtin(vertex_index) index: u32) -> @builtin(position) vec4f {
+    let uv = vec2f(f32((index << 1u) & 2u), f32(index & 2u));
+    return vec4f(uv * 2.0 - 1.0, 0.0, 1.0);
+}
+
+// box filter over the texels covered by the destination texel,
+// odd sizes cover 1.5 or 3 texels so non power of two levels don't drop texels
+@fragment
+fn fs_main(@builtin(position) position: vec4f) -> @location(0) vec4f {
+    let source_size = textureDimensions(source);
+    let target_size = max(source_size / 2u, vec2u(1u));
+    let ratio = vec2f(source_size) / vec2f(target_size);
+    let start = floor(position.xy) * ratio;
+    let end = start + ratio;
+
+    var color = vec4f(0.0);
+    for (var y = u32(start.y); f32(y) < end.y && y < source_size.y; y++) {
+        let weight_y = min(end.y, f32(y + 1u)) - max(start.y, f32(y));
+        for (var x = u32(start.x); f32(x) < end.x && x < source_size.x; x++) {
+            let weight_x = min(end.x, f32(x + 1u)) - max(start.x, f32(x));
+            // sRGB textures are decoded by the load and encoded by the target
+            color += textureLoad(source, vec2u(x, y), 0) * weight_x * weight_y;
+        }
+    }
+    return color / (ratio.x * ratio.y);
+}
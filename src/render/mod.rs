@@ -2,9 +2,17 @@ mod arc;
 mod bind_group;
 mod buffer;
 mod cache;
+mod compressed_texture;
+mod cubemap;
 mod framebuffer;
+mod graph;
 mod mesh;
+mod mipmap;
 mod pipeline;
+mod pipeline_cache;
+mod preprocessor;
+mod readback;
+mod reflect;
 mod render_pass;
 mod shader;
 mod texture;
@@ -13,15 +21,23 @@ pub use arc::*;
 pub use bind_group::*;
 pub use buffer::*;
 pub use cache::*;
+pub use compressed_texture::*;
+pub use cubemap::*;
 pub use framebuffer::*;
+pub use graph::*;
 pub use mesh::*;
+pub use mipmap::*;
 pub use pipeline::*;
+pub use pipeline_cache::*;
+pub use preprocessor::*;
+pub use readback::*;
+pub use reflect::*;
 pub use render_pass::*;
 pub use shader::*;
 pub use texture::*;
 pub use vertex::*;
 
-use crate::{Context, ContextBuilder};
+use crate::{filesystem::FileSystemContext, Context, ContextBuilder};
 use std::sync::Arc;
 
 pub struct RenderContext {
@@ -34,12 +50,20 @@ pub struct RenderContext {
     pub(crate) window: Arc<winit::window::Window>,
     pub(crate) window_size: winit::dpi::PhysicalSize<u32>,
 
+    pub(crate) msaa_samples: u32,
+    /// Multisampled color target passes to the screen draw to, resolved into the surface
+    pub(crate) msaa_view: Option<wgpu::TextureView>,
+    /// Surface view of the frame being rendered
+    pub(crate) screen_view: Option<wgpu::TextureView>,
+
     pub(crate) cache: RenderCache,
+    pub(crate) pipeline_cache: Option<PipelineDiskCache>,
 }
 
 impl RenderContext {
     pub(crate) async fn new(
         context_builder: &ContextBuilder,
+        filesystem: &FileSystemContext,
         window: winit::window::Window,
     ) -> Self {
         let window = Arc::new(window);
@@ -76,7 +100,7 @@ impl RenderContext {
             .features()
             .contains(wgpu::Features::TIMESTAMP_QUERY_INSIDE_ENCODERS)
         {
-            required_features |= wgpu::Features::TIMESTAMP_QUERY;
+            required_features |= wgpu::Features::TIMESTAMP_QUERY_INSIDE_ENCODERS;
         }
         if adapter
             .features()
@@ -84,6 +108,18 @@ impl RenderContext {
         {
             required_features |= wgpu::Features::TIMESTAMP_QUERY;
         }
+        if context_builder.pipeline_cache_enabled
+            && adapter.features().contains(wgpu::Features::PIPELINE_CACHE)
+        {
+            required_features |= wgpu::Features::PIPELINE_CACHE;
+        }
+        // unsupported compressed textures are decompressed on the CPU if possible
+        required_features |= adapter.features() & TEXTURE_COMPRESSION_FEATURES;
+        // sample counts other than 1 and 4 depend on the adapter
+        if context_builder.msaa_samples > 1 {
+            required_features |=
+                adapter.features() & wgpu::Features::TEXTURE_ADAPTER_SPECIFIC_FORMAT_FEATURES;
+        }
 
         let (device, queue) = adapter
             .request_device(&wgpu::DeviceDescriptor {
@@ -125,7 +161,27 @@ impl RenderContext {
         };
         surface.configure(&device, &surface_config);
 
+        let msaa_samples = supported_sample_count(
+            &adapter,
+            required_features,
+            &[
+                surface_format.add_srgb_suffix(),
+                wgpu::TextureFormat::Depth32Float,
+            ],
+            context_builder.msaa_samples,
+        );
+        if msaa_samples != context_builder.msaa_samples {
+            tracing::warn!(
+                "{}x MSAA is not supported for {:?}, using {}x",
+                context_builder.msaa_samples,
+                surface_format,
+                msaa_samples
+            );
+        }
+        let msaa_view = create_msaa_view(&device, &surface_config, msaa_samples);
+
         let cache = RenderCache::empty();
+        let pipeline_cache = PipelineDiskCache::load(&device, &adapter.get_info(), filesystem);
 
         Self {
             device: Arc::new(device),
@@ -137,7 +193,12 @@ impl RenderContext {
             window_size,
             window,
 
+            msaa_samples,
+            msaa_view,
+            screen_view: None,
+
             cache,
+            pipeline_cache,
         }
     }
 
@@ -153,6 +214,7 @@ impl RenderContext {
         self.surface_config.width = new_size.width;
         self.surface_config.height = new_size.height;
         self.surface.configure(&self.device, &self.surface_config);
+        self.msaa_view = create_msaa_view(&self.device, &self.surface_config, self.msaa_samples);
     }
 
     /// Resizes the window to the last safe window size
@@ -169,6 +231,57 @@ impl RenderContext {
     }
 }
 
+/// Largest sample count up to `requested` every format can be rendered with
+fn supported_sample_count(
+    adapter: &wgpu::Adapter,
+    features: wgpu::Features,
+    formats: &[wgpu::TextureFormat],
+    requested: u32,
+) -> u32 {
+    let adapter_specific =
+        features.contains(wgpu::Features::TEXTURE_ADAPTER_SPECIFIC_FORMAT_FEATURES);
+    let mut count = 1 << requested.max(1).ilog2();
+    while count > 1 {
+        let supported = formats.iter().all(|&format| {
+            let flags = match adapter_specific {
+                true => adapter.get_texture_format_features(format).flags,
+                false => format.guaranteed_format_features(features).flags,
+            };
+            flags.sample_count_supported(count)
+        });
+        if supported {
+            return count;
+        }
+        count /= 2;
+    }
+    1
+}
+
+fn create_msaa_view(
+    device: &wgpu::Device,
+    surface_config: &wgpu::SurfaceConfiguration,
+    sample_count: u32,
+) -> Option<wgpu::TextureView> {
+    if sample_count <= 1 {
+        return None;
+    }
+    let texture = device.create_texture(&wgpu::TextureDescriptor {
+        label: Some("screen msaa"),
+        size: wgpu::Extent3d {
+            width: surface_config.width,
+            height: surface_config.height,
+            depth_or_array_layers: 1,
+        },
+        mip_level_count: 1,
+        sample_count,
+        dimension: wgpu::TextureDimension::D2,
+        format: surface_config.format.add_srgb_suffix(),
+        usage: wgpu::TextureUsages::RENDER_ATTACHMENT,
+        view_formats: &[],
+    });
+    Some(texture.create_view(&wgpu::TextureViewDescriptor::default()))
+}
+
 // Getter functions for render and window operations
 pub fn aspect_ratio(ctx: &Context) -> f32 {
     ctx.render.aspect_ratio()
@@ -208,6 +321,13 @@ pub fn surface_format(ctx: &Context) -> wgpu::TextureFormat {
 pub fn surface_size(ctx: &Context) -> winit::dpi::PhysicalSize<u32> {
     ctx.render.window_size
 }
+/// Sample count of the screen set by [`ContextBuilder::msaa`]
+///
+/// Passes opting in with [`RenderPassColorAttachment::msaa_screen`] are resolved into the
+/// screen automatically, their pipelines and depth buffers must use the same count
+pub fn msaa_samples(ctx: &Context) -> u32 {
+    ctx.render.msaa_samples
+}
 pub fn cache(ctx: &Context) -> &RenderCache {
     &ctx.render.cache
 }
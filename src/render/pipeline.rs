@@ -163,6 +163,8 @@ pub struct RenderPipelineBuilder {
     polygon_mode: wgpu::PolygonMode,                // mesh
     cull_mode: Option<wgpu::Face>,                  //
     depth_stencil: Option<wgpu::DepthStencilState>, //
+    sample_count: u32,                              // targets
+    alpha_to_coverage: bool,                        //
     vertex_entry_point: Option<String>,             // shader
     fragment_entry_point: Option<String>,           // shader
 }
@@ -178,6 +180,8 @@ impl RenderPipelineBuilder {
             polygon_mode: wgpu::PolygonMode::Fill,
             cull_mode: None,
             depth_stencil: None,
+            sample_count: 1,
+            alpha_to_coverage: false,
             label: None,
             vertex_entry_point: None,
             fragment_entry_point: None,
@@ -251,12 +255,12 @@ impl RenderPipelineBuilder {
             },
             depth_stencil: self.depth_stencil.clone(),
             multisample: wgpu::MultisampleState {
-                count: 1,
+                count: self.sample_count,
                 mask: !0,
-                alpha_to_coverage_enabled: false,
+                alpha_to_coverage_enabled: self.alpha_to_coverage,
             },
             multiview: None,
-            cache: None,
+            cache: ctx.render.pipeline_cache.as_ref().map(|cache| &cache.cache),
         });
 
         ArcRenderPipeline::new(ctx, pipeline)
@@ -299,6 +303,16 @@ impl RenderPipelineBuilder {
         self.depth_stencil = Some(value);
         self
     }
+    /// Must match the attachments, [`render::msaa_samples`] for the screen
+    pub fn sample_count(mut self, value: u32) -> Self {
+        self.sample_count = value;
+        self
+    }
+    /// Use the alpha of the first target as sample coverage, requires a sample count above 1
+    pub fn alpha_to_coverage(mut self, value: bool) -> Self {
+        self.alpha_to_coverage = value;
+        self
+    }
     pub fn topology(mut self, value: wgpu::PrimitiveTopology) -> Self {
         self.topology = value;
         self
@@ -361,7 +375,7 @@ impl ComputePipelineBuilder {
             module: &self.shader,
             entry_point: self.entry_point.as_deref(),
             compilation_options: wgpu::PipelineCompilationOptions::default(), // TODO look into these options
-            cache: None,
+            cache: ctx.render.pipeline_cache.as_ref().map(|cache| &cache.cache),
         });
 
         ArcComputePipeline::new(ctx, pipeline)
@@ -0,0 +1,205 @@
+use crate::{
+    filesystem::FileSystemContext,
+    render::{self, ComputePipelineBuilder, RenderPipelineBuilder},
+    time::Instant,
+    Context,
+};
+use std::{collections::VecDeque, time::Duration};
+
+//
+// Disk cache
+//
+
+/// Driver pipeline cache persisted in the temporary folder between runs
+pub(crate) struct PipelineDiskCache {
+    pub(crate) cache: wgpu::PipelineCache,
+    file: String,
+}
+
+impl PipelineDiskCache {
+    /// None if the device was created without [`wgpu::Features::PIPELINE_CACHE`]
+    pub(crate) fn load(
+        device: &wgpu::Device,
+        adapter_info: &wgpu::AdapterInfo,
+        filesystem: &FileSystemContext,
+    ) -> Option<Self> {
+        if !device.features().contains(wgpu::Features::PIPELINE_CACHE) {
+            return None;
+        }
+
+        let file = pipeline_cache_file(adapter_info);
+        let data = filesystem.load_temporary_bytes(&file).ok();
+        // SAFETY: the data was written by `save` for an adapter with the same key,
+        // wgpu validates its header and falls back to an empty cache on mismatch
+        let cache = unsafe {
+            device.create_pipeline_cache(&wgpu::PipelineCacheDescriptor {
+                label: Some("pipeline cache"),
+                data: data.as_deref(),
+                fallback: true,
+            })
+        };
+        tracing::info!(
+            "Loaded pipeline cache {} ({} bytes)",
+            file,
+            data.map_or(0, |data| data.len())
+        );
+
+        Some(Self { cache, file })
+    }
+
+    pub(crate) fn save(&self, filesystem: &FileSystemContext) {
+        let Some(data) = self.cache.get_data() else {
+            return;
+        };
+        if let Err(err) = filesystem.write_temporary_bytes(&self.file, &data) {
+            tracing::error!("could not write pipeline cache {}: {}", self.file, err);
+        }
+    }
+}
+
+/// Caches are only valid for the same backend, device and driver version
+fn pipeline_cache_file(info: &wgpu::AdapterInfo) -> String {
+    let driver =
+        crc32fast::hash(format!("{}{}{}", info.name, info.driver, info.driver_info).as_bytes());
+    format!(
+        "pipeline_cache_{:?}_{:x}_{:x}_{:08x}.bin",
+        info.backend, info.vendor, info.device, driver
+    )
+    .to_lowercase()
+}
+
+/// Write the pipeline cache to the temporary folder, if the device supports it
+///
+/// Called on shutdown and when a [`PipelineWarmup`] finishes
+pub fn save_pipeline_cache(ctx: &Context) {
+    if let Some(cache) = &ctx.render.pipeline_cache {
+        cache.save(&ctx.filesystem);
+    }
+}
+
+//
+// Warmup
+//
+
+#[derive(Clone)]
+pub enum PipelineDescriptor {
+    Render(RenderPipelineBuilder),
+    Compute(ComputePipelineBuilder),
+}
+
+impl From<RenderPipelineBuilder> for PipelineDescriptor {
+    fn from(value: RenderPipelineBuilder) -> Self {
+        Self::Render(value)
+    }
+}
+
+impl From<ComputePipelineBuilder> for PipelineDescriptor {
+    fn from(value: ComputePipelineBuilder) -> Self {
+        Self::Compute(value)
+    }
+}
+
+/// Builds pipelines ahead of time, e.g. spread over the frames of a loading screen
+///
+/// Pipelines end up in the render cache, so later `build` calls with the same builder are free
+pub struct PipelineWarmup {
+    pending: VecDeque<PipelineDescriptor>,
+    total: usize,
+}
+
+impl PipelineWarmup {
+    pub fn new(pipelines: impl IntoIterator<Item = impl Into<PipelineDescriptor>>) -> Self {
+        let pending = pipelines
+            .into_iter()
+            .map(Into::into)
+            .collect::<VecDeque<_>>();
+        Self {
+            total: pending.len(),
+            pending,
+        }
+    }
+
+    pub fn push(&mut self, pipeline: impl Into<PipelineDescriptor>) {
+        self.pending.push_back(pipeline.into());
+        self.total += 1;
+    }
+
+    /// Build pipelines until `budget` has passed, at least one per call
+    ///
+    /// Returns true and saves the pipeline cache once all are built
+    pub fn poll(&mut self, ctx: &mut Context, budget: Duration) -> bool {
+        if self.pending.is_empty() {
+            return true;
+        }
+
+        let start = Instant::now();
+        while let Some(pipeline) = self.pending.pop_front() {
+            build(ctx, pipeline);
+            if start.elapsed() >= budget {
+                break;
+            }
+        }
+
+        let done = self.pending.is_empty();
+        if done {
+            render::save_pipeline_cache(ctx);
+        }
+        done
+    }
+
+    /// Build all remaining pipelines now
+    pub fn finish(&mut self, ctx: &mut Context) {
+        self.poll(ctx, Duration::MAX);
+    }
+
+    /// Fraction of pipelines built, 1.0 when empty
+    pub fn progress(&self) -> f32 {
+        match self.total {
+            0 => 1.0,
+            total => (total - self.pending.len()) as f32 / total as f32,
+        }
+    }
+
+    pub fn is_done(&self) -> bool {
+        self.pending.is_empty()
+    }
+}
+
+fn build(ctx: &mut Context, pipeline: PipelineDescriptor) {
+    match pipeline {
+        PipelineDescriptor::Render(pipeline) => {
+            pipeline.build(ctx);
+        }
+        PipelineDescriptor::Compute(pipeline) => {
+            pipeline.build(ctx);
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::pipeline_cache_file;
+
+    #[test]
+    fn test_pipeline_cache_file() {
+        let info = wgpu::AdapterInfo {
+            name: String::from("GPU"),
+            vendor: 0x10de,
+            device: 0x2684,
+            device_type: wgpu::DeviceType::DiscreteGpu,
+            driver: String::from("driver"),
+            driver_info: String::from("550.1"),
+            backend: wgpu::Backend::Vulkan,
+        };
+        let file = pipeline_cache_file(&info);
+        assert!(file.starts_with("pipeline_cache_vulkan_10de_2684_"));
+        assert_eq!(file, pipeline_cache_file(&info.clone()));
+
+        // driver updates invalidate the cache
+        let updated = wgpu::AdapterInfo {
+            driver_info: String::from("555.2"),
+            ..info
+        };
+        assert_ne!(file, pipeline_cache_file(&updated));
+    }
+}
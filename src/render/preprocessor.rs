@@ -0,0 +1,376 @@
+use crate::filesystem;
+use rustc_hash::{FxHashMap, FxHashSet};
+use serde::{Deserialize, Serialize};
+use std::{
+    collections::BTreeMap,
+    path::{Path, PathBuf},
+};
+
+/// Value of a shader def, substituted into the source with `#{NAME}`
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize, Deserialize)]
+pub enum ShaderDefValue {
+    Bool(bool),
+    Int(i32),
+    UInt(u32),
+}
+
+impl ShaderDefValue {
+    fn parse(value: &str) -> Option<Self> {
+        match value {
+            "true" => Some(Self::Bool(true)),
+            "false" => Some(Self::Bool(false)),
+            value => match value.strip_suffix('u') {
+                Some(value) => value.parse().ok().map(Self::UInt),
+                None => value.parse().ok().map(Self::Int),
+            },
+        }
+    }
+
+    fn as_i64(self) -> i64 {
+        match self {
+            ShaderDefValue::Bool(value) => value as i64,
+            ShaderDefValue::Int(value) => value as i64,
+            ShaderDefValue::UInt(value) => value as i64,
+        }
+    }
+}
+
+impl std::fmt::Display for ShaderDefValue {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            ShaderDefValue::Bool(value) => write!(f, "{value}"),
+            ShaderDefValue::Int(value) => write!(f, "{value}"),
+            ShaderDefValue::UInt(value) => write!(f, "{value}u"),
+        }
+    }
+}
+
+impl From<bool> for ShaderDefValue {
+    fn from(value: bool) -> Self {
+        Self::Bool(value)
+    }
+}
+
+impl From<i32> for ShaderDefValue {
+    fn from(value: i32) -> Self {
+        Self::Int(value)
+    }
+}
+
+impl From<u32> for ShaderDefValue {
+    fn from(value: u32) -> Self {
+        Self::UInt(value)
+    }
+}
+
+/// Ordered so shaders with the same defs hash the same
+pub type ShaderDefs = BTreeMap<String, ShaderDefValue>;
+
+#[derive(thiserror::Error, Debug, Clone, PartialEq, Eq)]
+#[error("shader preprocessor error at line {line}: {message}")]
+pub struct ShaderPreprocessError {
+    pub line: usize,
+    pub message: String,
+}
+
+//
+// Imports
+//
+
+/// Import of another shader file, resolved when loading with [`ShaderLoader`](crate::asset::ShaderLoader)
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub enum ShaderImport {
+    /// `#import "lighting.wgsl"`, relative to the importing file
+    Relative(PathBuf),
+    /// `#import shaders::lighting`, resolves to `shaders/lighting.wgsl` from the assets root
+    Module(String),
+}
+
+impl ShaderImport {
+    pub fn parse(line: &str) -> Option<Self> {
+        let rest = line.trim().strip_prefix("#import")?.trim();
+        if let Some(path) = rest
+            .strip_prefix('"')
+            .and_then(|rest| rest.strip_suffix('"'))
+        {
+            return Some(Self::Relative(PathBuf::from(path)));
+        }
+
+        let valid = !rest.is_empty()
+            && rest
+                .chars()
+                .all(|c| c.is_ascii_alphanumeric() || c == '_' || c == ':');
+        valid.then(|| Self::Module(rest.to_string()))
+    }
+
+    /// Asset path of the imported file, `.wgsl` is added if there is no extension
+    pub fn resolve(&self, importer: &Path) -> PathBuf {
+        let path = match self {
+            ShaderImport::Relative(path) => importer.parent().unwrap_or(Path::new("")).join(path),
+            ShaderImport::Module(module) => module.split("::").collect(),
+        };
+        let path = filesystem::normalize_path(path);
+        match path.extension() {
+            Some(_) => path,
+            None => path.with_extension("wgsl"),
+        }
+    }
+}
+
+/// Inline all imports of `root`, each file is included once at its first import
+///
+/// `sources` must contain every file reachable from `root`
+pub(crate) fn expand_imports(root: &Path, sources: &FxHashMap<PathBuf, String>) -> String {
+    fn expand(
+        path: &Path,
+        sources: &FxHashMap<PathBuf, String>,
+        included: &mut FxHashSet<PathBuf>,
+        output: &mut String,
+    ) {
+        if !included.insert(path.to_path_buf()) {
+            return;
+        }
+        let Some(source) = sources.get(path) else {
+            return;
+        };
+
+        for line in source.lines() {
+            match ShaderImport::parse(line) {
+                Some(import) => expand(&import.resolve(path), sources, included, output),
+                None => {
+                    output.push_str(line);
+                    output.push('\n');
+                }
+            }
+        }
+    }
+
+    let mut output = String::new();
+    expand(root, sources, &mut FxHashSet::default(), &mut output);
+    output
+}
+
+//
+// Conditionals
+//
+
+struct Branch {
+    active: bool,
+    taken: bool,
+    seen_else: bool,
+}
+
+/// Evaluate `#define`, `#ifdef`, `#ifndef`, `#if`, `#else` and `#endif` and substitute `#{NAME}`
+///
+/// Removed lines are kept empty so errors from wgpu point at the right line
+pub fn preprocess_shader(source: &str, defs: &ShaderDefs) -> Result<String, ShaderPreprocessError> {
+    let mut defs = defs.clone();
+    let mut branches = Vec::<Branch>::new();
+    let mut output = String::with_capacity(source.len());
+
+    for (index, line) in source.lines().enumerate() {
+        let error = |message: String| ShaderPreprocessError {
+            line: index + 1,
+            message,
+        };
+        let active = branches.last().is_none_or(|branch| branch.active);
+        let trimmed = line.trim();
+
+        let directive = trimmed
+            .strip_prefix('#')
+            .filter(|rest| rest.starts_with(|c: char| c.is_ascii_alphabetic()));
+        let Some(directive) = directive else {
+            if active {
+                output.push_str(&substitute(line, &defs).map_err(error)?);
+            }
+            output.push('\n');
+            continue;
+        };
+
+        let (keyword, argument) = directive
+            .split_once(char::is_whitespace)
+            .map(|(keyword, argument)| (keyword, argument.trim()))
+            .unwrap_or((directive, ""));
+        match keyword {
+            "ifdef" | "ifndef" | "if" => {
+                let condition = match keyword {
+                    "ifdef" => defs.contains_key(argument),
+                    "ifndef" => !defs.contains_key(argument),
+                    // conditions in removed branches might use undefined names
+                    _ => active && evaluate(argument, &defs).map_err(error)?,
+                };
+                branches.push(Branch {
+                    active: active && condition,
+                    taken: condition,
+                    seen_else: false,
+                });
+            }
+            "else" => {
+                let parent_active = branches.len() < 2 || branches[branches.len() - 2].active;
+                let Some(branch) = branches.last_mut() else {
+                    return Err(error(String::from("#else without #if")));
+                };
+                if branch.seen_else {
+                    return Err(error(String::from("multiple #else in one #if")));
+                }
+                branch.active = parent_active && !branch.taken;
+                branch.seen_else = true;
+            }
+            "endif" => {
+                if branches.pop().is_none() {
+                    return Err(error(String::from("#endif without #if")));
+                }
+            }
+            "define" if active => {
+                let (name, value) = argument
+                    .split_once(char::is_whitespace)
+                    .map(|(name, value)| (name, value.trim()))
+                    .unwrap_or((argument, ""));
+                let value = match value {
+                    "" => ShaderDefValue::Bool(true),
+                    value => ShaderDefValue::parse(value)
+                        .ok_or_else(|| error(format!("invalid value {value:?} for {name}")))?,
+                };
+                defs.insert(name.to_string(), value);
+            }
+            "define" => {}
+            "import" => {
+                return Err(error(format!(
+                    "unresolved import {argument}, load the shader with ShaderLoader to resolve imports"
+                )));
+            }
+            keyword => return Err(error(format!("unknown directive #{keyword}"))),
+        }
+        output.push('\n');
+    }
+
+    if !branches.is_empty() {
+        return Err(ShaderPreprocessError {
+            line: source.lines().count(),
+            message: String::from("missing #endif"),
+        });
+    }
+    Ok(output)
+}
+
+// NAME, !NAME or NAME <op> VALUE
+fn evaluate(expression: &str, defs: &ShaderDefs) -> Result<bool, String> {
+    let truthy = |name: &str| defs.get(name).is_some_and(|value| value.as_i64() != 0);
+
+    let mut tokens = expression.split_whitespace();
+    match (tokens.next(), tokens.next(), tokens.next(), tokens.next()) {
+        (Some(name), None, None, None) => match name.strip_prefix('!') {
+            Some(name) => Ok(!truthy(name)),
+            None => Ok(truthy(name)),
+        },
+        (Some(name), Some(op), Some(value), None) => {
+            let left = defs
+                .get(name)
+                .ok_or_else(|| format!("{name} is not defined"))?
+                .as_i64();
+            let right = ShaderDefValue::parse(value)
+                .ok_or_else(|| format!("invalid value {value:?}"))?
+                .as_i64();
+            match op {
+                "==" => Ok(left == right),
+                "!=" => Ok(left != right),
+                "<" => Ok(left < right),
+                "<=" => Ok(left <= right),
+                ">" => Ok(left > right),
+                ">=" => Ok(left >= right),
+                op => Err(format!("unknown operator {op}")),
+            }
+        }
+        _ => Err(format!("invalid condition {expression:?}")),
+    }
+}
+
+fn substitute(line: &str, defs: &ShaderDefs) -> Result<String, String> {
+    let mut output = String::with_capacity(line.len());
+    let mut rest = line;
+    while let Some(start) = rest.find("#{") {
+        output.push_str(&rest[..start]);
+        let end = rest[start..]
+            .find('}')
+            .ok_or_else(|| String::from("unclosed #{"))?;
+        let name = &rest[start + 2..start + end];
+        let value = defs
+            .get(name)
+            .ok_or_else(|| format!("{name} is not defined"))?;
+        output.push_str(&value.to_string());
+        rest = &rest[start + end + 1..];
+    }
+    output.push_str(rest);
+    Ok(output)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{expand_imports, preprocess_shader, ShaderDefValue, ShaderDefs, ShaderImport};
+    use rustc_hash::FxHashMap;
+    use std::path::{Path, PathBuf};
+
+    #[test]
+    fn test_preprocess_conditionals() {
+        let source = "\
+#define SAMPLES 4
+#ifdef SHADOWS
+shadows
+#else
+no shadows
+#endif
+#if SAMPLES >= 4
+#if !DEBUG
+let samples = #{SAMPLES};
+#endif
+#endif
+";
+        let defs = ShaderDefs::from([(String::from("SHADOWS"), ShaderDefValue::Bool(true))]);
+        let output = preprocess_shader(source, &defs).unwrap();
+        let lines = output
+            .lines()
+            .filter(|line| !line.is_empty())
+            .collect::<Vec<_>>();
+        assert_eq!(lines, ["shadows", "let samples = 4;"]);
+        // removed lines are kept empty
+        assert_eq!(output.lines().count(), source.lines().count());
+
+        let err = preprocess_shader("#ifdef A\n", &ShaderDefs::new()).unwrap_err();
+        assert_eq!(err.message, "missing #endif");
+        let err = preprocess_shader("\n#{MISSING}\n", &ShaderDefs::new()).unwrap_err();
+        assert_eq!(err.line, 2);
+    }
+
+    #[test]
+    fn test_expand_imports() {
+        assert_eq!(
+            ShaderImport::parse("#import \"../common\"")
+                .unwrap()
+                .resolve(Path::new("shaders/pbr/mesh.wgsl")),
+            PathBuf::from("shaders/common.wgsl")
+        );
+        assert_eq!(
+            ShaderImport::parse("#import shaders::utils::noise")
+                .unwrap()
+                .resolve(Path::new("a.wgsl")),
+            PathBuf::from("shaders/utils/noise.wgsl")
+        );
+
+        let sources = FxHashMap::from_iter([
+            (
+                PathBuf::from("main.wgsl"),
+                String::from("#import \"a\"\n#import lib::b\nmain"),
+            ),
+            (PathBuf::from("a.wgsl"), String::from("#import lib::b\na")),
+            (
+                PathBuf::from("lib/b.wgsl"),
+                String::from("#import \"../main.wgsl\"\nb"),
+            ),
+        ]);
+        // each file once, cycles are cut
+        assert_eq!(
+            expand_imports(Path::new("main.wgsl"), &sources),
+            "b\na\nmain\n"
+        );
+    }
+}
@@ -0,0 +1,391 @@
+use crate::{render, Context};
+use std::{
+    ops::{Bound, Range, RangeBounds},
+    sync::mpsc,
+};
+
+#[derive(thiserror::Error, Debug, Clone)]
+pub enum ReadbackError {
+    #[error("could not map readback buffer: {0}")]
+    Map(#[from] wgpu::BufferAsyncError),
+    #[error("readback buffer was dropped before it was mapped")]
+    Disconnected,
+    #[error("could not poll the device: {0}")]
+    Poll(String),
+    #[error("can not convert {0:?} to an image")]
+    UnsupportedFormat(wgpu::TextureFormat),
+    #[error("range {range:?} is out of bounds of a {size} byte buffer")]
+    OutOfBounds { range: Range<u64>, size: u64 },
+    #[error(
+        "range {range:?} ends in the unaligned tail of a {size} byte buffer, which can't be copied"
+    )]
+    UnalignedTail { range: Range<u64>, size: u64 },
+    #[error("can not copy the {aspect:?} aspect of {format:?}")]
+    UncopyableAspect {
+        format: wgpu::TextureFormat,
+        aspect: wgpu::TextureAspect,
+    },
+}
+
+/// Staging buffer being mapped for reading
+struct PendingMap {
+    buffer: wgpu::Buffer,
+    receiver: mpsc::Receiver<Result<(), wgpu::BufferAsyncError>>,
+}
+
+enum ReadbackState {
+    Pending(PendingMap),
+    Done(Result<Vec<u8>, ReadbackError>),
+}
+
+impl ReadbackState {
+    fn new(buffer: wgpu::Buffer) -> Self {
+        let (sender, receiver) = mpsc::channel();
+        buffer.slice(..).map_async(wgpu::MapMode::Read, move |res| {
+            // the handle may have been dropped
+            let _ = sender.send(res);
+        });
+        Self::Pending(PendingMap { buffer, receiver })
+    }
+
+    /// Mapped bytes passed through `finish` once the GPU is done, never blocks
+    fn poll(
+        &mut self,
+        ctx: &Context,
+        finish: impl FnOnce(&[u8]) -> Vec<u8>,
+    ) -> Option<Result<&[u8], ReadbackError>> {
+        if let Self::Pending(pending) = self {
+            // map callbacks only run while polling on native, no-op on wasm
+            // e.g. a lost device
+            let result = match render::device(ctx).poll(wgpu::MaintainBase::Poll) {
+                Err(err) => Err(ReadbackError::Poll(err.to_string())),
+                Ok(_) => match pending.receiver.try_recv() {
+                    Ok(Ok(())) => {
+                        let data = finish(&pending.buffer.slice(..).get_mapped_range());
+                        pending.buffer.unmap();
+                        Ok(data)
+                    }
+                    Ok(Err(err)) => Err(ReadbackError::Map(err)),
+                    Err(mpsc::TryRecvError::Empty) => return None,
+                    Err(mpsc::TryRecvError::Disconnected) => Err(ReadbackError::Disconnected),
+                },
+            };
+            *self = Self::Done(result);
+        }
+
+        match self {
+            Self::Done(result) => Some(result.as_deref().map_err(Clone::clone)),
+            Self::Pending(_) => None,
+        }
+    }
+}
+
+fn staging_buffer(ctx: &Context, size: u64) -> wgpu::Buffer {
+    render::device(ctx).create_buffer(&wgpu::BufferDescriptor {
+        label: Some("readback"),
+        size,
+        usage: wgpu::BufferUsages::MAP_READ | wgpu::BufferUsages::COPY_DST,
+        mapped_at_creation: false,
+    })
+}
+
+//
+// Buffer
+//
+
+/// Contents of a buffer copied back from the GPU, see [`readback_buffer`]
+pub struct BufferReadback {
+    state: ReadbackState,
+    /// Bytes before the requested range, copies must start 4 byte aligned
+    skip: usize,
+    len: usize,
+}
+
+impl BufferReadback {
+    /// The bytes once the copy has finished, `None` while pending
+    ///
+    /// Never blocks, call again in a later frame
+    pub fn poll(&mut self, ctx: &Context) -> Option<Result<&[u8], ReadbackError>> {
+        let (skip, len) = (self.skip, self.len);
+        self.state.poll(ctx, |data| data[skip..skip + len].to_vec())
+    }
+
+    /// Same as [`poll`](Self::poll) but cast to `T`
+    pub fn poll_cast<T: bytemuck::Pod>(
+        &mut self,
+        ctx: &Context,
+    ) -> Option<Result<Vec<T>, ReadbackError>> {
+        self.poll(ctx)
+            .map(|result| result.map(bytemuck::pod_collect_to_vec))
+    }
+}
+
+/// Copy a range of a buffer back to the CPU without blocking
+///
+/// The buffer needs `COPY_SRC` usage, the data is available from [`BufferReadback::poll`]
+/// once the GPU has finished, usually a frame or two later.
+/// Copies are 4 byte aligned, so the last bytes of a buffer whose size is not a
+/// multiple of 4 can't be read back. Empty ranges finish immediately
+pub fn readback_buffer(
+    ctx: &Context,
+    buffer: &wgpu::Buffer,
+    range: impl RangeBounds<wgpu::BufferAddress>,
+) -> Result<BufferReadback, ReadbackError> {
+    debug_assert!(buffer.usage().contains(wgpu::BufferUsages::COPY_SRC));
+    let size = buffer.size();
+    let range = resolve_range(range, size);
+    if range.start > range.end || range.end > size {
+        return Err(ReadbackError::OutOfBounds { range, size });
+    }
+    // wgpu can't map zero sized buffers
+    if range.is_empty() {
+        return Ok(BufferReadback {
+            state: ReadbackState::Done(Ok(Vec::new())),
+            skip: 0,
+            len: 0,
+        });
+    }
+    let copy = aligned_copy_range(&range, size).ok_or_else(|| ReadbackError::UnalignedTail {
+        range: range.clone(),
+        size,
+    })?;
+
+    let staging = staging_buffer(ctx, copy.end - copy.start);
+    let mut encoder = render::EncoderBuilder::new().build(ctx);
+    encoder.copy_buffer_to_buffer(buffer, copy.start, &staging, 0, copy.end - copy.start);
+    render::queue(ctx).submit([encoder.finish()]);
+
+    Ok(BufferReadback {
+        state: ReadbackState::new(staging),
+        skip: (range.start - copy.start) as usize,
+        len: (range.end - range.start) as usize,
+    })
+}
+
+fn resolve_range(range: impl RangeBounds<wgpu::BufferAddress>, size: u64) -> Range<u64> {
+    let start = match range.start_bound() {
+        Bound::Included(&start) => start,
+        Bound::Excluded(&start) => start + 1,
+        Bound::Unbounded => 0,
+    };
+    let end = match range.end_bound() {
+        Bound::Included(&end) => end + 1,
+        Bound::Excluded(&end) => end,
+        Bound::Unbounded => size,
+    };
+    start..end
+}
+
+/// Range widened to `COPY_BUFFER_ALIGNMENT`, `None` if that goes past the end of the buffer
+fn aligned_copy_range(range: &Range<u64>, size: u64) -> Option<Range<u64>> {
+    let start = range.start / wgpu::COPY_BUFFER_ALIGNMENT * wgpu::COPY_BUFFER_ALIGNMENT;
+    let end = range.end.div_ceil(wgpu::COPY_BUFFER_ALIGNMENT) * wgpu::COPY_BUFFER_ALIGNMENT;
+    (end <= size).then_some(start..end)
+}
+
+//
+// Texture
+//
+
+/// Texels of one layer of a mip level copied back from the GPU, see [`readback_texture`]
+pub struct TextureReadback {
+    state: ReadbackState,
+    format: wgpu::TextureFormat,
+    width: u32,
+    height: u32,
+    bytes_per_row: u32,
+    padded_bytes_per_row: u32,
+}
+
+impl TextureReadback {
+    /// Tightly packed rows once the copy has finished, `None` while pending
+    ///
+    /// Never blocks, call again in a later frame
+    pub fn poll(&mut self, ctx: &Context) -> Option<Result<&[u8], ReadbackError>> {
+        let (bytes_per_row, padded_bytes_per_row) = (self.bytes_per_row, self.padded_bytes_per_row);
+        self.state.poll(ctx, |data| {
+            unpad_rows(data, bytes_per_row, padded_bytes_per_row)
+        })
+    }
+
+    /// Same as [`poll`](Self::poll) but converted to an image, e.g. for screenshots
+    pub fn poll_image(
+        &mut self,
+        ctx: &Context,
+    ) -> Option<Result<image::DynamicImage, ReadbackError>> {
+        let (format, width, height) = (self.format, self.width, self.height);
+        self.poll(ctx)
+            .map(|result| result.and_then(|data| texels_to_image(format, width, height, data)))
+    }
+
+    pub fn format(&self) -> wgpu::TextureFormat {
+        self.format
+    }
+    pub fn width(&self) -> u32 {
+        self.width
+    }
+    pub fn height(&self) -> u32 {
+        self.height
+    }
+}
+
+/// Copy one layer of a mip level back to the CPU without blocking
+///
+/// The texture needs `COPY_SRC` usage, for 3D textures `layer` is the depth slice.
+/// Depth stencil textures copy one aspect at a time, e.g. `DepthOnly`, and some like
+/// `Depth24Plus` can't be copied at all.
+/// The data is available from [`TextureReadback::poll`] once the GPU has finished
+pub fn readback_texture(
+    ctx: &Context,
+    texture: &wgpu::Texture,
+    mip_level: u32,
+    layer: u32,
+    aspect: wgpu::TextureAspect,
+) -> Result<TextureReadback, ReadbackError> {
+    debug_assert!(texture.usage().contains(wgpu::TextureUsages::COPY_SRC));
+    let format = texture.format();
+    let uncopyable = ReadbackError::UncopyableAspect { format, aspect };
+    let block_size = format
+        .block_copy_size(Some(aspect))
+        .ok_or(uncopyable.clone())?;
+    let format = format.aspect_specific_format(aspect).ok_or(uncopyable)?;
+    let size = texture
+        .size()
+        .mip_level_size(mip_level, texture.dimension());
+    let copy_size = wgpu::Extent3d {
+        depth_or_array_layers: 1,
+        ..size.physical_size(format)
+    };
+    let (block_width, block_height) = format.block_dimensions();
+    let rows = copy_size.height / block_height;
+    let bytes_per_row = copy_size.width / block_width * block_size;
+    let padded_bytes_per_row = bytes_per_row.next_multiple_of(wgpu::COPY_BYTES_PER_ROW_ALIGNMENT);
+
+    let staging = staging_buffer(ctx, padded_bytes_per_row as u64 * rows as u64);
+    let mut encoder = render::EncoderBuilder::new().build(ctx);
+    encoder.copy_texture_to_buffer(
+        wgpu::TexelCopyTextureInfo {
+            texture,
+            mip_level,
+            origin: wgpu::Origin3d {
+                x: 0,
+                y: 0,
+                z: layer,
+            },
+            aspect,
+        },
+        wgpu::TexelCopyBufferInfo {
+            buffer: &staging,
+            layout: wgpu::TexelCopyBufferLayout {
+                offset: 0,
+                bytes_per_row: Some(padded_bytes_per_row),
+                rows_per_image: Some(rows),
+            },
+        },
+        copy_size,
+    );
+    render::queue(ctx).submit([encoder.finish()]);
+
+    Ok(TextureReadback {
+        state: ReadbackState::new(staging),
+        format,
+        width: size.width,
+        height: size.height,
+        bytes_per_row,
+        padded_bytes_per_row,
+    })
+}
+
+/// Remove the padding copies add to align rows to `COPY_BYTES_PER_ROW_ALIGNMENT`
+fn unpad_rows(data: &[u8], bytes_per_row: u32, padded_bytes_per_row: u32) -> Vec<u8> {
+    data.chunks(padded_bytes_per_row as usize)
+        .flat_map(|row| &row[..bytes_per_row as usize])
+        .copied()
+        .collect()
+}
+
+/// Convert tightly packed texels of common color formats to an image
+pub fn texels_to_image(
+    format: wgpu::TextureFormat,
+    width: u32,
+    height: u32,
+    data: &[u8],
+) -> Result<image::DynamicImage, ReadbackError> {
+    use image::DynamicImage;
+    let data = data.to_vec();
+    let image = match format {
+        wgpu::TextureFormat::Rgba8Unorm | wgpu::TextureFormat::Rgba8UnormSrgb => {
+            image::RgbaImage::from_raw(width, height, data).map(DynamicImage::ImageRgba8)
+        }
+        wgpu::TextureFormat::Bgra8Unorm | wgpu::TextureFormat::Bgra8UnormSrgb => {
+            let mut data = data;
+            for texel in data.chunks_exact_mut(4) {
+                texel.swap(0, 2);
+            }
+            image::RgbaImage::from_raw(width, height, data).map(DynamicImage::ImageRgba8)
+        }
+        wgpu::TextureFormat::R8Unorm => {
+            image::GrayImage::from_raw(width, height, data).map(DynamicImage::ImageLuma8)
+        }
+        wgpu::TextureFormat::Rgba16Unorm => {
+            image::ImageBuffer::from_raw(width, height, bytemuck::pod_collect_to_vec(&data))
+                .map(DynamicImage::ImageRgba16)
+        }
+        wgpu::TextureFormat::Rgba16Float => {
+            let texels = bytemuck::pod_collect_to_vec::<u8, u16>(&data)
+                .into_iter()
+                .map(|bits| half::f16::from_bits(bits).to_f32())
+                .collect();
+            image::Rgba32FImage::from_raw(width, height, texels).map(DynamicImage::ImageRgba32F)
+        }
+        wgpu::TextureFormat::Rgba32Float => {
+            image::Rgba32FImage::from_raw(width, height, bytemuck::pod_collect_to_vec(&data))
+                .map(DynamicImage::ImageRgba32F)
+        }
+        _ => None,
+    };
+    image.ok_or(ReadbackError::UnsupportedFormat(format))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{aligned_copy_range, resolve_range, texels_to_image, unpad_rows};
+
+    #[test]
+    fn test_buffer_ranges() {
+        assert_eq!(resolve_range(.., 64), 0..64);
+        assert_eq!(resolve_range(3..=9, 64), 3..10);
+
+        // widened to 4 byte alignment, but never past the buffer
+        assert_eq!(aligned_copy_range(&(3..10), 64), Some(0..12));
+        assert_eq!(aligned_copy_range(&(8..16), 64), Some(8..16));
+        assert_eq!(aligned_copy_range(&(60..63), 64), Some(60..64));
+        assert_eq!(aligned_copy_range(&(56..60), 63), Some(56..60));
+        assert_eq!(aligned_copy_range(&(60..63), 63), None);
+    }
+
+    #[test]
+    fn test_unpad_rows() {
+        let padded = [[1, 2, 0, 0], [3, 4, 0, 0]].concat();
+        assert_eq!(unpad_rows(&padded, 2, 4), [1, 2, 3, 4]);
+    }
+
+    #[test]
+    fn test_texels_to_image() {
+        let image =
+            texels_to_image(wgpu::TextureFormat::Bgra8UnormSrgb, 1, 1, &[1, 2, 3, 4]).unwrap();
+        assert_eq!(image.to_rgba8().into_raw(), [3, 2, 1, 4]);
+
+        // 1.0 and 0.5 as f16
+        let data = [0x3c00u16, 0x3800, 0, 0x3c00]
+            .iter()
+            .flat_map(|bits| bits.to_le_bytes())
+            .collect::<Vec<_>>();
+        let image = texels_to_image(wgpu::TextureFormat::Rgba16Float, 1, 1, &data).unwrap();
+        assert_eq!(image.to_rgba32f().into_raw(), [1.0, 0.5, 0.0, 1.0]);
+
+        // too little data and unsupported formats fail
+        assert!(texels_to_image(wgpu::TextureFormat::Rgba8Unorm, 2, 1, &[0; 4]).is_err());
+        assert!(texels_to_image(wgpu::TextureFormat::Depth32Float, 1, 1, &[0; 4]).is_err());
+    }
+}
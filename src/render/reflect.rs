@@ -0,0 +1,672 @@
+use crate::{
+    render::{
+        BindGroupEntry, BindGroupLayoutBuilder, BindGroupLayoutEntry, PipelineLayoutBuilder,
+        ShaderPreprocessError,
+    },
+    Context,
+};
+
+#[derive(thiserror::Error, Debug, Clone, PartialEq, Eq)]
+pub enum ShaderReflectError {
+    #[error(transparent)]
+    Preprocess(#[from] ShaderPreprocessError),
+    #[error("could not parse shader: {0}")]
+    Parse(String),
+    #[error("invalid shader: {0}")]
+    Validation(String),
+    #[error("{binding}: unsupported binding, {message}")]
+    UnsupportedBinding {
+        binding: BindingName,
+        message: String,
+    },
+    #[error("{entry_point}: unsupported vertex input @location({location}), {message}")]
+    UnsupportedVertexInput {
+        entry_point: String,
+        location: u32,
+        message: String,
+    },
+    #[error(
+        "@group({group}) is missing @binding({binding}), bind group layouts need bindings 0..n"
+    )]
+    MissingBinding { group: u32, binding: u32 },
+}
+
+#[derive(thiserror::Error, Debug, Clone, PartialEq, Eq)]
+pub enum BindGroupValidationError {
+    #[error("shader has no @group({group})")]
+    MissingGroup { group: u32 },
+    #[error("@group({group}) has {expected} bindings but {actual} entries were given")]
+    EntryCount {
+        group: u32,
+        expected: usize,
+        actual: usize,
+    },
+    #[error("{binding}: expected {expected} but got {actual}")]
+    Mismatch {
+        binding: BindingName,
+        expected: &'static str,
+        actual: &'static str,
+    },
+    #[error("{binding}: buffer needs {usage:?} usage")]
+    BufferUsage {
+        binding: BindingName,
+        usage: wgpu::BufferUsages,
+    },
+    #[error("{binding}: buffer is {size} bytes but the shader needs at least {min_size}")]
+    BufferTooSmall {
+        binding: BindingName,
+        size: u64,
+        min_size: u64,
+    },
+}
+
+/// `@group(0) @binding(1) name` in errors
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct BindingName {
+    pub group: u32,
+    pub binding: u32,
+    pub name: Option<String>,
+}
+
+impl std::fmt::Display for BindingName {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "@group({}) @binding({})", self.group, self.binding)?;
+        if let Some(name) = &self.name {
+            write!(f, " {name}")?;
+        }
+        Ok(())
+    }
+}
+
+//
+// Reflection
+//
+
+/// Resource binding declared by a shader
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ReflectedBinding {
+    pub group: u32,
+    pub binding: u32,
+    pub name: Option<String>,
+    /// Stages of the entry points using the binding
+    pub visibility: wgpu::ShaderStages,
+    /// Float textures are assumed filterable and samplers filtering,
+    /// the shader does not say how they are sampled
+    pub ty: wgpu::BindingType,
+    /// Size of the buffer type, the fixed part for runtime sized arrays
+    pub min_size: Option<u64>,
+}
+
+impl ReflectedBinding {
+    pub fn name(&self) -> BindingName {
+        BindingName {
+            group: self.group,
+            binding: self.binding,
+            name: self.name.clone(),
+        }
+    }
+
+    pub fn layout_entry(&self) -> BindGroupLayoutEntry {
+        BindGroupLayoutEntry::new()
+            .visibility(self.visibility)
+            .ty(self.ty)
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ReflectedVertexInput {
+    pub location: u32,
+    pub name: Option<String>,
+    pub format: wgpu::VertexFormat,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ReflectedEntryPoint {
+    pub name: String,
+    pub stage: wgpu::ShaderStages,
+    /// Vertex attributes by location, only for vertex entry points
+    pub vertex_inputs: Vec<ReflectedVertexInput>,
+    /// Only for compute entry points
+    pub workgroup_size: [u32; 3],
+}
+
+/// Bindings and entry points of a WGSL shader, see [`ShaderBuilder::reflect`](crate::render::ShaderBuilder::reflect)
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct ShaderReflection {
+    /// Sorted by group and binding
+    pub bindings: Vec<ReflectedBinding>,
+    pub entry_points: Vec<ReflectedEntryPoint>,
+}
+
+impl ShaderReflection {
+    pub fn from_wgsl(source: &str) -> Result<Self, ShaderReflectError> {
+        let module = naga::front::wgsl::parse_str(source)
+            .map_err(|err| ShaderReflectError::Parse(err.emit_to_string(source)))?;
+        let info = naga::valid::Validator::new(
+            naga::valid::ValidationFlags::all(),
+            naga::valid::Capabilities::all(),
+        )
+        .validate(&module)
+        .map_err(|err| ShaderReflectError::Validation(err.emit_to_string(source)))?;
+
+        let mut bindings = Vec::new();
+        for (handle, global) in module.global_variables.iter() {
+            let Some(resource) = &global.binding else {
+                continue;
+            };
+            let name = BindingName {
+                group: resource.group,
+                binding: resource.binding,
+                name: global.name.clone(),
+            };
+            let (ty, min_size) = binding_type(&module, global).map_err(|message| {
+                ShaderReflectError::UnsupportedBinding {
+                    binding: name.clone(),
+                    message,
+                }
+            })?;
+
+            let visibility = module
+                .entry_points
+                .iter()
+                .enumerate()
+                .filter(|(index, _)| !info.get_entry_point(*index)[handle].is_empty())
+                .fold(wgpu::ShaderStages::empty(), |visibility, (_, entry)| {
+                    visibility | shader_stage(entry.stage)
+                });
+
+            bindings.push(ReflectedBinding {
+                group: resource.group,
+                binding: resource.binding,
+                name: global.name.clone(),
+                visibility,
+                ty,
+                min_size,
+            });
+        }
+        bindings.sort_by_key(|binding| (binding.group, binding.binding));
+
+        let entry_points = module
+            .entry_points
+            .iter()
+            .map(|entry| {
+                let vertex_inputs = match entry.stage {
+                    naga::ShaderStage::Vertex => vertex_inputs(&module, entry)?,
+                    _ => Vec::new(),
+                };
+                Ok(ReflectedEntryPoint {
+                    name: entry.name.clone(),
+                    stage: shader_stage(entry.stage),
+                    vertex_inputs,
+                    workgroup_size: entry.workgroup_size,
+                })
+            })
+            .collect::<Result<Vec<_>, ShaderReflectError>>()?;
+
+        Ok(Self {
+            bindings,
+            entry_points,
+        })
+    }
+
+    pub fn entry_point(&self, name: &str) -> Option<&ReflectedEntryPoint> {
+        self.entry_points.iter().find(|entry| entry.name == name)
+    }
+
+    /// Bindings of one group, in binding order
+    pub fn group(&self, group: u32) -> impl Iterator<Item = &ReflectedBinding> {
+        self.bindings
+            .iter()
+            .filter(move |binding| binding.group == group)
+    }
+
+    /// Highest group used plus one
+    pub fn group_count(&self) -> u32 {
+        self.bindings.last().map_or(0, |binding| binding.group + 1)
+    }
+
+    /// Layout of one group, errors if bindings are not `0..n` since
+    /// [`BindGroupLayoutBuilder`] numbers entries by index
+    pub fn bind_group_layout(
+        &self,
+        group: u32,
+    ) -> Result<BindGroupLayoutBuilder, ShaderReflectError> {
+        let mut entries = Vec::new();
+        for (index, binding) in self.group(group).enumerate() {
+            if binding.binding != index as u32 {
+                return Err(ShaderReflectError::MissingBinding {
+                    group,
+                    binding: index as u32,
+                });
+            }
+            entries.push(binding.layout_entry());
+        }
+        Ok(BindGroupLayoutBuilder::new().entries(entries))
+    }
+
+    /// Layouts of groups `0..group_count`, unused groups are empty
+    pub fn bind_group_layouts(&self) -> Result<Vec<BindGroupLayoutBuilder>, ShaderReflectError> {
+        (0..self.group_count())
+            .map(|group| self.bind_group_layout(group))
+            .collect()
+    }
+
+    /// Pipeline layout with every reflected group, bind group layouts are built cached
+    pub fn pipeline_layout(
+        &self,
+        ctx: &mut Context,
+    ) -> Result<PipelineLayoutBuilder, ShaderReflectError> {
+        let bind_groups = self
+            .bind_group_layouts()?
+            .into_iter()
+            .map(|layout| layout.build(ctx))
+            .collect();
+        Ok(PipelineLayoutBuilder::new().bind_groups(bind_groups))
+    }
+
+    /// Check bind group entries against the bindings of `group`
+    ///
+    /// Texture views can't be inspected, only that a view is bound
+    pub fn validate_entries(
+        &self,
+        group: u32,
+        entries: &[BindGroupEntry],
+    ) -> Result<(), BindGroupValidationError> {
+        let bindings = self.group(group).collect::<Vec<_>>();
+        if bindings.is_empty() {
+            return Err(BindGroupValidationError::MissingGroup { group });
+        }
+        if bindings.len() != entries.len() {
+            return Err(BindGroupValidationError::EntryCount {
+                group,
+                expected: bindings.len(),
+                actual: entries.len(),
+            });
+        }
+
+        for (binding, entry) in bindings.into_iter().zip(entries) {
+            let mismatch = || BindGroupValidationError::Mismatch {
+                binding: binding.name(),
+                expected: binding_type_name(&binding.ty),
+                actual: entry_name(entry),
+            };
+            match (binding.ty, entry) {
+                (
+                    wgpu::BindingType::Buffer { ty, .. },
+                    BindGroupEntry::Buffer(buffer) | BindGroupEntry::BufferSlice { buffer, .. },
+                ) => {
+                    let usage = match ty {
+                        wgpu::BufferBindingType::Uniform => wgpu::BufferUsages::UNIFORM,
+                        wgpu::BufferBindingType::Storage { .. } => wgpu::BufferUsages::STORAGE,
+                    };
+                    if !buffer.usage().contains(usage) {
+                        return Err(BindGroupValidationError::BufferUsage {
+                            binding: binding.name(),
+                            usage,
+                        });
+                    }
+
+                    let size = match entry {
+                        BindGroupEntry::BufferSlice { size, .. } => *size,
+                        _ => buffer.size(),
+                    };
+                    if let Some(min_size) = binding.min_size.filter(|min_size| size < *min_size) {
+                        return Err(BindGroupValidationError::BufferTooSmall {
+                            binding: binding.name(),
+                            size,
+                            min_size,
+                        });
+                    }
+                }
+                (
+                    wgpu::BindingType::Texture { .. } | wgpu::BindingType::StorageTexture { .. },
+                    BindGroupEntry::Texture(_),
+                )
+                | (wgpu::BindingType::Sampler(_), BindGroupEntry::Sampler(_)) => {}
+                _ => return Err(mismatch()),
+            }
+        }
+        Ok(())
+    }
+}
+
+fn shader_stage(stage: naga::ShaderStage) -> wgpu::ShaderStages {
+    match stage {
+        naga::ShaderStage::Vertex => wgpu::ShaderStages::VERTEX,
+        naga::ShaderStage::Fragment => wgpu::ShaderStages::FRAGMENT,
+        naga::ShaderStage::Compute => wgpu::ShaderStages::COMPUTE,
+        naga::ShaderStage::Task => wgpu::ShaderStages::TASK,
+        naga::ShaderStage::Mesh => wgpu::ShaderStages::MESH,
+    }
+}
+
+fn binding_type(
+    module: &naga::Module,
+    global: &naga::GlobalVariable,
+) -> Result<(wgpu::BindingType, Option<u64>), String> {
+    let buffer = |ty| wgpu::BindingType::Buffer {
+        ty,
+        has_dynamic_offset: false,
+        min_binding_size: None,
+    };
+    let inner = &module.types[global.ty].inner;
+    let size = Some(inner.size(module.to_ctx()) as u64);
+
+    match global.space {
+        naga::AddressSpace::Uniform => Ok((buffer(wgpu::BufferBindingType::Uniform), size)),
+        naga::AddressSpace::Storage { access } => Ok((
+            buffer(wgpu::BufferBindingType::Storage {
+                read_only: !access.contains(naga::StorageAccess::STORE),
+            }),
+            size,
+        )),
+        naga::AddressSpace::Handle => match *inner {
+            naga::TypeInner::Sampler { comparison } => Ok((
+                wgpu::BindingType::Sampler(match comparison {
+                    true => wgpu::SamplerBindingType::Comparison,
+                    false => wgpu::SamplerBindingType::Filtering,
+                }),
+                None,
+            )),
+            naga::TypeInner::Image {
+                dim,
+                arrayed,
+                class,
+            } => {
+                let view_dimension = match (dim, arrayed) {
+                    (naga::ImageDimension::D1, _) => wgpu::TextureViewDimension::D1,
+                    (naga::ImageDimension::D2, false) => wgpu::TextureViewDimension::D2,
+                    (naga::ImageDimension::D2, true) => wgpu::TextureViewDimension::D2Array,
+                    (naga::ImageDimension::D3, _) => wgpu::TextureViewDimension::D3,
+                    (naga::ImageDimension::Cube, false) => wgpu::TextureViewDimension::Cube,
+                    (naga::ImageDimension::Cube, true) => wgpu::TextureViewDimension::CubeArray,
+                };
+                let ty = match class {
+                    naga::ImageClass::Sampled { kind, multi } => wgpu::BindingType::Texture {
+                        sample_type: match kind {
+                            // multisampled float textures can't be filtered
+                            naga::ScalarKind::Float => {
+                                wgpu::TextureSampleType::Float { filterable: !multi }
+                            }
+                            naga::ScalarKind::Sint => wgpu::TextureSampleType::Sint,
+                            naga::ScalarKind::Uint => wgpu::TextureSampleType::Uint,
+                            kind => return Err(format!("{kind:?} texture")),
+                        },
+                        view_dimension,
+                        multisampled: multi,
+                    },
+                    naga::ImageClass::Depth { multi } => wgpu::BindingType::Texture {
+                        sample_type: wgpu::TextureSampleType::Depth,
+                        view_dimension,
+                        multisampled: multi,
+                    },
+                    naga::ImageClass::Storage { format, access } => {
+                        wgpu::BindingType::StorageTexture {
+                            access: storage_access(access),
+                            format: storage_format(format),
+                            view_dimension,
+                        }
+                    }
+                };
+                Ok((ty, None))
+            }
+            ref inner => Err(format!("{inner:?}")),
+        },
+        space => Err(format!("{space:?} address space")),
+    }
+}
+
+fn storage_access(access: naga::StorageAccess) -> wgpu::StorageTextureAccess {
+    if access.contains(naga::StorageAccess::ATOMIC) {
+        wgpu::StorageTextureAccess::Atomic
+    } else if access.contains(naga::StorageAccess::LOAD | naga::StorageAccess::STORE) {
+        wgpu::StorageTextureAccess::ReadWrite
+    } else if access.contains(naga::StorageAccess::STORE) {
+        wgpu::StorageTextureAccess::WriteOnly
+    } else {
+        wgpu::StorageTextureAccess::ReadOnly
+    }
+}
+
+fn storage_format(format: naga::StorageFormat) -> wgpu::TextureFormat {
+    use naga::StorageFormat as Sf;
+    use wgpu::TextureFormat as Tf;
+
+    match format {
+        Sf::R8Unorm => Tf::R8Unorm,
+        Sf::R8Snorm => Tf::R8Snorm,
+        Sf::R8Uint => Tf::R8Uint,
+        Sf::R8Sint => Tf::R8Sint,
+        Sf::R16Uint => Tf::R16Uint,
+        Sf::R16Sint => Tf::R16Sint,
+        Sf::R16Float => Tf::R16Float,
+        Sf::Rg8Unorm => Tf::Rg8Unorm,
+        Sf::Rg8Snorm => Tf::Rg8Snorm,
+        Sf::Rg8Uint => Tf::Rg8Uint,
+        Sf::Rg8Sint => Tf::Rg8Sint,
+        Sf::R32Uint => Tf::R32Uint,
+        Sf::R32Sint => Tf::R32Sint,
+        Sf::R32Float => Tf::R32Float,
+        Sf::Rg16Uint => Tf::Rg16Uint,
+        Sf::Rg16Sint => Tf::Rg16Sint,
+        Sf::Rg16Float => Tf::Rg16Float,
+        Sf::Rgba8Unorm => Tf::Rgba8Unorm,
+        Sf::Rgba8Snorm => Tf::Rgba8Snorm,
+        Sf::Rgba8Uint => Tf::Rgba8Uint,
+        Sf::Rgba8Sint => Tf::Rgba8Sint,
+        Sf::Bgra8Unorm => Tf::Bgra8Unorm,
+        Sf::Rgb10a2Uint => Tf::Rgb10a2Uint,
+        Sf::Rgb10a2Unorm => Tf::Rgb10a2Unorm,
+        Sf::Rg11b10Ufloat => Tf::Rg11b10Ufloat,
+        Sf::R64Uint => Tf::R64Uint,
+        Sf::Rg32Uint => Tf::Rg32Uint,
+        Sf::Rg32Sint => Tf::Rg32Sint,
+        Sf::Rg32Float => Tf::Rg32Float,
+        Sf::Rgba16Uint => Tf::Rgba16Uint,
+        Sf::Rgba16Sint => Tf::Rgba16Sint,
+        Sf::Rgba16Float => Tf::Rgba16Float,
+        Sf::Rgba32Uint => Tf::Rgba32Uint,
+        Sf::Rgba32Sint => Tf::Rgba32Sint,
+        Sf::Rgba32Float => Tf::Rgba32Float,
+        Sf::R16Unorm => Tf::R16Unorm,
+        Sf::R16Snorm => Tf::R16Snorm,
+        Sf::Rg16Unorm => Tf::Rg16Unorm,
+        Sf::Rg16Snorm => Tf::Rg16Snorm,
+        Sf::Rgba16Unorm => Tf::Rgba16Unorm,
+        Sf::Rgba16Snorm => Tf::Rgba16Snorm,
+    }
+}
+
+// arguments and struct members with @location
+fn vertex_inputs(
+    module: &naga::Module,
+    entry: &naga::EntryPoint,
+) -> Result<Vec<ReflectedVertexInput>, ShaderReflectError> {
+    let mut inputs = Vec::new();
+    for argument in &entry.function.arguments {
+        let fields = match &module.types[argument.ty].inner {
+            naga::TypeInner::Struct { members, .. } => members
+                .iter()
+                .map(|member| (&member.name, &member.binding, member.ty))
+                .collect(),
+            _ => vec![(&argument.name, &argument.binding, argument.ty)],
+        };
+
+        for (name, binding, ty) in fields {
+            let Some(naga::Binding::Location { location, .. }) = *binding else {
+                continue;
+            };
+            let inner = &module.types[ty].inner;
+            let format =
+                vertex_format(inner).ok_or_else(|| ShaderReflectError::UnsupportedVertexInput {
+                    entry_point: entry.name.clone(),
+                    location,
+                    message: format!("{inner:?}"),
+                })?;
+            inputs.push(ReflectedVertexInput {
+                location,
+                name: name.clone(),
+                format,
+            });
+        }
+    }
+    inputs.sort_by_key(|input| input.location);
+    Ok(inputs)
+}
+
+fn vertex_format(inner: &naga::TypeInner) -> Option<wgpu::VertexFormat> {
+    use naga::{ScalarKind, VectorSize};
+    use wgpu::VertexFormat as Vf;
+
+    let (size, scalar) = match *inner {
+        naga::TypeInner::Scalar(scalar) => (None, scalar),
+        naga::TypeInner::Vector { size, scalar } => (Some(size), scalar),
+        _ => return None,
+    };
+    let format = match (scalar.kind, scalar.width, size) {
+        (ScalarKind::Float, 4, None) => Vf::Float32,
+        (ScalarKind::Float, 4, Some(VectorSize::Bi)) => Vf::Float32x2,
+        (ScalarKind::Float, 4, Some(VectorSize::Tri)) => Vf::Float32x3,
+        (ScalarKind::Float, 4, Some(VectorSize::Quad)) => Vf::Float32x4,
+        (ScalarKind::Float, 2, None) => Vf::Float16,
+        (ScalarKind::Float, 2, Some(VectorSize::Bi)) => Vf::Float16x2,
+        (ScalarKind::Float, 2, Some(VectorSize::Quad)) => Vf::Float16x4,
+        (ScalarKind::Sint, 4, None) => Vf::Sint32,
+        (ScalarKind::Sint, 4, Some(VectorSize::Bi)) => Vf::Sint32x2,
+        (ScalarKind::Sint, 4, Some(VectorSize::Tri)) => Vf::Sint32x3,
+        (ScalarKind::Sint, 4, Some(VectorSize::Quad)) => Vf::Sint32x4,
+        (ScalarKind::Uint, 4, None) => Vf::Uint32,
+        (ScalarKind::Uint, 4, Some(VectorSize::Bi)) => Vf::Uint32x2,
+        (ScalarKind::Uint, 4, Some(VectorSize::Tri)) => Vf::Uint32x3,
+        (ScalarKind::Uint, 4, Some(VectorSize::Quad)) => Vf::Uint32x4,
+        _ => return None,
+    };
+    Some(format)
+}
+
+fn binding_type_name(ty: &wgpu::BindingType) -> &'static str {
+    match ty {
+        wgpu::BindingType::Buffer {
+            ty: wgpu::BufferBindingType::Uniform,
+            ..
+        } => "a uniform buffer",
+        wgpu::BindingType::Buffer { .. } => "a storage buffer",
+        wgpu::BindingType::Sampler(_) => "a sampler",
+        wgpu::BindingType::Texture { .. } => "a texture",
+        wgpu::BindingType::StorageTexture { .. } => "a storage texture",
+        wgpu::BindingType::AccelerationStructure { .. } => "an acceleration structure",
+    }
+}
+
+fn entry_name(entry: &BindGroupEntry) -> &'static str {
+    match entry {
+        BindGroupEntry::Buffer(_) | BindGroupEntry::BufferSlice { .. } => "a buffer",
+        BindGroupEntry::Texture(_) => "a texture view",
+        BindGroupEntry::Sampler(_) => "a sampler",
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{ShaderReflectError, ShaderReflection};
+
+    const SHADER: &str = "
+struct Camera {
+    view_proj: mat4x4<f32>,
+}
+
+struct VertexInput {
+    @location(1) uv: vec2<f32>,
+    @location(0) position: vec3<f32>,
+}
+
+@group(0) @binding(0) var<uniform> camera: Camera;
+@group(0) @binding(1) var<storage, read> instances: array<mat4x4<f32>>;
+@group(1) @binding(0) var albedo: texture_2d<f32>;
+@group(1) @binding(1) var albedo_sampler: sampler;
+@group(1) @binding(2) var shadow_sampler: sampler_comparison;
+@group(2) @binding(0) var output: texture_storage_2d<rgba16float, write>;
+
+@vertex
+fn vs_main(in: VertexInput, @builtin(instance_index) instance: u32) -> @builtin(position) vec4<f32> {
+    return camera.view_proj * instances[instance] * vec4<f32>(in.position, 1.0);
+}
+
+@fragment
+fn fs_main(@builtin(position) position: vec4<f32>) -> @location(0) vec4<f32> {
+    return textureSample(albedo, albedo_sampler, position.xy) * camera.view_proj[0];
+}
+
+@compute @workgroup_size(8, 8, 1)
+fn cs_main(@builtin(global_invocation_id) id: vec3<u32>) {
+    textureStore(output, id.xy, vec4<f32>(1.0));
+}
+";
+
+    #[test]
+    fn test_reflect_shader() {
+        let reflection = ShaderReflection::from_wgsl(SHADER).unwrap();
+        assert_eq!(reflection.group_count(), 3);
+
+        let camera = &reflection.bindings[0];
+        assert_eq!(camera.name.as_deref(), Some("camera"));
+        assert_eq!(
+            camera.visibility,
+            wgpu::ShaderStages::VERTEX | wgpu::ShaderStages::FRAGMENT
+        );
+        assert_eq!(camera.min_size, Some(64));
+        assert_eq!(
+            reflection.bindings[1].ty,
+            wgpu::BindingType::Buffer {
+                ty: wgpu::BufferBindingType::Storage { read_only: true },
+                has_dynamic_offset: false,
+                min_binding_size: None,
+            }
+        );
+        assert_eq!(
+            reflection.bindings[4].ty,
+            wgpu::BindingType::Sampler(wgpu::SamplerBindingType::Comparison)
+        );
+        // declared but unused
+        assert!(reflection.bindings[4].visibility.is_empty());
+        assert_eq!(
+            reflection.bindings[5].ty,
+            wgpu::BindingType::StorageTexture {
+                access: wgpu::StorageTextureAccess::WriteOnly,
+                format: wgpu::TextureFormat::Rgba16Float,
+                view_dimension: wgpu::TextureViewDimension::D2,
+            }
+        );
+
+        let vs_main = reflection.entry_point("vs_main").unwrap();
+        let formats = vs_main
+            .vertex_inputs
+            .iter()
+            .map(|input| (input.location, input.format))
+            .collect::<Vec<_>>();
+        assert_eq!(
+            formats,
+            [
+                (0, wgpu::VertexFormat::Float32x3),
+                (1, wgpu::VertexFormat::Float32x2)
+            ]
+        );
+        assert_eq!(
+            reflection.entry_point("cs_main").unwrap().workgroup_size,
+            [8, 8, 1]
+        );
+
+        assert_eq!(reflection.bind_group_layouts().unwrap().len(), 3);
+        let gap = ShaderReflection::from_wgsl(
+            "@group(0) @binding(1) var<uniform> a: f32;\n@fragment fn fs_main() { _ = a; }",
+        )
+        .unwrap();
+        assert_eq!(
+            gap.bind_group_layout(0).err(),
+            Some(ShaderReflectError::MissingBinding {
+                group: 0,
+                binding: 0
+            })
+        );
+    }
+}
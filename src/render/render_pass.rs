@@ -61,6 +61,7 @@ pub struct RenderPassColorAttachment<'a> {
     view: &'a wgpu::TextureView,
     resolve_target: Option<&'a wgpu::TextureView>,
     ops: wgpu::Operations<wgpu::Color>,
+    msaa_screen: bool,
 }
 
 impl<'a> RenderPassColorAttachment<'a> {
@@ -72,6 +73,7 @@ impl<'a> RenderPassColorAttachment<'a> {
                 load: wgpu::LoadOp::Load,
                 store: wgpu::StoreOp::Store,
             },
+            msaa_screen: false,
         }
     }
 
@@ -91,6 +93,15 @@ impl<'a> RenderPassColorAttachment<'a> {
         self.ops.store = value;
         self
     }
+    /// If the view is the screen and [`render::msaa_samples`] is above 1, draw to the
+    /// multisampled screen target and resolve into the screen
+    ///
+    /// Pipelines must be built with [`render::msaa_samples`]. Resolving overwrites the
+    /// whole screen, so passes without MSAA should draw after the last pass using it
+    pub fn msaa_screen(mut self) -> Self {
+        self.msaa_screen = true;
+        self
+    }
 }
 
 impl<'a> From<RenderPassColorAttachment<'a>> for wgpu::RenderPassColorAttachment<'a> {
@@ -103,6 +114,34 @@ impl<'a> From<RenderPassColorAttachment<'a>> for wgpu::RenderPassColorAttachment
     }
 }
 
+/// Attachments to the screen opting in with [`RenderPassColorAttachment::msaa_screen`]
+/// draw to the multisampled screen target and resolve into it
+fn color_attachments<'a>(
+    render: &'a render::RenderContext,
+    attachments: &'a [Option<RenderPassColorAttachment<'a>>],
+) -> Vec<Option<wgpu::RenderPassColorAttachment<'a>>> {
+    attachments
+        .iter()
+        .map(|att| {
+            att.clone()
+                .map(|att| match (&render.msaa_view, &render.screen_view) {
+                    (Some(msaa_view), Some(screen_view))
+                        if att.msaa_screen
+                            && att.resolve_target.is_none()
+                            && att.view == screen_view =>
+                    {
+                        wgpu::RenderPassColorAttachment {
+                            view: msaa_view,
+                            resolve_target: Some(att.view),
+                            ops: att.ops,
+                        }
+                    }
+                    _ => att.into(),
+                })
+        })
+        .collect()
+}
+
 // TODO very sketchy rn
 pub struct RenderPassBuilder<'a> {
     label: Option<&'a str>,
@@ -131,11 +170,7 @@ impl<'a> RenderPassBuilder<'a> {
     ) -> wgpu::RenderPass<'a> {
         encoder.begin_render_pass(&wgpu::RenderPassDescriptor {
             label: self.label,
-            color_attachments: &self
-                .color_attachments
-                .iter()
-                .map(|att| att.clone().map(RenderPassColorAttachment::into))
-                .collect::<Vec<_>>(),
+            color_attachments: &color_attachments(&ctx.render, self.color_attachments),
             depth_stencil_attachment: self.depth_stencil_attachment,
             timestamp_writes: self
                 .timestamp_writes_label
@@ -153,11 +188,7 @@ impl<'a> RenderPassBuilder<'a> {
     ) {
         let render_pass = encoder.begin_render_pass(&wgpu::RenderPassDescriptor {
             label: self.label,
-            color_attachments: &self
-                .color_attachments
-                .iter()
-                .map(|att| att.clone().map(RenderPassColorAttachment::into))
-                .collect::<Vec<_>>(),
+            color_attachments: &color_attachments(&ctx.render, self.color_attachments),
             depth_stencil_attachment: self.depth_stencil_attachment,
             timestamp_writes: self
                 .timestamp_writes_label
@@ -175,11 +206,7 @@ impl<'a> RenderPassBuilder<'a> {
 
         let render_pass = encoder.begin_render_pass(&wgpu::RenderPassDescriptor {
             label: self.label,
-            color_attachments: &self
-                .color_attachments
-                .iter()
-                .map(|att| att.clone().map(RenderPassColorAttachment::into))
-                .collect::<Vec<_>>(),
+            color_attachments: &color_attachments(&ctx.render, self.color_attachments),
             depth_stencil_attachment: self.depth_stencil_attachment,
             timestamp_writes: self
                 .timestamp_writes_label
@@ -1,6 +1,6 @@
 use super::{ArcHandle, ArcShaderModule};
 use crate::{
-    render::{self, next_id},
+    render::{self, next_id, ShaderDefValue, ShaderDefs, ShaderPreprocessError},
     Context,
 };
 
@@ -12,6 +12,8 @@ use crate::{
 pub struct ShaderBuilder {
     pub label: Option<String>,
     pub source: String,
+    /// Evaluated by the preprocessor, each set of defines is a separate shader
+    pub defines: ShaderDefs,
 }
 
 impl ShaderBuilder {
@@ -19,9 +21,20 @@ impl ShaderBuilder {
         Self {
             source: source.into(),
             label: None,
+            defines: ShaderDefs::new(),
         }
     }
 
+    /// Source after evaluating defines and conditionals
+    pub fn preprocess(&self) -> Result<String, ShaderPreprocessError> {
+        render::preprocess_shader(&self.source, &self.defines)
+    }
+
+    /// Bindings, vertex inputs and workgroup sizes of the preprocessed source
+    pub fn reflect(&self) -> Result<render::ShaderReflection, render::ShaderReflectError> {
+        render::ShaderReflection::from_wgsl(&self.preprocess()?)
+    }
+
     /// Create shader module
     ///
     /// panics if source is invalid
@@ -43,9 +56,14 @@ impl ShaderBuilder {
         &self,
         ctx: &Context,
     ) -> Result<wgpu::ShaderModule, wgpu::Error> {
+        let shader_code = self.preprocess().map_err(|err| wgpu::Error::Validation {
+            description: err.to_string(),
+            source: Box::new(err),
+        })?;
+
         let device = render::device(ctx);
         device.push_error_scope(wgpu::ErrorFilter::Validation);
-        let shader = self.build_non_arc(ctx);
+        let shader = self.create_module(ctx, shader_code);
         pollster::block_on(async {
             if let Some(err) = device.pop_error_scope().await {
                 Err(err)
@@ -56,17 +74,18 @@ impl ShaderBuilder {
     }
 
     pub(crate) fn build_non_arc(&self, ctx: &Context) -> wgpu::ShaderModule {
-        let device = render::device(ctx);
-        let mut shader_code = String::with_capacity(self.source.len());
-
-        shader_code.push_str(&self.source);
+        let shader_code = self
+            .preprocess()
+            .unwrap_or_else(|err| panic!("could not preprocess shader {:?}: {}", self.label, err));
+        self.create_module(ctx, shader_code)
+    }
 
-        let module = device.create_shader_module(wgpu::ShaderModuleDescriptor {
+    fn create_module(&self, ctx: &Context, shader_code: String) -> wgpu::ShaderModule {
+        let device = render::device(ctx);
+        device.create_shader_module(wgpu::ShaderModuleDescriptor {
             label: self.label.as_deref(),
             source: wgpu::ShaderSource::Wgsl(shader_code.into()),
-        });
-
-        module
+        })
     }
 }
 
@@ -79,4 +98,12 @@ impl ShaderBuilder {
         self.source = value;
         self
     }
+    pub fn define(mut self, name: impl Into<String>, value: impl Into<ShaderDefValue>) -> Self {
+        self.defines.insert(name.into(), value.into());
+        self
+    }
+    pub fn defines(mut self, value: ShaderDefs) -> Self {
+        self.defines = value;
+        self
+    }
 }
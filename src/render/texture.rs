@@ -2,6 +2,7 @@ use crate::{
     render::{self, next_id, ArcSampler, ArcTexture, ArcTextureView},
     Context,
 };
+use std::borrow::Cow;
 
 #[derive(Debug, Clone)]
 pub struct Image {
@@ -51,7 +52,7 @@ impl SamplerBuilder {
             min_filter: wgpu::FilterMode::Linear,
             mip_map_filter: wgpu::FilterMode::Linear,
             lod_min_clamp_u32: 0,
-            lod_max_clamp_u32: 0,
+            lod_max_clamp_u32: 320,
             anisotropy_clamp: 1,
             compare: None,
             border_color: None,
@@ -166,10 +167,38 @@ impl SamplerBuilder {
 // TODO use struct notation?
 #[derive(Debug, Clone, Eq, PartialEq, Hash)]
 pub enum TextureSource {
-    /// (width, height, bytes)
+    /// (width, height, bytes), bytes of all mip levels largest first
     Data(u32, u32, Vec<u8>),
+    /// (width, height, levels), bytes of each mip level largest first,
+    /// with the array layers of a level back to back
+    ///
+    /// Block compressed levels are padded to whole blocks
+    Levels(u32, u32, Vec<Vec<u8>>),
     /// (width, height)
     Empty(u32, u32),
+    /// 2D array, one image per layer
+    Layers {
+        width: u32,
+        height: u32,
+        layers: Vec<Vec<u8>>,
+    },
+    /// Cubemap faces in +X, -X, +Y, -Y, +Z, -Z order
+    Cube { size: u32, faces: [Vec<u8>; 6] },
+    /// Latitude-longitude panorama converted to a cubemap on the GPU,
+    /// see [`render::equirectangular_to_cubemap`]
+    Equirectangular {
+        width: u32,
+        height: u32,
+        data: Vec<u8>,
+        face_size: u32,
+    },
+    /// 3D texture, depth slices back to back
+    Volume {
+        width: u32,
+        height: u32,
+        depth: u32,
+        data: Vec<u8>,
+    },
 }
 
 #[derive(Debug, Clone, Eq, PartialEq, Hash)]
@@ -181,6 +210,7 @@ pub struct TextureBuilder {
     format: wgpu::TextureFormat,
     depth_or_array_layers: u32,
     mip_level_count: u32,
+    generate_mipmaps: bool,
     sample_count: u32,
     dimension: wgpu::TextureDimension,
     view_formats: Vec<wgpu::TextureFormat>,
@@ -195,6 +225,7 @@ impl TextureBuilder {
             format: wgpu::TextureFormat::Rgba8UnormSrgb,
             depth_or_array_layers: 1,
             mip_level_count: 1,
+            generate_mipmaps: false,
             sample_count: 1,
             dimension: wgpu::TextureDimension::D2,
             view_formats: Vec::new(),
@@ -203,7 +234,6 @@ impl TextureBuilder {
 
     pub fn build(&self, ctx: &mut Context) -> render::ArcTexture {
         let device = render::device(ctx);
-        let queue = render::queue(ctx);
         match self.source {
             TextureSource::Empty(width, height) => {
                 let texture = device.create_texture(&wgpu::TextureDescriptor {
@@ -224,45 +254,325 @@ impl TextureBuilder {
                 ArcTexture::new(ctx, texture)
             }
             TextureSource::Data(width, height, ref bytes) => {
-                let texture = device.create_texture(&wgpu::TextureDescriptor {
-                    label: self.label.as_deref(),
-                    size: wgpu::Extent3d {
-                        width,
-                        height,
-                        depth_or_array_layers: self.depth_or_array_layers,
-                    },
-                    mip_level_count: self.mip_level_count,
-                    sample_count: self.sample_count,
-                    dimension: self.dimension,
-                    format: self.format,
-                    usage: self.usage,
-                    view_formats: &self.view_formats,
-                });
-                queue.write_texture(
-                    wgpu::TexelCopyTextureInfo {
-                        texture: &texture,
-                        mip_level: 0,
-                        origin: wgpu::Origin3d::ZERO,
-                        aspect: wgpu::TextureAspect::All,
+                let size = self.size(width, height);
+                let mip_level_count = self.data_mip_level_count(size, 1);
+                // as many mip levels as the data contains
+                let mut levels = Vec::new();
+                let mut offset = 0;
+                for mip_level in 0..mip_level_count {
+                    let level_size = size.mip_level_size(mip_level, self.dimension);
+                    let level_len = level_data_size(self.format, level_size);
+                    let Some(level_bytes) = bytes.get(offset..offset + level_len) else {
+                        break;
+                    };
+                    levels.push(Cow::Borrowed(level_bytes));
+                    offset += level_len;
+                }
+
+                self.build_levels(ctx, size, mip_level_count, levels)
+            }
+            TextureSource::Levels(width, height, ref levels) => {
+                let size = self.size(width, height);
+                let mip_level_count = self.data_mip_level_count(size, levels.len() as u32);
+                let levels = levels
+                    .iter()
+                    .map(|level| Cow::Borrowed(level.as_slice()))
+                    .collect();
+
+                self.build_levels(ctx, size, mip_level_count, levels)
+            }
+            TextureSource::Layers {
+                width,
+                height,
+                ref layers,
+            } => {
+                let size = wgpu::Extent3d {
+                    width,
+                    height,
+                    depth_or_array_layers: layers.len().max(1) as u32,
+                };
+                let mip_level_count = self.data_mip_level_count(size, 1);
+                let layer_len = level_data_size(
+                    self.format,
+                    wgpu::Extent3d {
+                        depth_or_array_layers: 1,
+                        ..size
                     },
-                    bytes,
-                    wgpu::TexelCopyBufferLayout {
-                        offset: 0,
-                        bytes_per_row: self // TODO check if correct
-                            .format
-                            .block_copy_size(Some(wgpu::TextureAspect::All))
-                            .map(|n| width * n),
-                        rows_per_image: Some(height),
+                );
+                // textures need at least one layer and all layers the same size
+                let levels = if layers.is_empty() {
+                    tracing::error!("no layers for {:?}, leaving it empty", self.label);
+                    Vec::new()
+                } else if let Some(layer) = layers.iter().position(|l| l.len() != layer_len) {
+                    tracing::error!(
+                        "layer {} of {:?} is {} bytes, expected {} for {}x{}, leaving it empty",
+                        layer,
+                        self.label,
+                        layers[layer].len(),
+                        layer_len,
+                        width,
+                        height
+                    );
+                    Vec::new()
+                } else {
+                    vec![Cow::Owned(layers.concat())]
+                };
+
+                self.build_levels(ctx, size, mip_level_count, levels)
+            }
+            TextureSource::Cube { size, ref faces } => {
+                let size = wgpu::Extent3d {
+                    width: size,
+                    height: size,
+                    depth_or_array_layers: 6,
+                };
+                let mip_level_count = self.data_mip_level_count(size, 1);
+                let levels = vec![Cow::Owned(faces.concat())];
+
+                self.build_levels(ctx, size, mip_level_count, levels)
+            }
+            TextureSource::Equirectangular {
+                width,
+                height,
+                ref data,
+                face_size,
+            } => self.build_equirectangular(ctx, width, height, data, face_size),
+            TextureSource::Volume {
+                width,
+                height,
+                depth,
+                ref data,
+            } => {
+                let size = wgpu::Extent3d {
+                    width,
+                    height,
+                    depth_or_array_layers: depth,
+                };
+                let builder = Self {
+                    dimension: wgpu::TextureDimension::D3,
+                    // mipmaps are only generated for 2D textures
+                    generate_mipmaps: false,
+                    ..self.clone()
+                };
+                let mip_level_count = builder.data_mip_level_count(size, 1);
+
+                builder.build_levels(ctx, size, mip_level_count, vec![Cow::Borrowed(data)])
+            }
+        }
+    }
+
+    /// View dimension the source needs if it differs from the default of the texture,
+    /// e.g. cube for 6 layered textures
+    pub fn view_dimension(&self) -> Option<wgpu::TextureViewDimension> {
+        match self.source {
+            TextureSource::Cube { .. } | TextureSource::Equirectangular { .. } => {
+                Some(wgpu::TextureViewDimension::Cube)
+            }
+            TextureSource::Layers { .. } => Some(wgpu::TextureViewDimension::D2Array),
+            _ => None,
+        }
+    }
+
+    /// False if the device can't create the texture and it can't be decompressed on the CPU
+    pub fn is_supported(&self, ctx: &Context) -> bool {
+        render::texture_format_supported(ctx, self.format)
+            || render::decompressed_format(self.format).is_some()
+    }
+
+    fn size(&self, width: u32, height: u32) -> wgpu::Extent3d {
+        wgpu::Extent3d {
+            width,
+            height,
+            depth_or_array_layers: self.depth_or_array_layers,
+        }
+    }
+
+    fn data_mip_level_count(&self, size: wgpu::Extent3d, data_levels: u32) -> u32 {
+        match self.generate_mipmaps && self.mip_level_count == 1 {
+            true => size.max_mips(self.dimension),
+            false => self.mip_level_count.max(data_levels),
+        }
+    }
+
+    fn build_levels(
+        &self,
+        ctx: &mut Context,
+        size: wgpu::Extent3d,
+        mip_level_count: u32,
+        mut levels: Vec<Cow<'_, [u8]>>,
+    ) -> render::ArcTexture {
+        let mut format = self.format;
+        if !render::texture_format_supported(ctx, format) {
+            if let Some(decompressed) = render::decompressed_format(format) {
+                tracing::warn!(
+                    "{:?} is not supported by the device, decompressing {:?} on the CPU",
+                    format,
+                    self.label
+                );
+                // never upload part of the levels, the texture is left empty instead
+                levels = decompress_levels(format, self.dimension, size, &levels).unwrap_or_else(
+                    |mip_level| {
+                        tracing::error!(
+                            "could not decompress mip level {} of {:?}, leaving it empty",
+                            mip_level,
+                            self.label
+                        );
+                        Vec::new()
                     },
-                    texture.size(),
                 );
+                format = decompressed;
+            }
+        }
+        levels.truncate(mip_level_count as usize);
+
+        let generate_mipmaps = self.generate_mipmaps && levels.len() < mip_level_count as usize;
+        let usage = match generate_mipmaps {
+            true => self.usage | wgpu::TextureUsages::RENDER_ATTACHMENT,
+            false => self.usage,
+        };
+        let texture = render::device(ctx).create_texture(&wgpu::TextureDescriptor {
+            label: self.label.as_deref(),
+            size,
+            mip_level_count,
+            sample_count: self.sample_count,
+            dimension: self.dimension,
+            format,
+            usage,
+            view_formats: &self.view_formats,
+        });
+        for (mip_level, bytes) in levels.iter().enumerate() {
+            write_level(ctx, &texture, mip_level as u32, bytes);
+        }
 
-                ArcTexture::new(ctx, texture)
+        if generate_mipmaps && !levels.is_empty() {
+            if let Err(err) = render::generate_mipmaps(ctx, &texture, levels.len() as u32 - 1) {
+                tracing::error!("could not generate mipmaps for {:?}: {}", self.label, err);
             }
         }
+
+        ArcTexture::new(ctx, texture)
     }
 }
 
+impl TextureBuilder {
+    fn build_equirectangular(
+        &self,
+        ctx: &mut Context,
+        width: u32,
+        height: u32,
+        data: &[u8],
+        face_size: u32,
+    ) -> render::ArcTexture {
+        let source = render::device(ctx).create_texture(&wgpu::TextureDescriptor {
+            label: Some("equirectangular source"),
+            size: wgpu::Extent3d {
+                width,
+                height,
+                depth_or_array_layers: 1,
+            },
+            mip_level_count: 1,
+            sample_count: 1,
+            dimension: wgpu::TextureDimension::D2,
+            format: self.format,
+            usage: wgpu::TextureUsages::TEXTURE_BINDING | wgpu::TextureUsages::COPY_DST,
+            view_formats: &[],
+        });
+        write_level(ctx, &source, 0, data);
+
+        let size = wgpu::Extent3d {
+            width: face_size,
+            height: face_size,
+            depth_or_array_layers: 6,
+        };
+        let texture = render::device(ctx).create_texture(&wgpu::TextureDescriptor {
+            label: self.label.as_deref(),
+            size,
+            mip_level_count: self.data_mip_level_count(size, 1),
+            sample_count: 1,
+            dimension: wgpu::TextureDimension::D2,
+            format: self.format,
+            usage: self.usage | wgpu::TextureUsages::RENDER_ATTACHMENT,
+            view_formats: &self.view_formats,
+        });
+        if let Err(err) = render::equirectangular_to_cubemap(ctx, &source, &texture) {
+            tracing::error!("could not convert {:?} to a cubemap: {}", self.label, err);
+        } else if let Err(err) = render::generate_mipmaps(ctx, &texture, 0) {
+            tracing::error!("could not generate mipmaps for {:?}: {}", self.label, err);
+        }
+
+        ArcTexture::new(ctx, texture)
+    }
+}
+
+/// Bytes of a mip level with all layers, block compressed formats are padded to whole blocks
+pub(crate) fn level_data_size(format: wgpu::TextureFormat, size: wgpu::Extent3d) -> usize {
+    let (block_width, block_height) = format.block_dimensions();
+    let block_size = format
+        .block_copy_size(Some(wgpu::TextureAspect::All))
+        .unwrap_or(0);
+    (size.width.div_ceil(block_width)
+        * size.height.div_ceil(block_height)
+        * size.depth_or_array_layers
+        * block_size) as usize
+}
+
+fn write_level(ctx: &Context, texture: &wgpu::Texture, mip_level: u32, bytes: &[u8]) {
+    let format = texture.format();
+    let size = texture
+        .size()
+        .mip_level_size(mip_level, texture.dimension());
+    let (block_width, block_height) = format.block_dimensions();
+    let block_size = format
+        .block_copy_size(Some(wgpu::TextureAspect::All))
+        .unwrap_or(0);
+
+    render::queue(ctx).write_texture(
+        wgpu::TexelCopyTextureInfo {
+            texture,
+            mip_level,
+            origin: wgpu::Origin3d::ZERO,
+            aspect: wgpu::TextureAspect::All,
+        },
+        bytes,
+        wgpu::TexelCopyBufferLayout {
+            offset: 0,
+            bytes_per_row: Some(size.width.div_ceil(block_width) * block_size),
+            rows_per_image: Some(size.height.div_ceil(block_height)),
+        },
+        // partial blocks of small mip levels are copied whole
+        size.physical_size(format),
+    );
+}
+
+/// Decompressed levels, or the first mip level which could not be decompressed
+fn decompress_levels(
+    format: wgpu::TextureFormat,
+    dimension: wgpu::TextureDimension,
+    size: wgpu::Extent3d,
+    levels: &[Cow<'_, [u8]>],
+) -> Result<Vec<Cow<'static, [u8]>>, u32> {
+    let mut decompressed = Vec::with_capacity(levels.len());
+    for (mip_level, bytes) in levels.iter().enumerate() {
+        let level_size = size.mip_level_size(mip_level as u32, dimension);
+        let layer_len = level_data_size(
+            format,
+            wgpu::Extent3d {
+                depth_or_array_layers: 1,
+                ..level_size
+            },
+        );
+        let mut level = Vec::new();
+        for layer in bytes.chunks(layer_len.max(1)) {
+            let rgba =
+                render::decompress_blocks(format, level_size.width, level_size.height, layer)
+                    .ok_or(mip_level as u32)?;
+            level.extend_from_slice(&rgba);
+        }
+        decompressed.push(Cow::Owned(level));
+    }
+    Ok(decompressed)
+}
+
 impl TextureBuilder {
     pub fn label(mut self, value: impl Into<String>) -> Self {
         self.label = Some(value.into());
@@ -287,6 +597,13 @@ impl TextureBuilder {
         self.mip_level_count = value;
         self
     }
+    /// Generate the mip levels missing from the data on the GPU
+    ///
+    /// Creates the full mip chain unless [`mip_level_count`](Self::mip_level_count) is set
+    pub fn generate_mipmaps(mut self, value: bool) -> Self {
+        self.generate_mipmaps = value;
+        self
+    }
     pub fn sample_count(mut self, value: u32) -> Self {
         self.sample_count = value;
         self
@@ -364,6 +681,27 @@ impl TextureViewBuilder {
     }
 }
 
+impl TextureViewBuilder {
+    /// View all 6 layers as a cube
+    pub fn cube(self) -> Self {
+        self.dimension(wgpu::TextureViewDimension::Cube)
+    }
+    /// View layers in multiples of 6 as an array of cubes
+    pub fn cube_array(self) -> Self {
+        self.dimension(wgpu::TextureViewDimension::CubeArray)
+    }
+    /// View the layers as a 2D array, also when there is only one
+    pub fn array(self) -> Self {
+        self.dimension(wgpu::TextureViewDimension::D2Array)
+    }
+    /// View a single layer, e.g. a cube face, as a 2D texture
+    pub fn layer(self, index: u32) -> Self {
+        self.dimension(wgpu::TextureViewDimension::D2)
+            .base_array_layer(index)
+            .array_layer_count(1)
+    }
+}
+
 impl TextureViewBuilder {
     pub fn label(mut self, value: impl Into<String>) -> Self {
         self.label = Some(value.into());
@@ -403,6 +741,29 @@ impl TextureViewBuilder {
     }
 }
 
+/// Bytes of GPU memory used by a texture, including all mip levels and samples
+///
+/// An estimate, drivers may pad or compress textures
+pub fn texture_memory_size(texture: &wgpu::Texture) -> u64 {
+    let format = texture.format();
+    let (block_width, block_height) = format.block_dimensions();
+    // combined depth stencil formats have no single block size
+    let block_size = format
+        .block_copy_size(None)
+        .or_else(|| format.block_copy_size(Some(wgpu::TextureAspect::DepthOnly)))
+        .unwrap_or(4) as u64;
+
+    let mip_bytes = (0..texture.mip_level_count())
+        .map(|level| {
+            let size = texture.size().mip_level_size(level, texture.dimension());
+            let blocks_x = size.width.div_ceil(block_width) as u64;
+            let blocks_y = size.height.div_ceil(block_height) as u64;
+            blocks_x * blocks_y * size.depth_or_array_layers as u64 * block_size
+        })
+        .sum::<u64>();
+    mip_bytes * texture.sample_count() as u64
+}
+
 //
 // Texture with view
 //
@@ -434,7 +795,11 @@ impl GpuImage {
     pub fn from_image(ctx: &mut Context, image: Image) -> Self {
         let texture = image.texture.clone().build(ctx);
         let sampler = image.sampler.clone().build(ctx);
-        let view = render::TextureViewBuilder::new(texture.clone()).build(ctx);
+        let mut view = render::TextureViewBuilder::new(texture.clone());
+        if let Some(dimension) = image.texture.view_dimension() {
+            view = view.dimension(dimension);
+        }
+        let view = view.build(ctx);
         Self {
             texture,
             view,
@@ -459,6 +824,14 @@ impl GpuImage {
     pub fn sampler_ref(&self) -> &wgpu::Sampler {
         &self.sampler
     }
+    /// Bytes of GPU memory used by the texture
+    pub fn memory_size(&self) -> u64 {
+        texture_memory_size(&self.texture)
+    }
+    /// Regenerate all mip levels from level 0, e.g. after rendering to it
+    pub fn generate_mipmaps(&self, ctx: &mut Context) -> Result<(), render::GenerateMipmapsError> {
+        render::generate_mipmaps(ctx, &self.texture, 0)
+    }
 }
 
 impl render::ArcTexture {
@@ -0,0 +1,119 @@
+use clap::Parser;
+use gbase::{
+    asset::{self, AssetProcessors, ProcessedAssetCache},
+    filesystem,
+};
+use std::{
+    fs,
+    path::{Path, PathBuf},
+};
+
+/// Process a whole assets folder ahead of time, e.g. for release and wasm builds
+///
+/// Processed assets are written next to their source as `<path>.processed`,
+/// which loaders prefer over the source. Other files are copied unchanged
+#[derive(clap::Parser)]
+struct Cli {
+    #[clap(short, long)]
+    source: PathBuf,
+    #[clap(short, long)]
+    destination: PathBuf,
+
+    /// Processed assets are reused from here if their source did not change
+    #[clap(short, long, default_value = "target/processed_assets")]
+    cache: PathBuf,
+
+    /// Leave out the sources of processed assets, only do this if nothing reads them directly
+    #[clap(long)]
+    strip_sources: bool,
+}
+
+fn main() {
+    env_logger::builder()
+        .filter_level(log::LevelFilter::Info)
+        .init();
+
+    let cli = Cli::parse();
+    let mut processors = AssetProcessors::default();
+    processors.register(gbase_utils::GltfProcessor::default(), &["glb"]);
+    let cache = ProcessedAssetCache::new(&cli.cache, processors);
+
+    // dependencies, e.g. shader imports, are relative to the source folder
+    let read = |path: &Path| {
+        fs::read(cli.source.join(path)).map_err(|err| match err.kind() {
+            std::io::ErrorKind::NotFound => filesystem::LoadFileError::FileNotFound,
+            _ => filesystem::LoadFileError::Other(Box::new(err)),
+        })
+    };
+
+    //
+    // collect files
+    //
+
+    let mut files = Vec::new();
+    let mut stack = vec![cli.source.clone()];
+    while let Some(path) = stack.pop() {
+        let metadata = fs::metadata(&path).expect("could not read metadata");
+        if metadata.is_dir() {
+            for entry in fs::read_dir(&path).expect("could not read dir") {
+                let entry = entry.expect("could not open dir entry");
+                stack.push(entry.path());
+            }
+        }
+
+        if metadata.is_file() {
+            files.push(path);
+        }
+    }
+
+    //
+    // process
+    //
+
+    let (mut processed, mut copied, mut failed) = (0, 0, 0);
+    for path in files {
+        let relative = path
+            .strip_prefix(&cli.source)
+            .expect("could not get relative path");
+        let source = fs::read(&path).expect("could not read source file");
+        let meta = fs::read_to_string(asset::meta_path(&path)).ok();
+        let mut outputs = Vec::new();
+        match cache.process(relative, &source, meta.as_deref(), &read) {
+            Some(Ok(output)) => {
+                processed += 1;
+                outputs.push((asset::processed_path(relative), output.data));
+                if !cli.strip_sources {
+                    outputs.push((relative.to_path_buf(), source));
+                }
+            }
+            Some(Err(err)) => {
+                log::error!("{}, copying source only", err);
+                failed += 1;
+                outputs.push((relative.to_path_buf(), source));
+            }
+            None => {
+                copied += 1;
+                outputs.push((relative.to_path_buf(), source));
+            }
+        }
+
+        for (destination, bytes) in outputs {
+            let destination = cli.destination.join(destination);
+            if let Some(parent) = destination.parent() {
+                fs::create_dir_all(parent).expect("could not create destination dir");
+            }
+            fs::write(&destination, bytes).expect("could not write destination file");
+        }
+        log::info!("cooked {:?}", relative);
+    }
+
+    log::info!(
+        "processed {} assets, copied {} files, {} failed",
+        processed,
+        copied,
+        failed
+    );
+    if failed > 0 {
+        std::process::exit(1);
+    }
+}
@@ -0,0 +1,95 @@
+use clap::Parser;
+use gbase::filesystem::{PackBuilder, PackCompression};
+use std::{fs, path::PathBuf};
+
+#[derive(Clone, Copy, clap::ValueEnum)]
+enum Compression {
+    None,
+    Deflate,
+    Zstd,
+}
+
+/// Build a gbase pack from an assets directory
+#[derive(clap::Parser)]
+struct Cli {
+    #[clap(short, long)]
+    source: PathBuf,
+    #[clap(short, long)]
+    output: PathBuf,
+
+    /// Only pack files matching any of these globs, relative to the source
+    #[clap(short, long)]
+    include: Vec<glob::Pattern>,
+    /// Skip files matching any of these globs, relative to the source
+    #[clap(short, long)]
+    exclude: Vec<glob::Pattern>,
+
+    #[clap(short, long, value_enum, default_value_t = Compression::Zstd)]
+    compression: Compression,
+}
+
+fn main() {
+    env_logger::builder()
+        .filter_level(log::LevelFilter::Info)
+        .init();
+
+    let cli = Cli::parse();
+    let compression = match cli.compression {
+        Compression::None => PackCompression::None,
+        Compression::Deflate => PackCompression::Deflate,
+        Compression::Zstd => PackCompression::Zstd,
+    };
+
+    //
+    // collect files
+    //
+
+    let mut files = Vec::new();
+    let mut stack = vec![cli.source.clone()];
+    while let Some(path) = stack.pop() {
+        let metadata = fs::metadata(&path).expect("could not read metadata");
+        if metadata.is_dir() {
+            for entry in fs::read_dir(&path).expect("could not read dir") {
+                let entry = entry.expect("could not open dir entry");
+                stack.push(entry.path());
+            }
+        }
+
+        if metadata.is_file() {
+            let relative = path
+                .strip_prefix(&cli.source)
+                .expect("could not get relative path")
+                .to_path_buf();
+            let included = cli.include.is_empty()
+                || cli.include.iter().any(|glob| glob.matches_path(&relative));
+            let excluded = cli.exclude.iter().any(|glob| glob.matches_path(&relative));
+            if included && !excluded {
+                files.push(relative);
+            }
+        }
+    }
+    files.sort();
+
+    //
+    // pack
+    //
+
+    let mut pack = PackBuilder::new().compression(compression);
+    let mut size = 0;
+    for relative in &files {
+        let data = fs::read(cli.source.join(relative)).expect("could not read source file");
+        size += data.len();
+        pack.add_file(relative, data);
+        log::info!("packed {:?}", relative);
+    }
+
+    let bytes = pack.build().expect("could not build pack");
+    fs::write(&cli.output, &bytes).expect("could not write pack");
+    log::info!(
+        "packed {} files into {:?}, {} -> {} bytes",
+        files.len(),
+        cli.output,
+        size,
+        bytes.len()
+    );
+}
@@ -22,6 +22,7 @@ pub struct GizmoRenderer {
     depth_buffer: render::DepthBuffer, // depth buffer only for gizmos
 
     resolution: u32,
+    sample_count: u32,
 }
 
 const GIZMO_MAX_VERTICES: usize = 100000;
@@ -66,6 +67,7 @@ impl GizmoRenderer {
             depth_buffer,
             bindgroup_layout,
             resolution: GIZMO_RESOLUTION,
+            sample_count: 1,
         }
     }
 
@@ -92,11 +94,15 @@ impl GizmoRenderer {
                 .single_target(render::ColorTargetState::new().format(view_format))
                 .depth_stencil(self.depth_buffer.depth_stencil_state())
                 .topology(wgpu::PrimitiveTopology::LineList)
+                .sample_count(self.sample_count)
                 .build(ctx);
 
         render::RenderPassBuilder::new()
             .label("gizmos")
-            .color_attachments(&[Some(render::RenderPassColorAttachment::new(view))])
+            .color_attachments(&[Some(match self.sample_count > 1 {
+                true => render::RenderPassColorAttachment::new(view).msaa_screen(),
+                false => render::RenderPassColorAttachment::new(view),
+            })])
             .depth_stencil_attachment(self.depth_buffer.depth_render_attachment_clear())
             .build_run_submit(ctx, |mut pass| {
                 pass.set_pipeline(&pipeline);
@@ -118,6 +124,21 @@ impl GizmoRenderer {
     pub fn set_resolution(&mut self, resolution: u32) {
         self.resolution = resolution;
     }
+
+    /// Samples per pixel of the view, defaults to 1
+    ///
+    /// Use [`render::msaa_samples`] to draw to the screen with MSAA
+    pub fn set_msaa(&mut self, ctx: &mut Context, sample_count: u32) {
+        if sample_count == self.sample_count {
+            return;
+        }
+        let size = self.depth_buffer.framebuffer().size();
+        self.depth_buffer = render::DepthBufferBuilder::new()
+            .size(size.width, size.height)
+            .sample_count(sample_count)
+            .build(ctx);
+        self.sample_count = sample_count;
+    }
 }
 
 impl GizmoRenderer {
@@ -1,11 +1,17 @@
 use crate::{texture_builder_from_image_bytes, Transform3D};
 use gbase::{
-    asset::{Asset, AssetCache, AssetHandle, LoadContext},
+    asset::{Asset, AssetCache, AssetHandle, AssetProcessor, LoadContext, ProcessContext},
+    filesystem,
     glam::{Quat, Vec3},
     render::{self, Image, Mesh, SamplerBuilder, VertexAttributeId},
     tracing, wgpu,
 };
-use std::collections::{BTreeMap, BTreeSet, HashMap};
+use serde::{Deserialize, Serialize};
+use std::{
+    borrow::Cow,
+    collections::{BTreeMap, BTreeSet, HashMap, HashSet},
+    path::Path,
+};
 
 pub struct GltfLoadCache {
     nodes: HashMap<usize, AssetHandle<GltfNode>>,
@@ -18,11 +24,17 @@ pub struct GltfLoadCache {
 
     images: HashMap<usize, AssetHandle<Image>>,
     single_pixel_images: HashMap<[u8; 4], AssetHandle<Image>>,
+
+    // names are not unique in gltf, only these are used as labels
+    unique_node_names: HashSet<Box<str>>,
+    unique_mesh_names: HashSet<Box<str>>,
 }
 
 impl GltfLoadCache {
-    fn new() -> Self {
+    fn new(info: &gltf::Gltf) -> Self {
         Self {
+            unique_node_names: unique_names(info.nodes().map(|node| node.name())),
+            unique_mesh_names: unique_names(info.meshes().map(|mesh| mesh.name())),
             nodes: HashMap::new(),
             named_nodes: HashMap::new(),
             meshes: HashMap::new(),
@@ -41,9 +53,9 @@ pub fn parse_gltf_primitives(
     bytes: &[u8],
     required_attributes: Option<&BTreeSet<VertexAttributeId>>,
 ) -> Vec<GltfPrimitive> {
-    let mut gltf_cache = GltfLoadCache::new();
     let glb = gltf::Glb::from_slice(bytes).expect("could not import glb from slice");
     let info = gltf::Gltf::from_slice(bytes).expect("could not import info from slice");
+    let mut gltf_cache = GltfLoadCache::new(&info);
     let buffer = glb.bin.expect("could not get glb buffer");
 
     let mut primitives = Vec::new();
@@ -70,9 +82,9 @@ pub fn parse_gltf_primitives(
 }
 
 pub fn parse_gltf_file(load_ctx: &LoadContext, bytes: &[u8]) -> Gltf {
-    let mut gltf_cache = GltfLoadCache::new();
     let glb = gltf::Glb::from_slice(bytes).expect("could not import glb from slice");
     let info = gltf::Gltf::from_slice(bytes).expect("could not import info from slice");
+    let mut gltf_cache = GltfLoadCache::new(&info);
     let buffer = glb.bin.expect("could not get glb buffer");
 
     // let mut nodes = Vec::new();
@@ -129,15 +141,22 @@ fn parse_gltf_node(
         children.push(child_node_handle);
     }
 
-    let node_handle = load_ctx.insert(GltfNode {
+    let gltf_node = GltfNode {
         name,
         mesh,
         transform,
         children,
-    });
+    };
+    let unique_name = node
+        .name()
+        .filter(|name| gltf_cache.unique_node_names.contains(*name));
+    if let Some(alias) = gltf_name_label("Node", unique_name) {
+        load_ctx.insert_labeled(&alias, gltf_node.clone());
+    }
+    let node_handle = load_ctx.insert_labeled(&format!("Node/{}", node.index()), gltf_node);
 
     gltf_cache.nodes.insert(node.index(), node_handle.clone());
-    if let Some(name) = node.name() {
+    if let Some(name) = unique_name {
         gltf_cache
             .named_nodes
             .insert(Box::from(name), node_handle.clone());
@@ -167,8 +186,15 @@ fn parse_gltf_mesh(
         primitives.push(primitive);
     }
 
-    let mesh_handle = load_ctx.insert(GltfMesh { name, primitives });
-    if let Some(name) = mesh.name() {
+    let gltf_mesh = GltfMesh { name, primitives };
+    let unique_name = mesh
+        .name()
+        .filter(|name| gltf_cache.unique_mesh_names.contains(*name));
+    if let Some(alias) = gltf_name_label("Mesh", unique_name) {
+        load_ctx.insert_labeled(&alias, gltf_mesh.clone());
+    }
+    let mesh_handle = load_ctx.insert_labeled(&format!("Mesh/{}", mesh.index()), gltf_mesh);
+    if let Some(name) = unique_name {
         gltf_cache
             .named_meshes
             .insert(Box::from(name), mesh_handle.clone());
@@ -185,14 +211,8 @@ fn parse_gltf_primitive(
     mesh_name: &str,
     required_attributes: Option<&BTreeSet<VertexAttributeId>>,
 ) -> GltfPrimitive {
-    let primitive_topology = match primitive.mode() {
-        gltf::mesh::Mode::Points => wgpu::PrimitiveTopology::PointList,
-        gltf::mesh::Mode::Lines => wgpu::PrimitiveTopology::LineList,
-        gltf::mesh::Mode::LineStrip => wgpu::PrimitiveTopology::LineStrip,
-        gltf::mesh::Mode::Triangles => wgpu::PrimitiveTopology::TriangleList,
-        gltf::mesh::Mode::TriangleStrip => wgpu::PrimitiveTopology::TriangleStrip,
-        mode => panic!("primite mode {:?} not supported", mode),
-    };
+    let primitive_topology = primitive_topology(primitive.mode())
+        .unwrap_or_else(|| panic!("primite mode {:?} not supported", primitive.mode()));
 
     let mut attributes = BTreeMap::new();
     for (sem, attr) in primitive.attributes() {
@@ -331,7 +351,8 @@ pub fn parse_gltf_material(
     material: gltf::Material<'_>,
 ) -> AssetHandle<Material> {
     // TODO: have default material on None?
-    if let Some(index) = material.index() {
+    let material_index = material.index();
+    if let Some(index) = material_index {
         if let Some(material) = gltf_cache.materials.get(&index) {
             return material.clone();
         }
@@ -366,29 +387,41 @@ pub fn parse_gltf_material(
         let texture_buffer = &buffer[offset..offset + length];
         let sampler = texture.sampler();
 
+        // (min filter, mipmap filter), no mipmap filter samples level 0 only
+        let (min_filter, mipmap_filter) = match sampler.min_filter() {
+            None | Some(gltf::texture::MinFilter::LinearMipmapLinear) => {
+                (wgpu::FilterMode::Linear, Some(wgpu::FilterMode::Linear))
+            }
+            Some(gltf::texture::MinFilter::LinearMipmapNearest) => {
+                (wgpu::FilterMode::Linear, Some(wgpu::FilterMode::Nearest))
+            }
+            Some(gltf::texture::MinFilter::NearestMipmapLinear) => {
+                (wgpu::FilterMode::Nearest, Some(wgpu::FilterMode::Linear))
+            }
+            Some(gltf::texture::MinFilter::NearestMipmapNearest) => {
+                (wgpu::FilterMode::Nearest, Some(wgpu::FilterMode::Nearest))
+            }
+            Some(gltf::texture::MinFilter::Linear) => (wgpu::FilterMode::Linear, None),
+            Some(gltf::texture::MinFilter::Nearest) => (wgpu::FilterMode::Nearest, None),
+        };
+
         let texture_builder = texture_builder_from_image_bytes(texture_buffer)
             .expect("could not load")
-            .with_format(format);
+            .with_format(format)
+            .generate_mipmaps(mipmap_filter.is_some());
+
+        let sampler_builder = match mipmap_filter {
+            Some(filter) => SamplerBuilder::new().mip_map_filer(filter),
+            None => SamplerBuilder::new()
+                .mip_map_filer(wgpu::FilterMode::Nearest)
+                .lod_clamp(0.0, 0.0),
+        };
 
         let image = Image {
             texture: texture_builder,
-            sampler: SamplerBuilder::new()
+            sampler: sampler_builder
                 .min_mag_filter(
-                    sampler
-                        .min_filter()
-                        // TODO: handle mipmap filters
-                        .map_or(wgpu::FilterMode::Linear, |filter| match filter {
-                            gltf::texture::MinFilter::Nearest
-                            | gltf::texture::MinFilter::NearestMipmapLinear
-                            | gltf::texture::MinFilter::NearestMipmapNearest => {
-                                wgpu::FilterMode::Nearest
-                            }
-                            gltf::texture::MinFilter::Linear
-                            | gltf::texture::MinFilter::LinearMipmapNearest
-                            | gltf::texture::MinFilter::LinearMipmapLinear => {
-                                wgpu::FilterMode::Linear
-                            }
-                        }),
+                    min_filter,
                     sampler
                         .mag_filter()
                         .map_or(wgpu::FilterMode::Linear, |filter| match filter {
@@ -415,7 +448,7 @@ pub fn parse_gltf_material(
                 ),
         };
 
-        let handle = load_ctx.insert(image);
+        let handle = load_ctx.insert_labeled(&format!("Texture/{}", texture.index()), image);
         gltf_cache.images.insert(texture.index(), handle.clone());
         handle
     }
@@ -547,7 +580,7 @@ pub fn parse_gltf_material(
         None => single_pixel_image(load_ctx, gltf_cache, EMISSIVE_DEFAULT),
     };
 
-    let material_handle = load_ctx.insert(Material {
+    let material = Material {
         base_color_texture,
         color_factor,
         metallic_roughness_texture,
@@ -559,15 +592,317 @@ pub fn parse_gltf_material(
         normal_scale,
         emissive_texture,
         emissive_factor,
-    });
+    };
+    let material_handle = match material_index {
+        Some(index) => load_ctx.insert_labeled(&format!("Material/{index}"), material),
+        None => load_ctx.insert(material),
+    };
 
-    if let Some(index) = material.index() {
+    if let Some(index) = material_index {
         gltf_cache.materials.insert(index, material_handle.clone());
     }
 
     material_handle
 }
 
+fn primitive_topology(mode: gltf::mesh::Mode) -> Option<wgpu::PrimitiveTopology> {
+    match mode {
+        gltf::mesh::Mode::Points => Some(wgpu::PrimitiveTopology::PointList),
+        gltf::mesh::Mode::Lines => Some(wgpu::PrimitiveTopology::LineList),
+        gltf::mesh::Mode::LineStrip => Some(wgpu::PrimitiveTopology::LineStrip),
+        gltf::mesh::Mode::Triangles => Some(wgpu::PrimitiveTopology::TriangleList),
+        gltf::mesh::Mode::TriangleStrip => Some(wgpu::PrimitiveTopology::TriangleStrip),
+        _ => None,
+    }
+}
+
+//
+// Processing
+//
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(default)]
+pub struct GltfProcessSettings {
+    /// Generate tangents for triangles with normals and uvs but no tangents
+    pub tangents: bool,
+}
+
+impl Default for GltfProcessSettings {
+    fn default() -> Self {
+        Self { tangents: true }
+    }
+}
+
+/// Rewrites binary glTF so every primitive has tightly packed f32 attributes and u32 indices,
+/// the layout [`parse_gltf_file`] reads, and generates missing tangents
+///
+/// Attributes which are not loaded, e.g. joints and weights, are dropped
+#[derive(Clone, Default)]
+pub struct GltfProcessor {}
+
+impl AssetProcessor for GltfProcessor {
+    const VERSION: u32 = 1;
+    type Error = filesystem::LoadFileError;
+    type Settings = GltfProcessSettings;
+
+    fn process(
+        &self,
+        _ctx: &ProcessContext<'_>,
+        _path: &Path,
+        source: &[u8],
+        settings: &Self::Settings,
+    ) -> Result<Vec<u8>, Self::Error> {
+        let info = gltf::Gltf::from_slice(source)
+            .map_err(|err| filesystem::LoadFileError::Other(Box::new(err)))?;
+        if info.buffers().any(|buffer| {
+            buffer.index() > 0 || !matches!(buffer.source(), gltf::buffer::Source::Bin)
+        }) {
+            return Err(filesystem::LoadFileError::Other(
+                "only glTF with a single embedded buffer can be processed".into(),
+            ));
+        }
+
+        // new data is appended, existing views such as images stay valid
+        let blob = info.blob.as_deref();
+        let mut bin = blob.map(<[u8]>::to_vec).unwrap_or_default();
+        let mut root = info.document.clone().into_json();
+        for mesh in info.meshes() {
+            for primitive in mesh.primitives() {
+                let Some(mut extracted) = read_gltf_primitive(&primitive, blob) else {
+                    continue;
+                };
+                let can_generate_tangents = extracted.primitive_topology
+                    == wgpu::PrimitiveTopology::TriangleList
+                    && extracted.get_attribute(VertexAttributeId::Normal).is_some()
+                    && extracted.get_attribute(VertexAttributeId::Uv(0)).is_some();
+                if settings.tangents
+                    && can_generate_tangents
+                    && extracted
+                        .get_attribute(VertexAttributeId::Tangent)
+                        .is_none()
+                {
+                    extracted.generate_tangents();
+                }
+
+                let attributes = extracted
+                    .attributes
+                    .iter()
+                    .map(|(id, values)| {
+                        let accessor = push_gltf_attribute(&mut root, &mut bin, *id, values);
+                        (
+                            gltf::json::validation::Checked::Valid(gltf_semantic(*id)),
+                            accessor,
+                        )
+                    })
+                    .collect();
+                let indices = extracted.indices.as_ref().map(|indices| {
+                    push_gltf_accessor(
+                        &mut root,
+                        &mut bin,
+                        bytemuck::cast_slice(indices),
+                        indices.len(),
+                        gltf::json::accessor::ComponentType::U32,
+                        gltf::json::accessor::Type::Scalar,
+                        gltf::json::buffer::Target::ElementArrayBuffer,
+                    )
+                });
+
+                let json = &mut root.meshes[mesh.index()].primitives[primitive.index()];
+                json.attributes = attributes;
+                json.indices = indices;
+            }
+        }
+
+        match root.buffers.first_mut() {
+            Some(buffer) => buffer.byte_length = bin.len().into(),
+            None => {
+                root.push(gltf::json::Buffer {
+                    byte_length: bin.len().into(),
+                    name: None,
+                    uri: None,
+                    extensions: None,
+                    extras: Default::default(),
+                });
+            }
+        }
+        let json = root
+            .to_vec()
+            .map_err(|err| filesystem::LoadFileError::Other(Box::new(err)))?;
+        gltf::Glb {
+            header: gltf::binary::Header {
+                magic: *b"glTF",
+                version: 2,
+                length: 0,
+            },
+            json: Cow::Owned(json),
+            bin: Some(Cow::Owned(bin)),
+        }
+        .to_vec()
+        .map_err(|err| filesystem::LoadFileError::Other(Box::new(err)))
+    }
+}
+
+// attributes the loader supports, converted to its formats
+fn read_gltf_primitive(primitive: &gltf::Primitive<'_>, blob: Option<&[u8]>) -> Option<Mesh> {
+    let reader = primitive.reader(|buffer| match buffer.source() {
+        gltf::buffer::Source::Bin => blob,
+        gltf::buffer::Source::Uri(_) => None,
+    });
+    let mut mesh = Mesh::new(primitive_topology(primitive.mode())?);
+
+    let positions = reader.read_positions()?.collect();
+    mesh.set_attribute(
+        VertexAttributeId::Position,
+        render::VertexAttributeValues::Float32x3(positions),
+    );
+    if let Some(normals) = reader.read_normals() {
+        mesh.set_attribute(
+            VertexAttributeId::Normal,
+            render::VertexAttributeValues::Float32x3(normals.collect()),
+        );
+    }
+    if let Some(tangents) = reader.read_tangents() {
+        mesh.set_attribute(
+            VertexAttributeId::Tangent,
+            render::VertexAttributeValues::Float32x4(tangents.collect()),
+        );
+    }
+    for set in 0.. {
+        let Some(uvs) = reader.read_tex_coords(set) else {
+            break;
+        };
+        mesh.set_attribute(
+            VertexAttributeId::Uv(set),
+            render::VertexAttributeValues::Float32x2(uvs.into_f32().collect()),
+        );
+    }
+    for set in 0.. {
+        let Some(colors) = reader.read_colors(set) else {
+            break;
+        };
+        mesh.set_attribute(
+            VertexAttributeId::Color(set),
+            render::VertexAttributeValues::Float32x3(colors.into_rgb_f32().collect()),
+        );
+    }
+    if let Some(indices) = reader.read_indices() {
+        mesh.set_indices(indices.into_u32().collect());
+    }
+
+    Some(mesh)
+}
+
+fn gltf_semantic(id: VertexAttributeId) -> gltf::Semantic {
+    match id {
+        VertexAttributeId::Position => gltf::Semantic::Positions,
+        VertexAttributeId::Normal => gltf::Semantic::Normals,
+        VertexAttributeId::Tangent => gltf::Semantic::Tangents,
+        VertexAttributeId::Uv(set) => gltf::Semantic::TexCoords(set),
+        VertexAttributeId::Color(set) => gltf::Semantic::Colors(set),
+    }
+}
+
+fn push_gltf_attribute(
+    root: &mut gltf::json::Root,
+    bin: &mut Vec<u8>,
+    id: VertexAttributeId,
+    values: &render::VertexAttributeValues,
+) -> gltf::json::Index<gltf::json::Accessor> {
+    let type_ = match id.format() {
+        wgpu::VertexFormat::Float32x2 => gltf::json::accessor::Type::Vec2,
+        wgpu::VertexFormat::Float32x3 => gltf::json::accessor::Type::Vec3,
+        _ => gltf::json::accessor::Type::Vec4,
+    };
+    let accessor = push_gltf_accessor(
+        root,
+        bin,
+        values.as_bytes(),
+        values.len(),
+        gltf::json::accessor::ComponentType::F32,
+        type_,
+        gltf::json::buffer::Target::ArrayBuffer,
+    );
+
+    // positions need their bounds
+    if let render::VertexAttributeValues::Float32x3(positions) = values {
+        if id == VertexAttributeId::Position {
+            let (mut min, mut max) = (Vec3::splat(f32::MAX), Vec3::splat(f32::MIN));
+            for position in positions {
+                min = min.min(Vec3::from(*position));
+                max = max.max(Vec3::from(*position));
+            }
+            let json = &mut root.accessors[accessor.value()];
+            json.min = Some(gltf::json::Value::from(min.to_array().to_vec()));
+            json.max = Some(gltf::json::Value::from(max.to_array().to_vec()));
+        }
+    }
+
+    accessor
+}
+
+fn push_gltf_accessor(
+    root: &mut gltf::json::Root,
+    bin: &mut Vec<u8>,
+    bytes: &[u8],
+    count: usize,
+    component_type: gltf::json::accessor::ComponentType,
+    type_: gltf::json::accessor::Type,
+    target: gltf::json::buffer::Target,
+) -> gltf::json::Index<gltf::json::Accessor> {
+    // every component type used is 4 bytes
+    bin.resize(bin.len().next_multiple_of(4), 0);
+    let view = root.push(gltf::json::buffer::View {
+        buffer: gltf::json::Index::new(0),
+        byte_length: bytes.len().into(),
+        byte_offset: Some(bin.len().into()),
+        byte_stride: None,
+        name: None,
+        target: Some(gltf::json::validation::Checked::Valid(target)),
+        extensions: None,
+        extras: Default::default(),
+    });
+    bin.extend_from_slice(bytes);
+
+    root.push(gltf::json::Accessor {
+        buffer_view: Some(view),
+        byte_offset: None,
+        count: count.into(),
+        component_type: gltf::json::validation::Checked::Valid(
+            gltf::json::accessor::GenericComponentType(component_type),
+        ),
+        extensions: None,
+        extras: Default::default(),
+        type_: gltf::json::validation::Checked::Valid(type_),
+        min: None,
+        max: None,
+        name: None,
+        normalized: false,
+        sparse: None,
+    })
+}
+
+/// Names occurring exactly once
+fn unique_names<'a>(names: impl Iterator<Item = Option<&'a str>>) -> HashSet<Box<str>> {
+    let mut counts = HashMap::<&str, usize>::new();
+    for name in names.flatten() {
+        *counts.entry(name).or_default() += 1;
+    }
+    counts
+        .into_iter()
+        .filter(|(_, count)| *count == 1)
+        .map(|(name, _)| Box::from(name))
+        .collect()
+}
+
+/// Alias label of a uniquely named sub asset, e.g. `Mesh/Hull` next to `Mesh/0`
+///
+/// Numeric names would shadow the index label of another sub asset and get no alias
+fn gltf_name_label(kind: &str, unique_name: Option<&str>) -> Option<String> {
+    let name = unique_name?;
+    let numeric = name.bytes().all(|byte| byte.is_ascii_digit());
+    (!numeric).then(|| format!("{kind}/{name}"))
+}
+
 impl Asset for Gltf {}
 
 impl Asset for GltfMesh {}
@@ -659,3 +994,131 @@ impl Material {
         self
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::{
+        gltf_name_label, push_gltf_accessor, push_gltf_attribute, unique_names, GltfProcessor,
+    };
+    use gbase::{
+        asset::{AssetProcessor, ProcessContext},
+        filesystem,
+        render::{VertexAttributeId, VertexAttributeValues},
+    };
+    use std::{borrow::Cow, collections::BTreeMap, path::Path};
+
+    #[test]
+    fn test_duplicate_names() {
+        let names = [Some("Cube"), Some("Cube"), Some("Ship"), Some("3"), None];
+        let unique = unique_names(names.into_iter());
+        assert!(!unique.contains("Cube"));
+        assert!(unique.contains("Ship"));
+
+        let labels = names
+            .iter()
+            .map(|name| gltf_name_label("Node", name.filter(|name| unique.contains(*name))))
+            .collect::<Vec<_>>();
+        // duplicates and numeric names only get their index label
+        assert_eq!(
+            labels,
+            [None, None, Some(String::from("Node/Ship")), None, None]
+        );
+    }
+
+    #[test]
+    fn test_process_gltf() {
+        // triangle with u16 indices and no tangents
+        let mut root = gltf::json::Root::default();
+        let mut bin = Vec::new();
+        let mut attributes = BTreeMap::new();
+        for (semantic, id, values) in [
+            (
+                gltf::Semantic::Positions,
+                VertexAttributeId::Position,
+                VertexAttributeValues::Float32x3(vec![
+                    [0.0, 0.0, 0.0],
+                    [1.0, 0.0, 0.0],
+                    [0.0, 1.0, 0.0],
+                ]),
+            ),
+            (
+                gltf::Semantic::Normals,
+                VertexAttributeId::Normal,
+                VertexAttributeValues::Float32x3(vec![[0.0, 0.0, 1.0]; 3]),
+            ),
+            (
+                gltf::Semantic::TexCoords(0),
+                VertexAttributeId::Uv(0),
+                VertexAttributeValues::Float32x2(vec![[0.0, 0.0], [1.0, 0.0], [0.0, 1.0]]),
+            ),
+        ] {
+            let accessor = push_gltf_attribute(&mut root, &mut bin, id, &values);
+            attributes.insert(gltf::json::validation::Checked::Valid(semantic), accessor);
+        }
+        let indices = push_gltf_accessor(
+            &mut root,
+            &mut bin,
+            bytemuck::cast_slice(&[0u16, 1, 2]),
+            3,
+            gltf::json::accessor::ComponentType::U16,
+            gltf::json::accessor::Type::Scalar,
+            gltf::json::buffer::Target::ElementArrayBuffer,
+        );
+        root.push(gltf::json::Mesh {
+            extensions: None,
+            extras: Default::default(),
+            name: None,
+            primitives: vec![gltf::json::mesh::Primitive {
+                attributes,
+                extensions: None,
+                extras: Default::default(),
+                indices: Some(indices),
+                material: None,
+                mode: gltf::json::validation::Checked::Valid(gltf::mesh::Mode::Triangles),
+                targets: None,
+            }],
+            weights: None,
+        });
+        root.push(gltf::json::Buffer {
+            byte_length: bin.len().into(),
+            name: None,
+            uri: None,
+            extensions: None,
+            extras: Default::default(),
+        });
+        let source = gltf::Glb {
+            header: gltf::binary::Header {
+                magic: *b"glTF",
+                version: 2,
+                length: 0,
+            },
+            json: Cow::Owned(root.to_vec().unwrap()),
+            bin: Some(Cow::Owned(bin)),
+        }
+        .to_vec()
+        .unwrap();
+
+        let read = |_: &Path| Err(filesystem::LoadFileError::FileNotFound);
+        let processed = GltfProcessor::default()
+            .process(
+                &ProcessContext::new(&read),
+                Path::new("triangle.glb"),
+                &source,
+                &Default::default(),
+            )
+            .unwrap();
+
+        let info = gltf::Gltf::from_slice(&processed).unwrap();
+        let blob = info.blob.as_deref();
+        let primitive = info.meshes().next().unwrap().primitives().next().unwrap();
+        let reader = primitive.reader(|_| blob);
+        let tangents = reader.read_tangents().unwrap().collect::<Vec<_>>();
+        assert_eq!(tangents, vec![[1.0, 0.0, 0.0, 1.0]; 3]);
+
+        // indices are widened to u32 in their own view
+        let indices = primitive.indices().unwrap();
+        assert_eq!(indices.data_type(), gltf::accessor::DataType::U32);
+        let view = indices.view().unwrap();
+        assert_eq!((view.length(), view.stride()), (12, None));
+    }
+}
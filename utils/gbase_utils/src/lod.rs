@@ -8,6 +8,7 @@ use gbase::{
     render::{self, BoundingBox, VertexAttributeId},
     tracing,
 };
+use serde::{Deserialize, Serialize};
 use std::{collections::BTreeSet, ops::Deref};
 
 #[derive(Debug, Clone)]
@@ -42,7 +43,14 @@ impl MeshLod {
 
 impl Asset for MeshLod {}
 
-#[derive(Clone)]
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+#[serde(default)]
+pub struct MeshLodSettings {
+    /// Other attributes are removed, missing ones are generated if possible
+    pub required_attributes: Option<BTreeSet<VertexAttributeId>>,
+}
+
+#[derive(Clone, Hash, Default)]
 pub struct MeshLodLoader {
     node_name: Option<String>,
     required_attributes: Option<BTreeSet<VertexAttributeId>>,
@@ -70,15 +78,23 @@ impl MeshLodLoader {
 impl AssetLoader for MeshLodLoader {
     type Asset = MeshLod;
     type Error = filesystem::LoadFileError;
+    type Settings = MeshLodSettings;
+
+    fn settings(&self) -> Self::Settings {
+        MeshLodSettings {
+            required_attributes: self.required_attributes.clone(),
+        }
+    }
 
     async fn load(
         &self,
         load_ctx: LoadContext,
         path: &std::path::Path,
+        settings: &Self::Settings,
     ) -> Result<Self::Asset, Self::Error> {
-        let bytes = load_ctx.load_bytes(path).await?;
+        let bytes = load_ctx.load_processed(path).await?;
         let primitives =
-            parse_gltf_primitives(&load_ctx, &bytes, self.required_attributes.as_ref());
+            parse_gltf_primitives(&load_ctx, &bytes, settings.required_attributes.as_ref());
 
         // extract material from LOD0
         let material = primitives[0].material.clone(); // TODO: using material of LOD0 currently
@@ -123,19 +139,24 @@ impl AssetWriter for MeshLodLoader {
     }
 }
 
-#[derive(Clone)]
+/// Sub assets can be loaded by label, e.g. `ship.glb#Mesh/Hull`
+///
+/// Nodes and meshes are labeled by name or index, materials and textures by index
+#[derive(Clone, Hash, Default)]
 pub struct GltfLoader {}
 
 impl AssetLoader for GltfLoader {
     type Asset = Gltf;
     type Error = filesystem::LoadFileError;
+    type Settings = ();
 
     async fn load(
         &self,
         load_ctx: LoadContext,
         path: &std::path::Path,
+        _settings: &Self::Settings,
     ) -> Result<Self::Asset, Self::Error> {
-        let bytes = load_ctx.load_bytes(path).await?;
+        let bytes = load_ctx.load_processed(path).await?;
         Ok(parse_gltf_file(&load_ctx, &bytes))
     }
 }
@@ -28,6 +28,8 @@ pub struct PbrRenderer {
 
     // TODO: use storagebuffer to avoid manual padding
     instances: RawBuffer<Instance>,
+
+    sample_count: u32,
 }
 
 impl PbrRenderer {
@@ -160,9 +162,17 @@ impl PbrRenderer {
             bindgroup_layout,
             vertex_attributes,
             instances,
+            sample_count: 1,
         }
     }
 
+    /// Samples per pixel of the view and depth buffer, defaults to 1
+    ///
+    /// Use [`render::msaa_samples`] to draw to the screen with MSAA
+    pub fn set_msaa(&mut self, sample_count: u32) {
+        self.sample_count = sample_count;
+    }
+
     #[allow(clippy::too_many_arguments)]
     pub fn render(
         &mut self,
@@ -214,6 +224,7 @@ impl PbrRenderer {
             .single_target(render::ColorTargetState::new().format(view_format))
             .cull_mode(wgpu::Face::Back)
             .depth_stencil(depth_buffer.depth_stencil_state())
+            .sample_count(self.sample_count)
             .build(ctx);
 
         let mut instances = Vec::new();
@@ -388,7 +399,10 @@ impl PbrRenderer {
         // TODO: using one render pass per draw call
         render::RenderPassBuilder::new()
             .label("pbr")
-            .color_attachments(&[Some(render::RenderPassColorAttachment::new(view))])
+            .color_attachments(&[Some(match self.sample_count > 1 {
+                true => render::RenderPassColorAttachment::new(view).msaa_screen(),
+                false => render::RenderPassColorAttachment::new(view),
+            })])
             .trace_gpu("pbr")
             .depth_stencil_attachment(depth_buffer.depth_render_attachment_load())
             .build_run(ctx, &mut encoder, |_ctx, mut pass| {